@@ -1,8 +1,31 @@
 use serde::{Deserialize, Serialize};
-use std::process::Command;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::fs;
-use crate::config::Config;
-use crate::services::openai::{transcribe_audio, TranscriptionResponse};
+use tauri::{AppHandle, Emitter, Manager, State};
+use crate::config::{Config, TranscriptionSettings};
+use crate::error::NolanForgeError;
+use crate::services::autosave::{self, ProjectChange, RecoverableSession, RecoveredSession};
+use crate::services::chunking;
+use crate::services::composition::{BlendMode, Canvas, FitMode};
+use crate::services::encoding::{self, EncodingProfile};
+use crate::services::ffmpeg::ProgressReporter;
+use crate::services::jobs::{JobGuard, JobRegistry, EXPORT_STARTED_EVENT};
+use crate::services::transcription::{
+    rebase, AudioFormat, Provider, SourceRange, TranscriptTimebase, TranscriptionProgress, TranscriptionProvider,
+    TranscriptionResponse, TranscriptionStarted, TRANSCRIPTION_PROGRESS_EVENT, TRANSCRIPTION_STARTED_EVENT,
+};
+use crate::services::preflight::{self, ExportKind, PreflightReport};
+use crate::services::probe::{self, MediaInfo};
+use crate::services::project::{self, LoadedProject, MediaReference, ProjectDocument};
+use crate::services::proxy::{self, ProxyEntry, ProxyFinished, PROXY_FINISHED_EVENT};
+use crate::services::relink::{self, Relink, RelinkSuggestion};
+use crate::services::thumbnails::{self, Thumbnail, ThumbnailRequest};
+use crate::services::timeline::{self, Clip as TimelineClip, ClipAudio, TimeRange, TimelineDocument, Track, Transform};
+use crate::services::toolchain::{CapabilityReport, Ffmpeg, FfmpegState};
+use crate::services::trim::{trim_video, TrimMode, TrimResult};
+use crate::services::waveform::{self, WaveformPeaks};
+use crate::services::workspace::Workspace;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ExportOptions {
@@ -10,6 +33,10 @@ pub struct ExportOptions {
     pub output_path: String,
     pub trim_start: f64,
     pub trim_end: f64,
+    #[serde(default)]
+    pub trim_mode: TrimMode,
+    #[serde(default)]
+    pub profile: Option<EncodingProfile>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -30,7 +57,13 @@ pub struct MultiTrackClip {
     pub overlay_position: Option<(f64, f64)>,
     pub overlay_size: Option<(f64, f64)>,
     pub overlay_opacity: Option<f64>,
-    pub overlay_blend_mode: Option<String>,
+    pub overlay_blend_mode: Option<BlendMode>,
+    /// Linear volume multiplier for the clip's audio, 1.0 when unset.
+    #[serde(default)]
+    pub gain: Option<f64>,
+    /// How a main-track clip is placed on the canvas.
+    #[serde(default)]
+    pub fit_mode: FitMode,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -39,567 +72,614 @@ pub struct MultiTrackExportOptions {
     pub output_path: String,
     pub global_trim_start: f64,
     pub global_trim_end: f64,
+    #[serde(default)]
+    pub profile: Option<EncodingProfile>,
+    #[serde(default)]
+    pub canvas: Canvas,
+    /// Mute and solo state of tracks; tracks not listed play normally.
+    #[serde(default)]
+    pub track_settings: Vec<TrackAudioSettings>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrackAudioSettings {
+    pub track_id: i32,
+    #[serde(default)]
+    pub muted: bool,
+    #[serde(default)]
+    pub solo: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportStarted {
+    pub job_id: String,
+}
+
+/// Registers an export job and tells the frontend its ID so it can be
+/// cancelled.
+fn start_export_job(app: &AppHandle, jobs: &JobRegistry, output_path: &str) -> JobGuard {
+    let job = jobs.start();
+    // A cancelled export must not leave a half-written file behind
+    job.track_path(output_path);
+
+    let started = ExportStarted { job_id: job.id().to_string() };
+    if let Err(e) = app.emit(EXPORT_STARTED_EVENT, started) {
+        eprintln!("Warning: Failed to emit export start: {}", e);
+    }
+
+    job
+}
+
+#[tauri::command]
+pub fn cancel_export(jobs: State<'_, JobRegistry>, job_id: String) -> Result<String, NolanForgeError> {
+    jobs.cancel(&job_id)?;
+    Ok(format!("Cancellation requested for job {}", job_id))
 }
 
 #[tauri::command]
-pub async fn export_trimmed_video(options: ExportOptions) -> Result<String, String> {
+pub async fn export_trimmed_video(
+    app: AppHandle,
+    jobs: State<'_, JobRegistry>,
+    ffmpeg: State<'_, FfmpegState>,
+    options: ExportOptions,
+) -> Result<TrimResult, NolanForgeError> {
     // Validate inputs
     if options.trim_start < 0.0 || options.trim_end <= options.trim_start {
-        return Err("Invalid trim range".to_string());
+        return Err(NolanForgeError::InvalidInput("Invalid trim range".to_string()));
     }
 
+    let profile = options.profile.clone().unwrap_or_default();
+    profile.validate()?;
+
+    let input = proxy::resolve_original(&options.input_path);
+    relink::ensure_present([input.as_str()])?;
+    let ffmpeg = ffmpeg.get().await?;
+    let input_path = Path::new(&input);
+    probe::basic_info(&ffmpeg, input_path)
+        .await?
+        .check_range(input_path, options.trim_start, options.trim_end)?;
+
     let duration = options.trim_end - options.trim_start;
+    let job = start_export_job(&app, &jobs, &options.output_path);
+    let mut reporter = ProgressReporter::new(&app, &job, duration * options.trim_mode.work_factor());
+    let workspace = Workspace::create("trim", job.id())?;
+
+    let result = trim_video(
+        &ffmpeg,
+        &mut reporter,
+        &workspace,
+        input_path,
+        Path::new(&options.output_path),
+        options.trim_start,
+        options.trim_end,
+        options.trim_mode,
+        &profile,
+    )
+    .await?;
+
+    println!(
+        "🔍 Debug: {:?} trim requested {}s-{}s, achieved {}s-{}s",
+        result.mode, result.requested_start, result.requested_end, result.actual_start, result.actual_end
+    );
+
+    reporter.finish("Export complete");
+    Ok(result)
+}
 
-    // Build FFmpeg command
-    // Note: This assumes FFmpeg is installed and available in PATH
-    // For production, you might want to bundle FFmpeg with the app
-    let output = Command::new("ffmpeg")
-        .arg("-ss")
-        .arg(options.trim_start.to_string())
-        .arg("-i")
-        .arg(&options.input_path)
-        .arg("-t")
-        .arg(duration.to_string())
-        .arg("-c")
-        .arg("copy") // Use stream copy for fast encoding
-        .arg("-y") // Overwrite output file
-        .arg(&options.output_path)
-        .output();
+#[tauri::command]
+pub async fn probe_media(ffmpeg: State<'_, FfmpegState>, path: String) -> Result<MediaInfo, NolanForgeError> {
+    let ffmpeg = ffmpeg.get().await?;
+    probe::probe_media(&ffmpeg, Path::new(&path)).await
+}
 
-    match output {
-        Ok(result) => {
-            if result.status.success() {
-                Ok(format!("Video exported successfully to: {}", options.output_path))
-            } else {
-                let error_msg = String::from_utf8_lossy(&result.stderr);
-                Err(format!("FFmpeg error: {}", error_msg))
-            }
+#[tauri::command]
+pub async fn generate_thumbnails(
+    ffmpeg: State<'_, FfmpegState>,
+    options: ThumbnailRequest,
+) -> Result<Vec<Thumbnail>, NolanForgeError> {
+    let ffmpeg = ffmpeg.get().await?;
+    ffmpeg.require("thumbnails").await?;
+    thumbnails::generate_thumbnails(&ffmpeg, &options).await
+}
+
+/// Result of `generate_proxy`: either an up-to-date proxy already exists, or
+/// a background job has been started to make one.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum ProxyRequest {
+    Ready { proxy: ProxyEntry },
+    Started { job_id: String },
+}
+
+/// Starts transcoding a proxy of `path` in the background. Progress arrives
+/// as `proxy-progress` events and the result as a `proxy-finished` event.
+#[tauri::command]
+pub async fn generate_proxy(
+    app: AppHandle,
+    jobs: State<'_, JobRegistry>,
+    ffmpeg: State<'_, FfmpegState>,
+    path: String,
+    height: Option<u32>,
+) -> Result<ProxyRequest, NolanForgeError> {
+    if let Some(existing) = proxy::find_proxy(Path::new(&path))? {
+        if height.is_none_or(|h| h == existing.height) {
+            return Ok(ProxyRequest::Ready { proxy: existing });
         }
-        Err(e) => Err(format!(
-            "Failed to execute FFmpeg. Make sure FFmpeg is installed and in your PATH. Error: {}",
-            e
-        )),
     }
+
+    let ffmpeg = ffmpeg.get().await?;
+    ffmpeg.require("proxies").await?;
+    let job = jobs.start();
+    let job_id = job.id().to_string();
+    tauri::async_runtime::spawn(async move {
+        let height = height.unwrap_or(proxy::DEFAULT_PROXY_HEIGHT);
+        let result = proxy::generate_proxy(&ffmpeg, &app, &job, Path::new(&path), height).await;
+        if let Err(e) = &result {
+            eprintln!("Proxy generation for {} failed: {}", path, e);
+        }
+
+        let finished = ProxyFinished {
+            job_id: job.id().to_string(),
+            original_path: path,
+            proxy: result.as_ref().ok().cloned(),
+            error: result.err(),
+        };
+        if let Err(e) = app.emit(PROXY_FINISHED_EVENT, finished) {
+            eprintln!("Warning: Failed to emit proxy result: {}", e);
+        }
+    });
+
+    Ok(ProxyRequest::Started { job_id })
 }
 
 #[tauri::command]
-pub fn check_ffmpeg() -> Result<String, String> {
-    let output = Command::new("ffmpeg").arg("-version").output();
+pub fn cancel_proxy(jobs: State<'_, JobRegistry>, job_id: String) -> Result<String, NolanForgeError> {
+    jobs.cancel(&job_id)?;
+    Ok(format!("Cancellation requested for proxy job {}", job_id))
+}
 
-    match output {
-        Ok(result) => {
-            if result.status.success() {
-                let version = String::from_utf8_lossy(&result.stdout);
-                let first_line = version.lines().next().unwrap_or("Unknown version");
-                Ok(first_line.to_string())
-            } else {
-                Err("FFmpeg found but returned an error".to_string())
-            }
-        }
-        Err(_) => Err("FFmpeg not found. Please install FFmpeg and add it to your PATH.".to_string()),
-    }
+#[tauri::command]
+pub fn list_proxies() -> Result<Vec<ProxyEntry>, NolanForgeError> {
+    proxy::list_proxies()
 }
 
 #[tauri::command]
-pub async fn export_multi_clip_video(clips: Vec<ClipData>, output_path: String) -> Result<String, String> {
-    // Validate inputs
-    if clips.is_empty() {
-        return Err("No clips provided".to_string());
-    }
+pub fn get_proxy(path: String) -> Result<Option<ProxyEntry>, NolanForgeError> {
+    proxy::find_proxy(Path::new(&path))
+}
 
-    // Create a temporary directory for intermediate files
-    let temp_dir = std::env::temp_dir().join("nolanforge_export");
-    fs::create_dir_all(&temp_dir).map_err(|e| format!("Failed to create temp directory: {}", e))?;
+#[tauri::command]
+pub fn delete_proxy(path: String) -> Result<String, NolanForgeError> {
+    proxy::delete_proxy(Path::new(&path))?;
+    Ok(format!("Proxy of {} deleted", path))
+}
 
-    // Step 1: Trim each clip individually
-    let mut trimmed_clips = Vec::new();
-    for (i, clip) in clips.iter().enumerate() {
-        let duration = clip.trim_end - clip.trim_start;
-        if duration <= 0.0 {
-            return Err(format!("Invalid trim range for clip {}", i));
-        }
+/// Saves the project and returns the path it was written to.
+#[tauri::command]
+pub fn save_project(path: String, project: ProjectDocument) -> Result<String, NolanForgeError> {
+    let written = project::save_project(Path::new(&path), &project)?;
+    Ok(written.to_string_lossy().to_string())
+}
 
-        let temp_output = temp_dir.join(format!("clip_{}.mp4", i));
-        
-        // Trim the clip
-        let output = Command::new("ffmpeg")
-            .arg("-ss")
-            .arg(clip.trim_start.to_string())
-            .arg("-i")
-            .arg(&clip.input_path)
-            .arg("-t")
-            .arg(duration.to_string())
-            .arg("-c")
-            .arg("copy")
-            .arg("-y")
-            .arg(&temp_output)
-            .output();
-
-        match output {
-            Ok(result) => {
-                if !result.status.success() {
-                    let error_msg = String::from_utf8_lossy(&result.stderr);
-                    return Err(format!("FFmpeg error trimming clip {}: {}", i, error_msg));
-                }
-            }
-            Err(e) => {
-                return Err(format!("Failed to execute FFmpeg for clip {}: {}", i, e));
-            }
-        }
+#[tauri::command]
+pub fn load_project(app: AppHandle, path: String) -> Result<LoadedProject, NolanForgeError> {
+    let loaded = project::load_project(Path::new(&path))?;
+    allow_media_playback(&app, &loaded.project);
+    Ok(loaded)
+}
 
-        trimmed_clips.push(temp_output);
+/// Media of a reopened project has no blob URL, so let the webview play the
+/// files directly.
+fn allow_media_playback(app: &AppHandle, project: &ProjectDocument) {
+    let scope = app.asset_protocol_scope();
+    for media in &project.media {
+        if let Err(e) = scope.allow_file(&media.path) {
+            eprintln!("Warning: Failed to allow playback of {}: {}", media.path, e);
+        }
     }
+}
 
-    // Step 2: Create concat file list
-    let concat_file = temp_dir.join("concat_list.txt");
-    let mut concat_content = String::new();
-    for clip_path in &trimmed_clips {
-        concat_content.push_str(&format!("file '{}'\n", clip_path.display()));
-    }
-    
-    fs::write(&concat_file, concat_content)
-        .map_err(|e| format!("Failed to write concat file: {}", e))?;
-
-    // Step 3: Concatenate all clips
-    let output = Command::new("ffmpeg")
-        .arg("-f")
-        .arg("concat")
-        .arg("-safe")
-        .arg("0")
-        .arg("-i")
-        .arg(&concat_file)
-        .arg("-c")
-        .arg("copy")
-        .arg("-y")
-        .arg(&output_path)
-        .output();
+#[tauri::command]
+pub fn find_missing_media(media: Vec<MediaReference>) -> Vec<MediaReference> {
+    relink::find_missing(media)
+}
 
-    // Clean up temp files
-    let cleanup_result = fs::remove_dir_all(&temp_dir);
-    if let Err(e) = cleanup_result {
-        eprintln!("Warning: Failed to clean up temp directory: {}", e);
-    }
+#[tauri::command]
+pub async fn search_relink_candidates(
+    ffmpeg: State<'_, FfmpegState>,
+    media: Vec<MediaReference>,
+    directories: Vec<String>,
+) -> Result<Vec<RelinkSuggestion>, NolanForgeError> {
+    let directories: Vec<PathBuf> = directories.into_iter().map(PathBuf::from).collect();
+    let ffmpeg = ffmpeg.get().await?;
+    relink::search_candidates(&ffmpeg, &media, &directories).await
+}
 
-    match output {
-        Ok(result) => {
-            if result.status.success() {
-                Ok(format!("Multi-clip video exported successfully to: {}", output_path))
-            } else {
-                let error_msg = String::from_utf8_lossy(&result.stderr);
-                Err(format!("FFmpeg concatenation error: {}", error_msg))
-            }
+#[tauri::command]
+pub fn relink_media(
+    app: AppHandle,
+    media: Vec<MediaReference>,
+    relinks: Vec<Relink>,
+) -> Result<Vec<MediaReference>, NolanForgeError> {
+    let media = relink::apply_relinks(media, &relinks)?;
+    let scope = app.asset_protocol_scope();
+    for relink in &relinks {
+        if let Err(e) = scope.allow_file(&relink.path) {
+            eprintln!("Warning: Failed to allow playback of {}: {}", relink.path, e);
         }
-        Err(e) => Err(format!(
-            "Failed to execute FFmpeg concatenation. Error: {}",
-            e
-        )),
     }
+    Ok(media)
 }
 
 #[tauri::command]
-pub async fn export_multi_track_video(options: MultiTrackExportOptions) -> Result<String, String> {
-    // Validate inputs
-    if options.clips.is_empty() {
-        return Err("No clips provided".to_string());
-    }
+pub fn autosave_snapshot(project: ProjectDocument, project_path: Option<String>) -> Result<(), NolanForgeError> {
+    autosave::snapshot(project, project_path)
+}
 
-    if options.global_trim_start < 0.0 || options.global_trim_end <= options.global_trim_start {
-        return Err("Invalid global trim range".to_string());
-    }
+#[tauri::command]
+pub fn autosave_record(changes: Vec<ProjectChange>) -> Result<(), NolanForgeError> {
+    autosave::record(changes)
+}
 
-    // Create a temporary directory for intermediate files
-    let temp_dir = std::env::temp_dir().join("nolanforge_multitrack_export");
-    fs::create_dir_all(&temp_dir).map_err(|e| format!("Failed to create temp directory: {}", e))?;
+#[tauri::command]
+pub fn clear_autosave() -> Result<(), NolanForgeError> {
+    autosave::clear()
+}
 
-    // Separate main track clips (track 0) from overlay clips (track 1+)
-    let main_track_clips: Vec<_> = options.clips.iter().filter(|clip| clip.track_id == 0).collect();
-    let overlay_clips: Vec<_> = options.clips.iter().filter(|clip| clip.track_id > 0).collect();
+#[tauri::command]
+pub fn list_recoverable_sessions() -> Result<Vec<RecoverableSession>, NolanForgeError> {
+    autosave::list_recoverable_sessions()
+}
 
-    if main_track_clips.is_empty() {
-        return Err("No main track clips found (track 0)".to_string());
-    }
+#[tauri::command]
+pub fn recover_session(app: AppHandle, session_id: String) -> Result<RecoveredSession, NolanForgeError> {
+    let recovered = autosave::recover_session(&session_id)?;
+    allow_media_playback(&app, &recovered.project);
+    Ok(recovered)
+}
 
-    // Step 1: Create the main video track with audio
-    let main_video_path = temp_dir.join("main_video.mp4");
-    let main_audio_path = temp_dir.join("main_audio.mp3");
-
-    println!("🔍 Debug: Processing {} main track clips", main_track_clips.len());
-
-    // Sort main track clips by start time
-    let mut sorted_main_clips = main_track_clips.clone();
-    sorted_main_clips.sort_by(|a, b| a.start_time.partial_cmp(&b.start_time).unwrap());
-
-    // Calculate total duration and create a single video with proper timing
-    let total_duration = options.global_trim_end - options.global_trim_start;
-    
-    // Create a black background video for the full duration
-    let background_path = temp_dir.join("background.mp4");
-    let output = Command::new("ffmpeg")
-        .arg("-f")
-        .arg("lavfi")
-        .arg("-i")
-        .arg("color=black:size=1920x1080:duration=0.1") // Create a short black video
-        .arg("-vf")
-        .arg(&format!("scale=1920:1080,loop=loop=-1:size=1:start=0"))
-        .arg("-t")
-        .arg(total_duration.to_string())
-        .arg("-c:v")
-        .arg("libx264")
-        .arg("-y")
-        .arg(&background_path)
-        .output();
+#[tauri::command]
+pub fn discard_session(session_id: String) -> Result<(), NolanForgeError> {
+    autosave::discard_session(&session_id)
+}
 
-    match output {
-        Ok(result) => {
-            if !result.status.success() {
-                let error_msg = String::from_utf8_lossy(&result.stderr);
-                return Err(format!("FFmpeg error creating background: {}", error_msg));
-            }
-        }
-        Err(e) => {
-            return Err(format!("Failed to execute FFmpeg for background: {}", e));
-        }
+#[tauri::command]
+pub async fn get_waveform_peaks(
+    ffmpeg: State<'_, FfmpegState>,
+    path: String,
+    peaks_per_second: Option<f64>,
+) -> Result<WaveformPeaks, NolanForgeError> {
+    let ffmpeg = ffmpeg.get().await?;
+    ffmpeg.require("waveforms").await?;
+    waveform::waveform_peaks(&ffmpeg, Path::new(&path), peaks_per_second).await
+}
+
+/// The version line of the FFmpeg in use, looking for one on first call.
+#[tauri::command]
+pub async fn check_ffmpeg(ffmpeg: State<'_, FfmpegState>) -> Result<String, NolanForgeError> {
+    Ok(ffmpeg.get().await?.version.clone())
+}
+
+/// Which FFmpeg is in use and where it was found.
+#[tauri::command]
+pub async fn get_ffmpeg_info(ffmpeg: State<'_, FfmpegState>) -> Result<Arc<Ffmpeg>, NolanForgeError> {
+    ffmpeg.get().await
+}
+
+/// Uses the FFmpeg binary (or the folder containing it) at `path`, or goes
+/// back to looking for one when `path` is empty.
+#[tauri::command]
+pub async fn set_ffmpeg_path(
+    ffmpeg: State<'_, FfmpegState>,
+    path: Option<String>,
+) -> Result<Arc<Ffmpeg>, NolanForgeError> {
+    let path = path.map(|path| path.trim().to_string()).filter(|path| !path.is_empty());
+    ffmpeg.configure(path.map(PathBuf::from)).await
+}
+
+/// What the FFmpeg in use can do, and which NolanForge features that makes
+/// available.
+#[tauri::command]
+pub async fn ffmpeg_capabilities(ffmpeg: State<'_, FfmpegState>) -> Result<CapabilityReport, NolanForgeError> {
+    ffmpeg.get().await?.capability_report().await
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenderTimelineOptions {
+    pub timeline: TimelineDocument,
+    pub output_path: String,
+    #[serde(default)]
+    pub profile: Option<EncodingProfile>,
+}
+
+#[tauri::command]
+pub async fn render_timeline(
+    app: AppHandle,
+    jobs: State<'_, JobRegistry>,
+    ffmpeg: State<'_, FfmpegState>,
+    mut options: RenderTimelineOptions,
+) -> Result<String, NolanForgeError> {
+    // Edits may reference proxies, exports always read the originals
+    proxy::resolve_timeline_originals(&mut options.timeline);
+    relink::ensure_present(
+        options
+            .timeline
+            .tracks
+            .iter()
+            .flat_map(|track| &track.clips)
+            .map(|clip| clip.input_path.as_str()),
+    )?;
+
+    let profile = options.profile.clone().unwrap_or_default();
+    options.timeline.validate()?;
+    profile.validate()?;
+
+    let ffmpeg = ffmpeg.get().await?;
+    ffmpeg.require("export").await?;
+    if options.timeline.uses_blend_modes() {
+        ffmpeg.require("blend_modes").await?;
     }
+    let range = options.timeline.render_range();
+    let job = start_export_job(&app, &jobs, &options.output_path);
+    let mut reporter = ProgressReporter::new(&app, &job, range.end - range.start);
 
-    // Process each main track clip and overlay it at the correct time
-    let mut filter_inputs = Vec::new();
-    let mut filter_complex_parts = Vec::new();
-    
-    // Add background as input 0
-    filter_inputs.push(background_path.to_string_lossy().to_string());
-    
-    for (i, clip) in sorted_main_clips.iter().enumerate() {
-        let duration = clip.trim_end - clip.trim_start;
-        if duration <= 0.0 {
-            continue;
-        }
+    timeline::render_timeline(&ffmpeg, &mut reporter, &options.timeline, Path::new(&options.output_path), &profile).await?;
 
-        let clip_path = temp_dir.join(format!("main_clip_{}.mp4", i));
-        
-        // Create trimmed clip (video only)
-        let output = Command::new("ffmpeg")
-            .arg("-ss")
-            .arg(clip.trim_start.to_string())
-            .arg("-i")
-            .arg(&clip.input_path)
-            .arg("-t")
-            .arg(duration.to_string())
-            .arg("-an") // No audio for individual clips
-            .arg("-c:v")
-            .arg("libx264")
-            .arg("-y")
-            .arg(&clip_path)
-            .output();
-
-        match output {
-            Ok(result) => {
-                if !result.status.success() {
-                    let error_msg = String::from_utf8_lossy(&result.stderr);
-                    return Err(format!("FFmpeg error creating main clip {}: {}", i, error_msg));
-                }
-                filter_inputs.push(clip_path.to_string_lossy().to_string());
-                
-                // Calculate when this clip should start (relative to global trim start)
-                let clip_start_time = clip.start_time - options.global_trim_start;
-                let clip_end_time = clip_start_time + duration;
-                
-                println!("🔍 Debug: Main clip {} starts at {}s, ends at {}s", i, clip_start_time, clip_end_time);
-                
-                // Create overlay filter for this clip
-                let input_idx = i + 1; // Input index (0 is background)
-                let filter_part = format!("[{}:v]scale=1920:1080[main{}];[{}][main{}]overlay=0:0:enable='between(t,{},{})'[out{}]", 
-                    input_idx, i, 
-                    if i == 0 { "0:v" } else { &format!("out{}", i-1) }, i,
-                    clip_start_time, clip_end_time, i);
-                filter_complex_parts.push(filter_part);
-            }
-            Err(e) => {
-                return Err(format!("Failed to execute FFmpeg for main clip {}: {}", i, e));
-            }
-        }
+    reporter.finish("Export complete");
+    Ok(format!("Timeline exported successfully to: {}", options.output_path))
+}
+
+#[tauri::command]
+pub async fn export_multi_clip_video(
+    app: AppHandle,
+    jobs: State<'_, JobRegistry>,
+    ffmpeg: State<'_, FfmpegState>,
+    mut clips: Vec<ClipData>,
+    output_path: String,
+    profile: Option<EncodingProfile>,
+) -> Result<String, NolanForgeError> {
+    // Validate inputs
+    if clips.is_empty() {
+        return Err(NolanForgeError::InvalidInput("No clips provided".to_string()));
     }
+    resolve_clip_originals(&mut clips);
+    relink::ensure_present(clips.iter().map(|clip| clip.input_path.as_str()))?;
 
-    // Build the final filter complex for main video
-    let main_filter_complex = filter_complex_parts.join(";");
-    
-    println!("🔍 Debug: Main filter complex: {}", main_filter_complex);
-    println!("🔍 Debug: Number of clips processed: {}", sorted_main_clips.len());
+    // Keep the picture format of the first clip's original, not its proxy
+    let first_clip = probe::basic_info(&*ffmpeg.get().await?, Path::new(&clips[0].input_path)).await?;
+    let options = multi_clip_options(&clips, Canvas::for_source(&first_clip), output_path, profile);
+
+    render_timeline(app, jobs, ffmpeg, options).await
+}
 
-    // Create the main video with proper timing
-    let mut main_cmd = Command::new("ffmpeg");
-    for input in &filter_inputs {
-        main_cmd.arg("-i").arg(input);
+/// Points clips at their originals, like `resolve_timeline_originals`, so
+/// what is probed is what gets rendered.
+fn resolve_clip_originals(clips: &mut [ClipData]) {
+    for clip in clips {
+        clip.input_path = proxy::resolve_original(&clip.input_path);
     }
-    
-    if !main_filter_complex.is_empty() {
-        // Use filter complex when we have clips to process
-        let final_output_name = format!("out{}", sorted_main_clips.len() - 1);
-        println!("🔍 Debug: Final output name: {}", final_output_name);
-        main_cmd.arg("-filter_complex").arg(&main_filter_complex);
-        main_cmd.arg("-map").arg(&format!("[{}]", final_output_name));
-    } else {
-        // No clips to process, just copy the background
-        println!("🔍 Debug: No clips to process, copying background");
-        main_cmd.arg("-map").arg("0:v");
+}
+
+/// Lays `clips` out back to back on a single track.
+fn multi_clip_options(
+    clips: &[ClipData],
+    canvas: Canvas,
+    output_path: String,
+    profile: Option<EncodingProfile>,
+) -> RenderTimelineOptions {
+    let mut start_time = 0.0;
+    let mut track_clips = Vec::new();
+    for clip in clips {
+        track_clips.push(TimelineClip {
+            input_path: clip.input_path.clone(),
+            start_time,
+            trim_start: clip.trim_start,
+            trim_end: clip.trim_end,
+            transform: Transform::default(),
+            audio: ClipAudio::default(),
+        });
+        start_time += clip.trim_end - clip.trim_start;
     }
-    
-    main_cmd.arg("-c:v").arg("libx264");
-    main_cmd.arg("-y").arg(&main_video_path);
 
-    let output = main_cmd.output();
-    match output {
-        Ok(result) => {
-            if !result.status.success() {
-                let error_msg = String::from_utf8_lossy(&result.stderr);
-                return Err(format!("FFmpeg error creating main video: {}", error_msg));
-            }
-        }
-        Err(e) => {
-            return Err(format!("Failed to execute FFmpeg for main video: {}", e));
-        }
+    RenderTimelineOptions {
+        timeline: TimelineDocument {
+            canvas,
+            tracks: vec![Track {
+                id: 0,
+                muted: false,
+                solo: false,
+                clips: track_clips,
+            }],
+            range: None,
+        },
+        output_path,
+        profile,
     }
+}
 
-    // Extract audio from all main track clips
-    let mut audio_clip_paths = Vec::new();
-    for (i, clip) in main_track_clips.iter().enumerate() {
-        let duration = clip.trim_end - clip.trim_start;
-        if duration <= 0.0 {
-            continue;
-        }
+#[tauri::command]
+pub async fn export_multi_track_video(
+    app: AppHandle,
+    jobs: State<'_, JobRegistry>,
+    ffmpeg: State<'_, FfmpegState>,
+    options: MultiTrackExportOptions,
+) -> Result<String, NolanForgeError> {
+    let render_options = multi_track_options(&options)?;
+    render_timeline(app, jobs, ffmpeg, render_options).await?;
+    Ok(format!("Multi-track video exported successfully to: {}", options.output_path))
+}
 
-        let audio_clip_path = temp_dir.join(format!("main_audio_{}.mp3", i));
-        
-        // Extract audio from this clip
-        let output = Command::new("ffmpeg")
-            .arg("-ss")
-            .arg(clip.trim_start.to_string())
-            .arg("-i")
-            .arg(&clip.input_path)
-            .arg("-t")
-            .arg(duration.to_string())
-            .arg("-vn") // No video
-            .arg("-acodec")
-            .arg("mp3")
-            .arg("-y")
-            .arg(&audio_clip_path)
-            .output();
-
-        match output {
-            Ok(result) => {
-                if !result.status.success() {
-                    let error_msg = String::from_utf8_lossy(&result.stderr);
-                    return Err(format!("FFmpeg error extracting audio from clip {}: {}", i, error_msg));
-                }
-                audio_clip_paths.push(audio_clip_path);
-            }
-            Err(e) => {
-                return Err(format!("Failed to execute FFmpeg for audio extraction from clip {}: {}", i, e));
-            }
-        }
+/// The timeline of a multi-track export.
+fn multi_track_options(options: &MultiTrackExportOptions) -> Result<RenderTimelineOptions, NolanForgeError> {
+    // Validate inputs
+    if options.clips.is_empty() {
+        return Err(NolanForgeError::InvalidInput("No clips provided".to_string()));
     }
 
-    // Concatenate all audio clips
-    if audio_clip_paths.len() > 1 {
-        let audio_concat_file = temp_dir.join("audio_concat_list.txt");
-        let mut audio_concat_content = String::new();
-        for audio_path in &audio_clip_paths {
-            audio_concat_content.push_str(&format!("file '{}'\n", audio_path.display()));
-        }
-        
-        fs::write(&audio_concat_file, audio_concat_content)
-            .map_err(|e| format!("Failed to write audio concat file: {}", e))?;
-
-        // Concatenate audio clips
-        let output = Command::new("ffmpeg")
-            .arg("-f")
-            .arg("concat")
-            .arg("-safe")
-            .arg("0")
-            .arg("-i")
-            .arg(&audio_concat_file)
-            .arg("-c:a")
-            .arg("mp3")
-            .arg("-y")
-            .arg(&main_audio_path)
-            .output();
-
-        match output {
-            Ok(result) => {
-                if !result.status.success() {
-                    let error_msg = String::from_utf8_lossy(&result.stderr);
-                    return Err(format!("FFmpeg error concatenating audio clips: {}", error_msg));
-                }
-            }
-            Err(e) => {
-                return Err(format!("Failed to execute FFmpeg for audio concatenation: {}", e));
-            }
-        }
-    } else if audio_clip_paths.len() == 1 {
-        // Just copy the single audio clip
-        fs::copy(&audio_clip_paths[0], &main_audio_path)
-            .map_err(|e| format!("Failed to copy audio clip: {}", e))?;
+    if options.global_trim_start < 0.0 || options.global_trim_end <= options.global_trim_start {
+        return Err(NolanForgeError::InvalidInput("Invalid global trim range".to_string()));
     }
 
-    // Step 2: Create overlay videos if any
-    let mut overlay_videos = Vec::new();
-    for (i, clip) in overlay_clips.iter().enumerate() {
-        let duration = clip.trim_end - clip.trim_start;
-        if duration <= 0.0 {
-            continue;
-        }
+    if !options.clips.iter().any(|clip| clip.track_id == 0) {
+        return Err(NolanForgeError::InvalidInput("No main track clips found (track 0)".to_string()));
+    }
 
-        let overlay_path = temp_dir.join(format!("overlay_{}.mp4", i));
-        
-        let output = Command::new("ffmpeg")
-            .arg("-ss")
-            .arg(clip.trim_start.to_string())
-            .arg("-i")
-            .arg(&clip.input_path)
-            .arg("-t")
-            .arg(duration.to_string())
-            .arg("-an") // No audio
-            .arg("-c:v")
-            .arg("libx264")
-            .arg("-y")
-            .arg(&overlay_path)
-            .output();
-
-        match output {
-            Ok(result) => {
-                if !result.status.success() {
-                    let error_msg = String::from_utf8_lossy(&result.stderr);
-                    return Err(format!("FFmpeg error creating overlay video {}: {}", i, error_msg));
-                }
-                overlay_videos.push(overlay_path);
-            }
-            Err(e) => {
-                return Err(format!("Failed to execute FFmpeg for overlay video {}: {}", i, e));
+    // Main track clips (track 0) fill the canvas; overlay clips (track 1+)
+    // are drawn in their own box on top
+    let mut track_ids: Vec<i32> = options.clips.iter().map(|clip| clip.track_id).collect();
+    track_ids.sort_unstable();
+    track_ids.dedup();
+
+    let tracks = track_ids
+        .into_iter()
+        .map(|track_id| {
+            let settings = options.track_settings.iter().find(|t| t.track_id == track_id);
+            let clips = options
+                .clips
+                .iter()
+                .filter(|clip| clip.track_id == track_id)
+                .map(|clip| TimelineClip {
+                    input_path: clip.input_path.clone(),
+                    start_time: clip.start_time,
+                    trim_start: clip.trim_start,
+                    trim_end: clip.trim_end,
+                    transform: multi_track_transform(clip),
+                    audio: ClipAudio {
+                        gain: clip.gain.unwrap_or(1.0),
+                        muted: false,
+                    },
+                })
+                .collect();
+
+            Track {
+                id: track_id,
+                muted: settings.is_some_and(|t| t.muted),
+                solo: settings.is_some_and(|t| t.solo),
+                clips,
             }
-        }
-    }
+        })
+        .collect();
+
+    Ok(RenderTimelineOptions {
+        timeline: TimelineDocument {
+            canvas: options.canvas,
+            tracks,
+            range: Some(TimeRange {
+                start: options.global_trim_start,
+                end: options.global_trim_end,
+            }),
+        },
+        output_path: options.output_path.clone(),
+        profile: options.profile.clone(),
+    })
+}
 
-    // Step 3: Compose final video with overlays and audio
-    let mut ffmpeg_args = Vec::new();
-    
-    // Add main video input
-    ffmpeg_args.extend(["-i".to_string(), main_video_path.to_string_lossy().to_string()]);
-    
-    // Add overlay video inputs
-    for overlay_path in &overlay_videos {
-        ffmpeg_args.extend(["-i".to_string(), overlay_path.to_string_lossy().to_string()]);
-    }
-    
-    // Add audio input
-    ffmpeg_args.extend(["-i".to_string(), main_audio_path.to_string_lossy().to_string()]);
+/// Any export, as passed to the command that would run it.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExportRequest {
+    Trim {
+        options: ExportOptions,
+    },
+    MultiClip {
+        clips: Vec<ClipData>,
+        output_path: String,
+        #[serde(default)]
+        profile: Option<EncodingProfile>,
+    },
+    MultiTrack {
+        options: MultiTrackExportOptions,
+    },
+    Timeline {
+        options: RenderTimelineOptions,
+    },
+}
 
-    // Build filter complex for video composition
-    let mut filter_complex = String::new();
-    
-    println!("🔍 Debug: Processing {} overlay videos", overlay_videos.len());
-    println!("🔍 Debug: {} overlay clips provided", overlay_clips.len());
-    
-    if overlay_videos.is_empty() {
-        // No overlays, just use main video
-        filter_complex.push_str("[0:v]scale=1920:1080[video]");
-        println!("🔍 Debug: No overlays, using main video only");
-    } else {
-        // Scale main video
-        filter_complex.push_str("[0:v]scale=1920:1080[main];");
-        
-        // Process each overlay
-        for (i, clip) in overlay_clips.iter().enumerate() {
-            if i < overlay_videos.len() {
-                let input_idx = i + 1; // Overlay inputs start from index 1
-                
-                // Get overlay properties
-                let (x, y) = clip.overlay_position.unwrap_or((0.0, 0.0));
-                let (width, height) = clip.overlay_size.unwrap_or((640.0, 360.0));
-                let opacity = clip.overlay_opacity.unwrap_or(0.8);
-                
-                println!("🔍 Debug: Overlay {} - Position: ({}, {}), Size: {}x{}, Opacity: {}", 
-                    i, x, y, width, height, opacity);
-                
-                // Scale overlay
-                filter_complex.push_str(&format!("[{}:v]scale={}:{}[overlay{}_scaled];", 
-                    input_idx, width as i32, height as i32, i));
-                
-                // Apply opacity
-                filter_complex.push_str(&format!("[overlay{}_scaled]format=yuva420p,colorchannelmixer=aa={}[overlay{}_alpha];", 
-                    i, opacity, i));
-            }
+/// Checks an export request without starting it, returning every error and
+/// warning found instead of stopping at the first.
+#[tauri::command]
+pub async fn validate_export(
+    ffmpeg: State<'_, FfmpegState>,
+    request: ExportRequest,
+) -> Result<PreflightReport, NolanForgeError> {
+    let mut report = PreflightReport::default();
+    let ffmpeg = match ffmpeg.get().await {
+        Ok(ffmpeg) => ffmpeg,
+        Err(e) => {
+            report.error("ffmpeg_unavailable", e.to_string(), None);
+            return Ok(report);
+        }
+    };
+    let (options, kind) = match request {
+        ExportRequest::Trim { options } => {
+            let clips = [ClipData {
+                input_path: proxy::resolve_original(&options.input_path),
+                trim_start: options.trim_start,
+                trim_end: options.trim_end,
+            }];
+            let canvas = source_canvas(&ffmpeg, &clips[0].input_path).await;
+            let render_options = multi_clip_options(&clips, canvas, options.output_path, options.profile);
+            let kind = if options.trim_mode == TrimMode::Copy {
+                ExportKind::StreamCopy
+            } else {
+                ExportKind::Reencode
+            };
+            (render_options, kind)
         }
-        
-        // Chain overlays together with proper positioning and timing
-        let mut current_input = "main".to_string();
-        for i in 0..overlay_videos.len() {
-            let output_name = if i == overlay_videos.len() - 1 { "video" } else { &format!("overlay{}", i) };
-            
-            // Get the actual position for this overlay
-            let (x, y) = overlay_clips[i].overlay_position.unwrap_or((0.0, 0.0));
-            
-            // Calculate when this overlay should appear (relative to global trim start)
-            let overlay_start_time = overlay_clips[i].start_time - options.global_trim_start;
-            let overlay_end_time = overlay_start_time + (overlay_clips[i].trim_end - overlay_clips[i].trim_start);
-            
-            println!("🔍 Debug: Overlay {} Timeline Info:", i);
-            println!("  📍 Position: ({}, {})", x, y);
-            println!("  ⏰ Original start_time: {}s", overlay_clips[i].start_time);
-            println!("  ⏰ Global trim start: {}s", options.global_trim_start);
-            println!("  ⏰ Relative start_time: {}s", overlay_start_time);
-            println!("  ⏰ Duration: {}s", overlay_clips[i].trim_end - overlay_clips[i].trim_start);
-            println!("  ⏰ End time: {}s", overlay_end_time);
-            println!("  🎬 Will appear from {}s to {}s", overlay_start_time, overlay_end_time);
-            
-            // Use enable parameter to control when overlay appears
-            filter_complex.push_str(&format!("[{}][overlay{}_alpha]overlay={}:{}:enable='between(t,{},{})'[{}]", 
-                current_input, i, x as i32, y as i32, overlay_start_time, overlay_end_time, output_name));
-            
-            if i < overlay_videos.len() - 1 {
-                filter_complex.push_str(";");
-                current_input = format!("overlay{}", i);
+        ExportRequest::MultiClip {
+            mut clips,
+            output_path,
+            profile,
+        } => {
+            if clips.is_empty() {
+                return Err(NolanForgeError::InvalidInput("No clips provided".to_string()));
             }
+            resolve_clip_originals(&mut clips);
+            let canvas = source_canvas(&ffmpeg, &clips[0].input_path).await;
+            (multi_clip_options(&clips, canvas, output_path, profile), ExportKind::FilterGraph)
         }
-    }
-    
-    println!("🔍 Debug: Filter complex: {}", filter_complex);
+        ExportRequest::MultiTrack { options } => match multi_track_options(&options) {
+            Ok(render_options) => (render_options, ExportKind::FilterGraph),
+            Err(e) => {
+                report.error("invalid_request", e.to_string(), None);
+                return Ok(report);
+            }
+        },
+        ExportRequest::Timeline { options } => (options, ExportKind::FilterGraph),
+    };
 
-    // Build final FFmpeg command
-    let mut cmd = Command::new("ffmpeg");
-    cmd.args(&ffmpeg_args);
-    cmd.arg("-filter_complex").arg(&filter_complex);
-    cmd.arg("-map").arg("[video]");
-    
-    // Map audio directly from the audio input (last input)
-    let audio_input_idx = overlay_videos.len() + 1; // Audio is the last input
-    cmd.arg("-map").arg(&format!("{}:a", audio_input_idx));
-    
-    cmd.arg("-c:v").arg("libx264");
-    cmd.arg("-c:a").arg("aac");
-    cmd.arg("-preset").arg("fast");
-    cmd.arg("-y");
-    cmd.arg(&options.output_path);
+    let mut timeline = options.timeline;
+    proxy::resolve_timeline_originals(&mut timeline);
+    let profile = options.profile.unwrap_or_default();
+    preflight::validate_export(&ffmpeg, &mut report, &timeline, Path::new(&options.output_path), &profile, kind).await;
+    Ok(report)
+}
 
-    let output = cmd.output();
+/// The canvas a single-source export gets. It only matters for the size
+/// estimate, so a source that cannot be probed falls back to the default.
+async fn source_canvas(ffmpeg: &Ffmpeg, path: &str) -> Canvas {
+    probe::basic_info(ffmpeg, Path::new(path))
+        .await
+        .map(|info| Canvas::for_source(&info))
+        .unwrap_or_default()
+}
 
-    // Clean up temp files
-    let cleanup_result = fs::remove_dir_all(&temp_dir);
-    if let Err(e) = cleanup_result {
-        eprintln!("Warning: Failed to clean up temp directory: {}", e);
+/// How a multi-track export clip is drawn: main track clips fill the canvas,
+/// overlay clips are stretched into their box.
+fn multi_track_transform(clip: &MultiTrackClip) -> Transform {
+    if clip.track_id == 0 {
+        return Transform {
+            fit_mode: clip.fit_mode,
+            ..Transform::default()
+        };
     }
 
-    match output {
-        Ok(result) => {
-            if result.status.success() {
-                Ok(format!("Multi-track video exported successfully to: {}", options.output_path))
-            } else {
-                let error_msg = String::from_utf8_lossy(&result.stderr);
-                Err(format!("FFmpeg composition error: {}", error_msg))
-            }
-        }
-        Err(e) => Err(format!(
-            "Failed to execute FFmpeg composition. Error: {}",
-            e
-        )),
+    Transform {
+        position: Some(clip.overlay_position.unwrap_or((0.0, 0.0))),
+        size: Some(clip.overlay_size.unwrap_or((640.0, 360.0))),
+        fit_mode: FitMode::Stretch,
+        opacity: clip.overlay_opacity.unwrap_or(0.8),
+        blend_mode: clip.overlay_blend_mode.unwrap_or_default(),
     }
 }
 
@@ -608,68 +688,211 @@ pub struct TranscriptionOptions {
     pub video_path: String,
     pub language: Option<String>,
     pub output_format: String, // "srt", "vtt", "txt", "json"
+    /// Only transcribe from `start` to `end` of `video_path`, in source seconds.
+    #[serde(default)]
+    pub start: Option<f64>,
+    #[serde(default)]
+    pub end: Option<f64>,
+    /// Transcribe these clips back to back instead of `video_path`.
+    #[serde(default)]
+    pub clips: Option<Vec<ClipData>>,
+    /// Where the range, or the first clip, sits on the timeline.
+    #[serde(default)]
+    pub timeline_start: Option<f64>,
+    #[serde(default)]
+    pub timebase: TranscriptTimebase,
+}
+
+impl TranscriptionOptions {
+    /// The audio to transcribe: the clips laid out like
+    /// `export_multi_clip_video`, a range of `video_path`, or all of it.
+    fn ranges(&self) -> Result<Vec<SourceRange>, NolanForgeError> {
+        let mut timeline_start = self.timeline_start.unwrap_or(0.0);
+
+        if let Some(clips) = self.clips.as_ref().filter(|clips| !clips.is_empty()) {
+            let mut ranges = Vec::with_capacity(clips.len());
+            for clip in clips {
+                if clip.trim_start < 0.0 || clip.trim_end <= clip.trim_start {
+                    return Err(NolanForgeError::InvalidInput(format!(
+                        "Invalid clip range {:.2}s - {:.2}s in {}",
+                        clip.trim_start, clip.trim_end, clip.input_path
+                    )));
+                }
+                ranges.push(SourceRange {
+                    input_path: PathBuf::from(&clip.input_path),
+                    start: clip.trim_start,
+                    end: Some(clip.trim_end),
+                    timeline_start,
+                });
+                timeline_start += clip.trim_end - clip.trim_start;
+            }
+            return Ok(ranges);
+        }
+
+        let start = self.start.unwrap_or(0.0);
+        if start < 0.0 || self.end.is_some_and(|end| end <= start) {
+            return Err(NolanForgeError::InvalidInput(format!(
+                "Invalid transcription range {:.2}s - {}",
+                start,
+                self.end.map(|end| format!("{:.2}s", end)).unwrap_or_else(|| "end".to_string())
+            )));
+        }
+        Ok(vec![SourceRange {
+            input_path: PathBuf::from(&self.video_path),
+            start,
+            end: self.end,
+            timeline_start,
+        }])
+    }
 }
 
 #[tauri::command]
 pub async fn transcribe_video(
+    app: AppHandle,
+    jobs: State<'_, JobRegistry>,
+    ffmpeg: State<'_, FfmpegState>,
     options: TranscriptionOptions,
-) -> Result<TranscriptionResponse, String> {
-    // Load configuration
-    let config = Config::from_env()
-        .map_err(|e| format!("Configuration error: {}", e))?;
+) -> Result<TranscriptionResponse, NolanForgeError> {
+    // Load configuration and pick the backend before doing any work
+    let config = Config::from_env()?;
+    let provider = Arc::new(Provider::from_config(&config)?);
+    let format = provider.audio_format();
+    
+    let ranges = options.ranges()?;
+    relink::ensure_present(ranges.iter().map(|range| &range.input_path))?;
     
     println!("🔍 Debug: Starting video transcription...");
-    println!("🔍 Debug: Video path: {}", options.video_path);
+    for range in &ranges {
+        println!(
+            "🔍 Debug: Source: {} from {:.2}s to {}",
+            range.input_path.display(),
+            range.start,
+            range.end.map(|end| format!("{:.2}s", end)).unwrap_or_else(|| "end".to_string())
+        );
+    }
+    
+    // Registered up front so the frontend can cancel from the first stage
+    let job = jobs.start();
+    let started = TranscriptionStarted { job_id: job.id().to_string() };
+    if let Err(e) = app.emit(TRANSCRIPTION_STARTED_EVENT, started) {
+        eprintln!("Warning: Failed to emit transcription start: {}", e);
+    }
+
+    let report = move |stage: &str, percent: f64| {
+        let progress = TranscriptionProgress { stage: stage.to_string(), percent };
+        if let Err(e) = app.emit(TRANSCRIPTION_PROGRESS_EVENT, progress) {
+            eprintln!("Warning: Failed to emit transcription progress: {}", e);
+        }
+    };
+    report("Extracting audio", 0.0);
+    
+    // Audio and chunks go in this job's own workspace; finished chunks are
+    // kept until the transcript is delivered, so a retry resumes instead of
+    // starting over
+    let workspace = Workspace::create("transcribe", job.id())?;
+    let checkpoint = chunking::Checkpoint::open(
+        &ranges,
+        provider.name(),
+        options.language.as_deref(),
+        format,
+        job.id(),
+    )?;
+    let audio_path = workspace.join(format!("audio.{}", format.extension()));
     
-    // Create temporary directory for audio extraction
-    let temp_dir = std::env::temp_dir().join("nolanforge_transcription");
-    fs::create_dir_all(&temp_dir)
-        .map_err(|e| format!("Failed to create temp directory: {}", e))?;
+    let ffmpeg = ffmpeg.get().await?;
+    extract_transcription_audio(&ffmpeg, &ranges, format, &audio_path).await?;
+    if job.is_cancelled() {
+        return Err(NolanForgeError::Cancelled);
+    }
+    println!("🔍 Debug: Audio extracted successfully");
+    report("Transcribing", 10.0);
     
-    // Extract audio from video
-    let audio_path = temp_dir.join("audio_for_transcription.mp3");
+    // Transcribe audio with the configured backend, in chunks if it is too
+    // large for one request
+    let duration = probe::media_duration(&ffmpeg, &audio_path).await?;
+    let report = Arc::new(report);
+    let progress = {
+        let report = report.clone();
+        Arc::new(move |percent: f64| report("Transcribing", 10.0 + percent.clamp(0.0, 100.0) * 0.9))
+    };
+    let mut transcription = chunking::transcribe_in_chunks(
+        &ffmpeg,
+        job.handle(),
+        provider,
+        &checkpoint,
+        &audio_path,
+        duration,
+        options.language.clone(),
+        progress,
+    ).await?;
+    checkpoint.discard();
+    rebase(&mut transcription, &ranges, options.timebase);
     
-    let output = Command::new("ffmpeg")
-        .arg("-i")
-        .arg(&options.video_path)
+    report("Done", 100.0);
+    println!("🔍 Debug: Transcription completed successfully");
+    Ok(transcription)
+}
+
+#[tauri::command]
+pub fn cancel_transcription(jobs: State<'_, JobRegistry>, job_id: String) -> Result<String, NolanForgeError> {
+    jobs.cancel(&job_id)?;
+    Ok(format!("Cancellation requested for transcription job {}", job_id))
+}
+
+/// Extracts 16 kHz mono audio of `ranges`, back to back, in the format the
+/// provider takes.
+async fn extract_transcription_audio(
+    ffmpeg: &Ffmpeg,
+    ranges: &[SourceRange],
+    format: AudioFormat,
+    audio_path: &Path,
+) -> Result<(), NolanForgeError> {
+    ffmpeg.require(format.feature()).await?;
+
+    let mut cmd = ffmpeg.command();
+    // Seeking on the input keeps FFmpeg from decoding audio that is not needed
+    for range in ranges {
+        if range.start > 0.0 {
+            cmd.arg("-ss").arg(format!("{:.3}", range.start));
+        }
+        if let Some(end) = range.end {
+            cmd.arg("-t").arg(format!("{:.3}", end - range.start));
+        }
+        cmd.arg("-i").arg(&range.input_path);
+    }
+    if ranges.len() > 1 {
+        let inputs: String = (0..ranges.len()).map(|i| format!("[{}:a:0]", i)).collect();
+        cmd.arg("-filter_complex")
+            .arg(format!("{}concat=n={}:v=0:a=1[audio]", inputs, ranges.len()))
+            .arg("-map")
+            .arg("[audio]");
+    }
+    let output = cmd
         .arg("-vn") // No video
         .arg("-acodec")
-        .arg("mp3")
+        .arg(format.codec())
         .arg("-ar")
         .arg("16000") // 16kHz sample rate (recommended for Whisper)
         .arg("-ac")
         .arg("1") // Mono audio
         .arg("-y")
-        .arg(&audio_path)
-        .output();
+        .arg(audio_path)
+        .output()
+        .await;
     
     match output {
         Ok(result) => {
             if !result.status.success() {
                 let error_msg = String::from_utf8_lossy(&result.stderr);
-                return Err(format!("FFmpeg error extracting audio: {}", error_msg));
+                return Err(NolanForgeError::ffmpeg("Failed to extract audio for transcription", error_msg));
             }
         }
         Err(e) => {
-            return Err(format!("Failed to execute FFmpeg: {}", e));
+            return Err(NolanForgeError::launch("FFmpeg", e));
         }
     }
     
-    println!("🔍 Debug: Audio extracted successfully");
-    
-    // Transcribe audio using OpenAI
-    let transcription = transcribe_audio(
-        &audio_path,
-        &config.openai_api_key,
-        &config.openai_model,
-        options.language.as_deref(),
-    ).await?;
-    
-    // Clean up temporary files
-    let _ = fs::remove_dir_all(&temp_dir);
-    
-    println!("🔍 Debug: Transcription completed successfully");
-    Ok(transcription)
+    Ok(())
 }
 
 #[tauri::command]
@@ -677,7 +900,7 @@ pub async fn export_transcript(
     transcript: TranscriptionResponse,
     output_path: String,
     format: String,
-) -> Result<String, String> {
+) -> Result<String, NolanForgeError> {
     println!("🔍 Debug: Exporting transcript to {}", output_path);
     println!("🔍 Debug: Format: {}", format);
     
@@ -686,12 +909,12 @@ pub async fn export_transcript(
         "vtt" => generate_vtt(&transcript),
         "txt" => transcript.text.clone(),
         "json" => serde_json::to_string_pretty(&transcript)
-            .map_err(|e| format!("Failed to serialize transcript: {}", e))?,
-        _ => return Err(format!("Unsupported format: {}", format)),
+            .map_err(|e| NolanForgeError::Internal(format!("Failed to serialize transcript: {}", e)))?,
+        _ => return Err(NolanForgeError::InvalidInput(format!("Unsupported format: {}", format))),
     };
     
     fs::write(&output_path, content)
-        .map_err(|e| format!("Failed to write transcript file: {}", e))?;
+        .map_err(|e| NolanForgeError::io("Failed to write transcript file", e))?;
     
     println!("🔍 Debug: Transcript exported successfully");
     Ok(format!("Transcript exported to {}", output_path))
@@ -758,47 +981,72 @@ fn format_time_vtt(seconds: f64) -> String {
 }
 
 #[tauri::command]
-pub async fn store_api_key(api_key: String) -> Result<String, String> {
+pub fn list_encoding_profiles() -> Result<Vec<EncodingProfile>, NolanForgeError> {
+    encoding::all_profiles()
+}
+
+#[tauri::command]
+pub fn save_encoding_profile(profile: EncodingProfile) -> Result<String, NolanForgeError> {
+    let name = profile.name.clone();
+    encoding::upsert_user_profile(profile)?;
+    Ok(format!("Encoding profile '{}' saved", name))
+}
+
+#[tauri::command]
+pub fn delete_encoding_profile(id: String) -> Result<String, NolanForgeError> {
+    encoding::delete_user_profile(&id)?;
+    Ok(format!("Encoding profile '{}' deleted", id))
+}
+
+#[tauri::command]
+pub async fn store_api_key(api_key: String) -> Result<String, NolanForgeError> {
     use std::fs;
     
     // Create app data directory
-    let app_data_dir = dirs::data_dir()
-        .ok_or("Failed to get app data directory")?
-        .join("NolanForge");
+    let app_data_dir = Config::app_data_dir()?;
     
     fs::create_dir_all(&app_data_dir)
-        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+        .map_err(|e| NolanForgeError::io("Failed to create app data directory", e))?;
     
     // Store API key in a secure file
     let api_key_file = app_data_dir.join("openai_api_key.txt");
     fs::write(&api_key_file, api_key)
-        .map_err(|e| format!("Failed to write API key file: {}", e))?;
+        .map_err(|e| NolanForgeError::io("Failed to write API key file", e))?;
     
     Ok("API key stored successfully".to_string())
 }
 
 #[tauri::command]
-pub async fn get_stored_api_key() -> Result<String, String> {
+pub async fn get_stored_api_key() -> Result<String, NolanForgeError> {
     use std::fs;
     
-    let app_data_dir = dirs::data_dir()
-        .ok_or("Failed to get app data directory")?
-        .join("NolanForge");
+    let app_data_dir = Config::app_data_dir()?;
     
     let api_key_file = app_data_dir.join("openai_api_key.txt");
     
     if !api_key_file.exists() {
-        return Err("No stored API key found".to_string());
+        return Err(NolanForgeError::ApiKeyMissing);
     }
     
     let api_key = fs::read_to_string(&api_key_file)
-        .map_err(|e| format!("Failed to read API key file: {}", e))?;
+        .map_err(|e| NolanForgeError::io("Failed to read API key file", e))?;
     
     Ok(api_key.trim().to_string())
 }
 
 #[tauri::command]
-pub async fn test_api_key(api_key: String) -> Result<String, String> {
+pub async fn get_transcription_settings() -> Result<TranscriptionSettings, NolanForgeError> {
+    Config::transcription_settings()
+}
+
+#[tauri::command]
+pub async fn save_transcription_settings(settings: TranscriptionSettings) -> Result<String, NolanForgeError> {
+    Config::store_transcription_settings(&settings)?;
+    Ok("Transcription settings saved".to_string())
+}
+
+#[tauri::command]
+pub async fn test_api_key(api_key: String) -> Result<String, NolanForgeError> {
     use reqwest::Client;
     
     let client = Client::new();
@@ -809,12 +1057,12 @@ pub async fn test_api_key(api_key: String) -> Result<String, String> {
         .header("Authorization", format!("Bearer {}", api_key))
         .send()
         .await
-        .map_err(|e| format!("API request failed: {}", e))?;
+        .map_err(|e| NolanForgeError::Network(e.to_string()))?;
     
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("OpenAI API error ({}): {}", status, error_text));
+        return Err(NolanForgeError::openai(status.as_u16(), error_text));
     }
     
     Ok("API key is valid and working".to_string())
@@ -824,7 +1072,7 @@ pub async fn test_api_key(api_key: String) -> Result<String, String> {
 pub async fn save_recording_to_file(
     file_path: String,
     data: Vec<u8>,
-) -> Result<String, String> {
+) -> Result<String, NolanForgeError> {
     use std::fs::File;
     use std::io::Write;
     
@@ -832,10 +1080,10 @@ pub async fn save_recording_to_file(
         Ok(mut file) => {
             match file.write_all(&data) {
                 Ok(_) => Ok(format!("Recording saved successfully to: {}", file_path)),
-                Err(e) => Err(format!("Failed to write file: {}", e))
+                Err(e) => Err(NolanForgeError::io("Failed to write file", e))
             }
         }
-        Err(e) => Err(format!("Failed to create file: {}", e))
+        Err(e) => Err(NolanForgeError::io("Failed to create file", e))
     }
 }
 
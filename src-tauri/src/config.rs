@@ -1,48 +1,162 @@
+use serde::{Deserialize, Serialize};
 use std::env;
+use std::path::PathBuf;
+
+use crate::error::NolanForgeError;
 
 #[derive(Debug, Clone)]
 pub struct Config {
-    pub openai_api_key: String,
+    /// Only required by the OpenAI backend.
+    pub openai_api_key: Option<String>,
     pub openai_model: String,
+    pub transcription: TranscriptionSettings,
+}
+
+/// Which service turns audio into a transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionBackend {
+    #[default]
+    OpenAi,
+    /// A self-hosted server exposing `/audio/transcriptions`.
+    OpenAiCompatible,
+    /// whisper.cpp on this machine, for footage that must not be uploaded.
+    Local,
+}
+
+/// Transcription backend chosen in Settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptionSettings {
+    pub backend: TranscriptionBackend,
+    /// Base URL of an OpenAI-compatible server, e.g. `http://localhost:8000/v1`.
+    pub base_url: Option<String>,
+    /// Key for the compatible server. The OpenAI key is stored separately.
+    pub api_key: Option<String>,
+    /// Overrides `OPENAI_MODEL`.
+    pub model: Option<String>,
+    /// whisper.cpp executable for the local backend.
+    pub whisper_binary: Option<String>,
+    /// ggml model file for the local backend, e.g. `ggml-base.en.bin`.
+    pub whisper_model: Option<String>,
 }
 
 impl Config {
-    pub fn from_env() -> Result<Self, String> {
+    pub fn from_env() -> Result<Self, NolanForgeError> {
         // Load .env file for development
         dotenv::dotenv().ok();
         
-        // Try to get API key from stored file first (production)
-        let api_key = match Self::get_stored_api_key() {
-            Ok(key) => key,
-            Err(_) => {
-                // Fall back to environment variable (development)
-                env::var("OPENAI_API_KEY")
-                    .map_err(|_| "OPENAI_API_KEY not found. Please set it in Settings or add it to your .env file.")?
-            }
-        };
+        // Try to get API key from stored file first (production),
+        // then fall back to environment variable (development)
+        let api_key = Self::get_stored_api_key()
+            .ok()
+            .or_else(|| env::var("OPENAI_API_KEY").ok())
+            .filter(|key| !key.trim().is_empty());
         
         Ok(Config {
             openai_api_key: api_key,
             openai_model: env::var("OPENAI_MODEL")
                 .unwrap_or_else(|_| "whisper-1".to_string()),
+            transcription: Self::transcription_settings()?,
         })
     }
+
+    /// Transcription settings saved in Settings, or from
+    /// `NOLANFORGE_TRANSCRIPTION_BACKEND` / `NOLANFORGE_TRANSCRIPTION_URL` /
+    /// `NOLANFORGE_WHISPER_BINARY` / `NOLANFORGE_WHISPER_MODEL` in development.
+    pub fn transcription_settings() -> Result<TranscriptionSettings, NolanForgeError> {
+        let file = Self::app_data_dir()?.join("transcription.json");
+        if let Ok(content) = std::fs::read_to_string(&file) {
+            return serde_json::from_str(&content)
+                .map_err(|e| NolanForgeError::Config(format!("Invalid transcription settings: {}", e)));
+        }
+
+        dotenv::dotenv().ok();
+        let backend = match env::var("NOLANFORGE_TRANSCRIPTION_BACKEND").as_deref().map(str::trim) {
+            Ok("openai_compatible") => TranscriptionBackend::OpenAiCompatible,
+            Ok("local") => TranscriptionBackend::Local,
+            _ => TranscriptionBackend::OpenAi,
+        };
+        Ok(TranscriptionSettings {
+            backend,
+            base_url: env::var("NOLANFORGE_TRANSCRIPTION_URL").ok(),
+            api_key: env::var("NOLANFORGE_TRANSCRIPTION_API_KEY").ok(),
+            model: None,
+            whisper_binary: env::var("NOLANFORGE_WHISPER_BINARY").ok(),
+            whisper_model: env::var("NOLANFORGE_WHISPER_MODEL").ok(),
+        })
+    }
+
+    pub fn store_transcription_settings(settings: &TranscriptionSettings) -> Result<(), NolanForgeError> {
+        let content = serde_json::to_string_pretty(settings)
+            .map_err(|e| NolanForgeError::Internal(format!("Failed to serialize transcription settings: {}", e)))?;
+        crate::services::files::write_atomic(&Self::app_data_dir()?.join("transcription.json"), content.as_bytes())
+    }
     
-    fn get_stored_api_key() -> Result<String, String> {
+    /// Debug option to keep per-job intermediate files for inspection
+    /// instead of deleting them. Enabled with `NOLANFORGE_KEEP_INTERMEDIATES=1`.
+    pub fn keep_intermediates() -> bool {
+        dotenv::dotenv().ok();
+
+        env::var("NOLANFORGE_KEEP_INTERMEDIATES")
+            .map(|value| matches!(value.trim(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+    }
+
+    /// The NolanForge folder inside the platform data directory, where the
+    /// API key and other user settings are stored.
+    pub fn app_data_dir() -> Result<PathBuf, NolanForgeError> {
+        dirs::data_dir()
+            .map(|dir| dir.join("NolanForge"))
+            .ok_or_else(|| NolanForgeError::Config("Failed to get app data directory".to_string()))
+    }
+
+    /// FFmpeg chosen in Settings, or with `NOLANFORGE_FFMPEG` in development.
+    /// Either the binary or the directory containing it.
+    pub fn ffmpeg_path() -> Option<PathBuf> {
+        let stored = Self::app_data_dir()
+            .ok()
+            .and_then(|dir| std::fs::read_to_string(dir.join("ffmpeg_path.txt")).ok())
+            .map(|path| path.trim().to_string())
+            .filter(|path| !path.is_empty());
+        if let Some(path) = stored {
+            return Some(PathBuf::from(path));
+        }
+
+        dotenv::dotenv().ok();
+        env::var("NOLANFORGE_FFMPEG")
+            .ok()
+            .filter(|path| !path.trim().is_empty())
+            .map(PathBuf::from)
+    }
+
+    /// Saves the FFmpeg chosen in Settings, or forgets it when `None`.
+    pub fn store_ffmpeg_path(path: Option<&std::path::Path>) -> Result<(), NolanForgeError> {
+        let file = Self::app_data_dir()?.join("ffmpeg_path.txt");
+        match path {
+            Some(path) => crate::services::files::write_atomic(&file, path.to_string_lossy().as_bytes()),
+            None => match std::fs::remove_file(&file) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(NolanForgeError::io("Failed to reset the FFmpeg path", e))
+                }
+                _ => Ok(()),
+            },
+        }
+    }
+
+    fn get_stored_api_key() -> Result<String, NolanForgeError> {
         use std::fs;
         
-        let app_data_dir = dirs::data_dir()
-            .ok_or("Failed to get app data directory")?
-            .join("NolanForge");
+        let app_data_dir = Self::app_data_dir()?;
         
         let api_key_file = app_data_dir.join("openai_api_key.txt");
         
         if !api_key_file.exists() {
-            return Err("No stored API key found".to_string());
+            return Err(NolanForgeError::ApiKeyMissing);
         }
         
         let api_key = fs::read_to_string(&api_key_file)
-            .map_err(|e| format!("Failed to read API key file: {}", e))?;
+            .map_err(|e| NolanForgeError::io("Failed to read API key file", e))?;
         
         Ok(api_key.trim().to_string())
     }
@@ -0,0 +1,277 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::fmt;
+use std::io;
+
+/// Diagnostics longer than this are cut down to their tail, which is where
+/// FFmpeg and HTTP APIs put the actual error.
+const MAX_DIAGNOSTIC_LEN: usize = 2000;
+
+/// Error returned by every Tauri command.
+///
+/// Serializes to `{ code, message, diagnostic, retryable }` so the frontend
+/// can tell failures apart and offer a matching recovery action.
+#[derive(Debug, Clone)]
+pub enum NolanForgeError {
+    /// FFmpeg or ffprobe could not be started.
+    FfmpegNotFound { diagnostic: String },
+    /// FFmpeg ran and failed; `stderr` is its log.
+    FfmpegFailed { message: String, stderr: String },
+    /// The FFmpeg in use lacks what a feature needs, e.g. an encoder.
+    FeatureUnavailable { feature: String, missing: Vec<String> },
+    /// The user cancelled the job.
+    Cancelled,
+    /// The request itself is wrong, e.g. an empty trim range.
+    InvalidInput(String),
+    /// A file is missing; `diagnostic` is the log or OS error that reported it.
+    FileNotFound { message: String, diagnostic: String },
+    /// Source media of an export that no longer exists, so it can be relinked.
+    MediaMissing { paths: Vec<String> },
+    DiskFull { diagnostic: String },
+    PermissionDenied { message: String, diagnostic: String },
+    /// Any other file system error.
+    Io { message: String, diagnostic: String },
+    /// No OpenAI API key in the settings or the environment.
+    ApiKeyMissing,
+    Config(String),
+    /// OpenAI rejected the API key.
+    OpenAiUnauthorized { diagnostic: String },
+    OpenAiRateLimited { diagnostic: String },
+    /// Any other non-success response from OpenAI.
+    OpenAiFailed { status: u16, diagnostic: String },
+    /// whisper.cpp ran and failed; `diagnostic` is the end of its log.
+    WhisperFailed { diagnostic: String },
+    /// The Whisper model file is gone or whisper.cpp could not load it.
+    WhisperModelMissing { path: String },
+    /// The request never got a response.
+    Network(String),
+    Internal(String),
+}
+
+impl NolanForgeError {
+    /// Classifies a failed FFmpeg run by its stderr.
+    pub fn ffmpeg(message: impl Into<String>, stderr: impl Into<String>) -> Self {
+        let stderr = stderr.into();
+        if stderr.contains("No space left on device") {
+            return NolanForgeError::DiskFull { diagnostic: stderr };
+        }
+        if stderr.contains("Permission denied") {
+            return NolanForgeError::PermissionDenied {
+                message: "FFmpeg could not read or write a file: permission denied".to_string(),
+                diagnostic: stderr,
+            };
+        }
+        if let Some(line) = stderr.lines().find(|line| line.contains("No such file or directory")) {
+            return NolanForgeError::FileNotFound {
+                message: format!("File not found: {}", reported_path(line)),
+                diagnostic: stderr,
+            };
+        }
+        NolanForgeError::FfmpegFailed {
+            message: message.into(),
+            stderr,
+        }
+    }
+
+    /// Wraps a failure to start `program` (FFmpeg or ffprobe).
+    pub fn launch(program: &str, error: io::Error) -> Self {
+        if error.kind() == io::ErrorKind::NotFound {
+            return NolanForgeError::FfmpegNotFound {
+                diagnostic: format!("{}: {}", program, error),
+            };
+        }
+        NolanForgeError::io(format!("Failed to execute {}", program), error)
+    }
+
+    /// Wraps a file system error, `message` saying what was being done.
+    pub fn io(message: impl Into<String>, error: io::Error) -> Self {
+        let message = message.into();
+        match error.kind() {
+            io::ErrorKind::NotFound => NolanForgeError::FileNotFound {
+                message: format!("{}: {}", message, error),
+                diagnostic: error.to_string(),
+            },
+            io::ErrorKind::StorageFull => NolanForgeError::DiskFull {
+                diagnostic: format!("{}: {}", message, error),
+            },
+            io::ErrorKind::PermissionDenied => NolanForgeError::PermissionDenied {
+                message,
+                diagnostic: error.to_string(),
+            },
+            _ => NolanForgeError::Io {
+                message,
+                diagnostic: error.to_string(),
+            },
+        }
+    }
+
+    /// Maps a non-success OpenAI response to its error.
+    pub fn openai(status: u16, body: String) -> Self {
+        match status {
+            401 | 403 => NolanForgeError::OpenAiUnauthorized { diagnostic: body },
+            429 => NolanForgeError::OpenAiRateLimited { diagnostic: body },
+            _ => NolanForgeError::OpenAiFailed { status, diagnostic: body },
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            NolanForgeError::FfmpegNotFound { .. } => "ffmpeg_not_found",
+            NolanForgeError::FfmpegFailed { .. } => "ffmpeg_failed",
+            NolanForgeError::FeatureUnavailable { .. } => "feature_unavailable",
+            NolanForgeError::Cancelled => "cancelled",
+            NolanForgeError::InvalidInput(_) => "invalid_input",
+            NolanForgeError::FileNotFound { .. } => "file_not_found",
+            NolanForgeError::MediaMissing { .. } => "media_missing",
+            NolanForgeError::DiskFull { .. } => "disk_full",
+            NolanForgeError::PermissionDenied { .. } => "permission_denied",
+            NolanForgeError::Io { .. } => "io_error",
+            NolanForgeError::ApiKeyMissing => "api_key_missing",
+            NolanForgeError::Config(_) => "config_error",
+            NolanForgeError::OpenAiUnauthorized { .. } => "openai_unauthorized",
+            NolanForgeError::OpenAiRateLimited { .. } => "openai_rate_limited",
+            NolanForgeError::OpenAiFailed { .. } => "openai_failed",
+            NolanForgeError::WhisperFailed { .. } => "whisper_failed",
+            NolanForgeError::WhisperModelMissing { .. } => "whisper_model_missing",
+            NolanForgeError::Network(_) => "network_error",
+            NolanForgeError::Internal(_) => "internal_error",
+        }
+    }
+
+    /// Whether the same request may succeed if simply tried again.
+    pub fn retryable(&self) -> bool {
+        match self {
+            NolanForgeError::OpenAiRateLimited { .. } | NolanForgeError::Network(_) => true,
+            NolanForgeError::OpenAiFailed { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// Raw log output behind the error, cut to its last
+    /// `MAX_DIAGNOSTIC_LEN` bytes.
+    pub fn diagnostic(&self) -> Option<String> {
+        let log = match self {
+            NolanForgeError::FfmpegNotFound { diagnostic }
+            | NolanForgeError::FileNotFound { diagnostic, .. }
+            | NolanForgeError::DiskFull { diagnostic }
+            | NolanForgeError::PermissionDenied { diagnostic, .. }
+            | NolanForgeError::Io { diagnostic, .. }
+            | NolanForgeError::OpenAiUnauthorized { diagnostic }
+            | NolanForgeError::OpenAiRateLimited { diagnostic }
+            | NolanForgeError::OpenAiFailed { diagnostic, .. }
+            | NolanForgeError::WhisperFailed { diagnostic } => diagnostic,
+            NolanForgeError::FfmpegFailed { stderr, .. } => stderr,
+            NolanForgeError::MediaMissing { paths } => &paths.join("\n"),
+            NolanForgeError::FeatureUnavailable { missing, .. } => &missing.join("\n"),
+            _ => return None,
+        };
+        Some(truncate_start(log.trim(), MAX_DIAGNOSTIC_LEN))
+    }
+}
+
+/// What an FFmpeg "No such file or directory" line says is missing, without
+/// the `[filter @ 0x...]` tag FFmpeg puts in front.
+fn reported_path(line: &str) -> &str {
+    let reported = line.split(": No such file or directory").next().unwrap_or(line).trim();
+    match reported.strip_prefix('[').and_then(|rest| rest.split_once("] ")) {
+        Some((_, rest)) => rest.trim(),
+        None => reported,
+    }
+}
+
+fn truncate_start(text: &str, max_len: usize) -> String {
+    if text.len() <= max_len {
+        return text.to_string();
+    }
+    let mut start = text.len() - max_len;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    format!("…{}", &text[start..])
+}
+
+impl fmt::Display for NolanForgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NolanForgeError::FfmpegNotFound { .. } => {
+                write!(f, "FFmpeg not found. Please install FFmpeg and add it to your PATH.")
+            }
+            NolanForgeError::FfmpegFailed { message, .. } => write!(f, "{}", message),
+            NolanForgeError::FeatureUnavailable { feature, .. } => {
+                write!(f, "{} is not available with this FFmpeg build", feature)
+            }
+            NolanForgeError::Cancelled => write!(f, "Cancelled"),
+            NolanForgeError::InvalidInput(message)
+            | NolanForgeError::Config(message)
+            | NolanForgeError::Internal(message) => write!(f, "{}", message),
+            NolanForgeError::MediaMissing { paths } => match paths.as_slice() {
+                [path] => write!(f, "Source file not found: {}", path),
+                _ => write!(f, "{} source files could not be found", paths.len()),
+            },
+            NolanForgeError::DiskFull { .. } => write!(f, "Not enough disk space to write the output"),
+            NolanForgeError::FileNotFound { message, .. }
+            | NolanForgeError::PermissionDenied { message, .. }
+            | NolanForgeError::Io { message, .. } => {
+                write!(f, "{}", message)
+            }
+            NolanForgeError::ApiKeyMissing => write!(
+                f,
+                "OPENAI_API_KEY not found. Please set it in Settings or add it to your .env file."
+            ),
+            NolanForgeError::OpenAiUnauthorized { .. } => {
+                write!(f, "OpenAI rejected the API key. Check it in Settings.")
+            }
+            NolanForgeError::OpenAiRateLimited { .. } => {
+                write!(f, "OpenAI rate limit reached. Wait a moment and try again.")
+            }
+            NolanForgeError::OpenAiFailed { status, .. } => write!(f, "OpenAI API error ({})", status),
+            NolanForgeError::WhisperFailed { .. } => write!(f, "whisper.cpp could not transcribe the audio"),
+            NolanForgeError::WhisperModelMissing { path } => write!(
+                f,
+                "Whisper model not found at {}. Choose a ggml model file in Settings",
+                path
+            ),
+            NolanForgeError::Network(message) => write!(f, "Network error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for NolanForgeError {}
+
+impl Serialize for NolanForgeError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("NolanForgeError", 4)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("diagnostic", &self.diagnostic())?;
+        state.serialize_field("retryable", &self.retryable())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_file_keeps_ffmpeg_log() {
+        let stderr = "Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'in.mp4':\n\
+                      [Parsed_lut3d_0 @ 0x5581c0a3e2c0] /luts/film.cube: No such file or directory\n\
+                      Error initializing filters\n";
+        let error = NolanForgeError::ffmpeg("Export failed", stderr);
+
+        assert_eq!(error.code(), "file_not_found");
+        assert_eq!(error.to_string(), "File not found: /luts/film.cube");
+        let diagnostic = error.diagnostic().unwrap();
+        assert!(diagnostic.contains("Parsed_lut3d_0"));
+        assert!(diagnostic.ends_with("Error initializing filters"));
+    }
+
+    #[test]
+    fn reported_path_without_tag() {
+        assert_eq!(reported_path("out/clip.mp4: No such file or directory"), "out/clip.mp4");
+        assert_eq!(
+            reported_path("[out#0/mp4 @ 0x1] Error opening output out/clip.mp4: No such file or directory"),
+            "Error opening output out/clip.mp4"
+        );
+    }
+}
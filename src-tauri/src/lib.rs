@@ -1,5 +1,6 @@
 mod commands;
 mod config;
+mod error;
 mod services;
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
@@ -14,17 +15,57 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .manage(services::jobs::JobRegistry::default())
+        .manage(services::toolchain::FfmpegState::default())
+        .setup(|_app| {
+            // Clear out intermediates of exports that never finished
+            std::thread::spawn(services::workspace::sweep_stale_workspaces);
+            // and transcription checkpoints that were never retried
+            std::thread::spawn(services::chunking::sweep_stale_checkpoints);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
             commands::export_trimmed_video,
             commands::export_multi_clip_video,
             commands::export_multi_track_video,
+            commands::render_timeline,
+            commands::validate_export,
+            commands::cancel_export,
+            commands::list_encoding_profiles,
+            commands::save_encoding_profile,
+            commands::delete_encoding_profile,
             commands::check_ffmpeg,
+            commands::get_ffmpeg_info,
+            commands::set_ffmpeg_path,
+            commands::ffmpeg_capabilities,
+            commands::probe_media,
+            commands::generate_thumbnails,
+            commands::get_waveform_peaks,
+            commands::generate_proxy,
+            commands::cancel_proxy,
+            commands::list_proxies,
+            commands::get_proxy,
+            commands::delete_proxy,
+            commands::save_project,
+            commands::load_project,
+            commands::find_missing_media,
+            commands::search_relink_candidates,
+            commands::relink_media,
+            commands::autosave_snapshot,
+            commands::autosave_record,
+            commands::clear_autosave,
+            commands::list_recoverable_sessions,
+            commands::recover_session,
+            commands::discard_session,
             commands::save_recording_to_file,
             commands::transcribe_video,
+            commands::cancel_transcription,
             commands::export_transcript,
             commands::store_api_key,
             commands::get_stored_api_key,
+            commands::get_transcription_settings,
+            commands::save_transcription_settings,
             commands::test_api_key
         ])
         .run(tauri::generate_context!())
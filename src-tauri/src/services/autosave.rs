@@ -0,0 +1,465 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::files;
+use super::project::{ExportSettings, GlobalTrim, MediaReference, ProjectClip, ProjectDocument, ProjectTrack};
+use super::workspace;
+use crate::config::Config;
+use crate::error::NolanForgeError;
+
+/// The journal is folded into a new snapshot once it has this many entries...
+const MAX_JOURNAL_ENTRIES: usize = 200;
+
+/// ...or grows past this size.
+const MAX_JOURNAL_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Only the most recent sessions are kept for recovery.
+const MAX_SESSIONS: usize = 5;
+
+const SNAPSHOT_FILE: &str = "snapshot.json";
+const JOURNAL_FILE: &str = "journal.jsonl";
+/// PID of the process writing the session, like a workspace owner file.
+const OWNER_FILE: &str = ".owner";
+
+/// Identifies this run's autosave session.
+static SESSION_ID: LazyLock<String> = LazyLock::new(|| uuid::Uuid::new_v4().to_string());
+
+/// The current session as of its last journal entry, `None` until the first
+/// snapshot.
+static SESSION: Mutex<Option<SessionState>> = Mutex::new(None);
+
+struct SessionState {
+    project: ProjectDocument,
+    project_path: Option<String>,
+    seq: u64,
+    journal_entries: usize,
+    journal_bytes: u64,
+}
+
+/// One edit to an autosaved project. Each replaces a whole part of the
+/// document except clips, which change one at a time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ProjectChange {
+    Name { name: String },
+    ProjectPath { project_path: Option<String> },
+    Media { media: Vec<MediaReference> },
+    Tracks { tracks: Vec<ProjectTrack> },
+    UpsertClips { clips: Vec<ProjectClip> },
+    RemoveClips { ids: Vec<String> },
+    GlobalTrim { global_trim: GlobalTrim },
+    ExportSettings { export_settings: Box<ExportSettings> },
+}
+
+/// Full state of a session, from which journal entries after `seq` are
+/// replayed.
+#[derive(Debug, Serialize, Deserialize)]
+struct Snapshot {
+    seq: u64,
+    updated_at: u64,
+    project_path: Option<String>,
+    project: ProjectDocument,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalEntry {
+    seq: u64,
+    recorded_at: u64,
+    changes: Vec<ProjectChange>,
+}
+
+/// An autosaved session left behind by an earlier run.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoverableSession {
+    pub session_id: String,
+    pub project_name: String,
+    /// Project file the session was last saved to, if any.
+    pub project_path: Option<String>,
+    /// Milliseconds since the Unix epoch.
+    pub updated_at: u64,
+    pub clip_count: usize,
+    /// Edits made since the last snapshot.
+    pub edit_count: usize,
+}
+
+/// The state of a recoverable session with its journal replayed.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecoveredSession {
+    pub session_id: String,
+    pub project_path: Option<String>,
+    pub project: ProjectDocument,
+    /// IDs of media whose file could not be found.
+    pub missing_media: Vec<String>,
+}
+
+fn autosave_root() -> Result<PathBuf, NolanForgeError> {
+    Ok(Config::app_data_dir()?.join("autosave"))
+}
+
+/// Directory of `session_id`, which must be a session ID and not a path.
+fn session_dir(session_id: &str) -> Result<PathBuf, NolanForgeError> {
+    if uuid::Uuid::parse_str(session_id).is_err() {
+        return Err(NolanForgeError::InvalidInput(format!(
+            "Invalid autosave session: {}",
+            session_id
+        )));
+    }
+    Ok(autosave_root()?.join(session_id))
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn apply(project: &mut ProjectDocument, project_path: &mut Option<String>, change: ProjectChange) {
+    match change {
+        ProjectChange::Name { name } => project.name = name,
+        ProjectChange::ProjectPath { project_path: path } => *project_path = path,
+        ProjectChange::Media { media } => project.media = media,
+        ProjectChange::Tracks { tracks } => project.tracks = tracks,
+        ProjectChange::UpsertClips { clips } => {
+            for clip in clips {
+                match project.clips.iter_mut().find(|existing| existing.id == clip.id) {
+                    Some(existing) => *existing = clip,
+                    None => project.clips.push(clip),
+                }
+            }
+        }
+        ProjectChange::RemoveClips { ids } => project.clips.retain(|clip| !ids.contains(&clip.id)),
+        ProjectChange::GlobalTrim { global_trim } => project.global_trim = global_trim,
+        ProjectChange::ExportSettings { export_settings } => project.export_settings = *export_settings,
+    }
+}
+
+/// Writes `state` as the session's snapshot and empties its journal.
+///
+/// The snapshot is renamed into place before the journal is truncated, and
+/// records the last sequence number it includes, so a crash in between only
+/// leaves entries that replay skips.
+fn write_snapshot(state: &SessionState) -> Result<(), NolanForgeError> {
+    let dir = session_dir(&SESSION_ID)?;
+    let snapshot = Snapshot {
+        seq: state.seq,
+        updated_at: now_millis(),
+        project_path: state.project_path.clone(),
+        project: state.project.clone(),
+    };
+    let content = serde_json::to_vec(&snapshot)
+        .map_err(|e| NolanForgeError::Internal(format!("Failed to serialize autosave snapshot: {}", e)))?;
+    files::write_atomic(&dir.join(SNAPSHOT_FILE), &content)?;
+
+    File::create(dir.join(JOURNAL_FILE)).map_err(|e| NolanForgeError::io("Failed to reset autosave journal", e))?;
+    Ok(())
+}
+
+/// Starts the session's journal over from `project`.
+pub fn snapshot(project: ProjectDocument, project_path: Option<String>) -> Result<(), NolanForgeError> {
+    let mut session = SESSION.lock().unwrap();
+    if session.is_none() {
+        start_session()?;
+    }
+
+    let state = SessionState {
+        project,
+        project_path,
+        seq: session.as_ref().map_or(0, |state| state.seq),
+        journal_entries: 0,
+        journal_bytes: 0,
+    };
+    write_snapshot(&state)?;
+    *session = Some(state);
+    Ok(())
+}
+
+/// Creates this run's session directory and drops the oldest sessions.
+fn start_session() -> Result<(), NolanForgeError> {
+    let dir = session_dir(&SESSION_ID)?;
+    fs::create_dir_all(&dir).map_err(|e| NolanForgeError::io("Failed to create autosave directory", e))?;
+    fs::write(dir.join(OWNER_FILE), std::process::id().to_string())
+        .map_err(|e| NolanForgeError::io("Failed to write autosave owner file", e))?;
+
+    // Leave room for the one being started. Sessions that cannot be read
+    // right now count towards the limit too, by when they were last written,
+    // but are never dropped just for failing to replay
+    let (sessions, unreadable) = scan_sessions()?;
+    let mut by_age: Vec<(String, u64)> = sessions
+        .into_iter()
+        .map(|session| (session.session_id, session.updated_at))
+        .chain(unreadable.into_iter().map(|session| (session.session_id, session.modified)))
+        .collect();
+    by_age.sort_by_key(|(_, updated_at)| std::cmp::Reverse(*updated_at));
+    for (session_id, _) in by_age.into_iter().skip(MAX_SESSIONS - 1) {
+        println!("🔍 Debug: Dropping old autosave session {}", session_id);
+        remove_session_dir(&session_id);
+    }
+    println!("🔍 Debug: Autosaving to session {}", *SESSION_ID);
+    Ok(())
+}
+
+/// Appends `changes` to the journal, folding it into a new snapshot once it
+/// grows past its bounds.
+pub fn record(changes: Vec<ProjectChange>) -> Result<(), NolanForgeError> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let mut session = SESSION.lock().unwrap();
+    let state = session.as_mut().ok_or_else(|| {
+        NolanForgeError::InvalidInput("Autosave has no snapshot to record edits against".to_string())
+    })?;
+
+    let entry = JournalEntry {
+        seq: state.seq + 1,
+        recorded_at: now_millis(),
+        changes,
+    };
+    let mut line = serde_json::to_vec(&entry)
+        .map_err(|e| NolanForgeError::Internal(format!("Failed to serialize autosave entry: {}", e)))?;
+    line.push(b'\n');
+
+    let path = session_dir(&SESSION_ID)?.join(JOURNAL_FILE);
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| {
+            file.write_all(&line)?;
+            file.sync_data()
+        })
+        .map_err(|e| NolanForgeError::io("Failed to append to autosave journal", e))?;
+
+    state.seq = entry.seq;
+    state.journal_entries += 1;
+    state.journal_bytes += line.len() as u64;
+    for change in entry.changes {
+        apply(&mut state.project, &mut state.project_path, change);
+    }
+
+    if journal_full(state.journal_entries, state.journal_bytes) {
+        println!("🔍 Debug: Compacting autosave journal at entry {}", state.seq);
+        write_snapshot(state)?;
+        state.journal_entries = 0;
+        state.journal_bytes = 0;
+    }
+    Ok(())
+}
+
+/// Whether a journal has grown enough to be folded into a snapshot.
+fn journal_full(entries: usize, bytes: u64) -> bool {
+    entries >= MAX_JOURNAL_ENTRIES || bytes >= MAX_JOURNAL_BYTES
+}
+
+/// Drops this run's autosave, e.g. once the project has been saved.
+pub fn clear() -> Result<(), NolanForgeError> {
+    let mut session = SESSION.lock().unwrap();
+    if session.take().is_some() {
+        remove_session_dir(&SESSION_ID);
+    }
+    Ok(())
+}
+
+/// Reads a session's snapshot and replays its journal. Returns the state
+/// and the number of journal entries applied.
+fn replay(dir: &Path) -> Result<(Snapshot, usize), NolanForgeError> {
+    let content = fs::read(dir.join(SNAPSHOT_FILE))
+        .map_err(|e| NolanForgeError::io("Failed to read autosave snapshot", e))?;
+    let mut snapshot: Snapshot = serde_json::from_slice(&content)
+        .map_err(|e| NolanForgeError::Config(format!("Failed to parse autosave snapshot: {}", e)))?;
+
+    let journal = fs::read_to_string(dir.join(JOURNAL_FILE)).unwrap_or_default();
+    let mut applied = 0;
+    for line in journal.lines() {
+        let Ok(entry) = serde_json::from_str::<JournalEntry>(line) else {
+            // Only the last entry can be torn, by a crash mid-append
+            println!("🔍 Debug: Ignoring unreadable autosave entry in {}", dir.display());
+            break;
+        };
+        // Entries already in the snapshot, left by a crash during compaction
+        if entry.seq <= snapshot.seq {
+            continue;
+        }
+        for change in entry.changes {
+            apply(&mut snapshot.project, &mut snapshot.project_path, change);
+        }
+        snapshot.seq = entry.seq;
+        snapshot.updated_at = snapshot.updated_at.max(entry.recorded_at);
+        applied += 1;
+    }
+    Ok((snapshot, applied))
+}
+
+/// Sessions of earlier runs that can be restored, newest first. Sessions of
+/// this run and of other running instances are left out.
+pub fn list_recoverable_sessions() -> Result<Vec<RecoverableSession>, NolanForgeError> {
+    Ok(scan_sessions()?.0)
+}
+
+/// A session of an earlier run whose snapshot or journal could not be read.
+struct UnreadableSession {
+    session_id: String,
+    /// When its directory was last modified, in milliseconds since the Unix
+    /// epoch.
+    modified: u64,
+}
+
+/// Sessions of earlier runs, newest first, and those that could not be read.
+fn scan_sessions() -> Result<(Vec<RecoverableSession>, Vec<UnreadableSession>), NolanForgeError> {
+    let Ok(entries) = fs::read_dir(autosave_root()?) else {
+        return Ok((Vec::new(), Vec::new()));
+    };
+
+    let mut sessions = Vec::new();
+    let mut unreadable = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let session_id = entry.file_name().to_string_lossy().to_string();
+        if !path.is_dir() || session_id == *SESSION_ID {
+            continue;
+        }
+
+        let owner = fs::read_to_string(path.join(OWNER_FILE))
+            .ok()
+            .and_then(|pid| pid.trim().parse::<u32>().ok());
+        if owner.is_some_and(|pid| pid != std::process::id() && workspace::process_alive(pid)) {
+            continue;
+        }
+
+        match replay(&path) {
+            Ok((snapshot, edit_count)) => sessions.push(RecoverableSession {
+                session_id,
+                project_name: snapshot.project.name,
+                project_path: snapshot.project_path,
+                updated_at: snapshot.updated_at,
+                clip_count: snapshot.project.clips.len(),
+                edit_count,
+            }),
+            Err(e) => {
+                eprintln!("Warning: Skipping autosave session {}: {}", session_id, e);
+                let modified = fs::metadata(&path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map_or(0, |d| d.as_millis() as u64);
+                unreadable.push(UnreadableSession { session_id, modified });
+            }
+        }
+    }
+
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.updated_at));
+    Ok((sessions, unreadable))
+}
+
+/// The last state of an earlier session.
+pub fn recover_session(session_id: &str) -> Result<RecoveredSession, NolanForgeError> {
+    let dir = session_dir(session_id)?;
+    if !dir.exists() {
+        return Err(NolanForgeError::InvalidInput(format!(
+            "Autosave session {} no longer exists",
+            session_id
+        )));
+    }
+
+    let (snapshot, edit_count) = replay(&dir)?;
+    let missing_media = snapshot
+        .project
+        .media
+        .iter()
+        .filter(|media| !Path::new(&media.path).exists())
+        .map(|media| media.id.clone())
+        .collect();
+
+    println!(
+        "🔍 Debug: Recovered autosave session {} with {} edits after its snapshot",
+        session_id, edit_count
+    );
+    Ok(RecoveredSession {
+        session_id: session_id.to_string(),
+        project_path: snapshot.project_path,
+        project: snapshot.project,
+        missing_media,
+    })
+}
+
+/// Deletes an earlier session, once restored or declined.
+pub fn discard_session(session_id: &str) -> Result<(), NolanForgeError> {
+    if session_id == *SESSION_ID {
+        return clear();
+    }
+    let dir = session_dir(session_id)?;
+    if dir.exists() {
+        fs::remove_dir_all(&dir).map_err(|e| NolanForgeError::io("Failed to remove autosave session", e))?;
+    }
+    Ok(())
+}
+
+fn remove_session_dir(session_id: &str) {
+    let Ok(dir) = session_dir(session_id) else {
+        return;
+    };
+    if let Err(e) = fs::remove_dir_all(&dir) {
+        if dir.exists() {
+            eprintln!("Warning: Failed to remove autosave session {}: {}", session_id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(seq: u64, recorded_at: u64, name: &str) -> String {
+        serde_json::to_string(&JournalEntry {
+            seq,
+            recorded_at,
+            changes: vec![ProjectChange::Name { name: name.to_string() }],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn replay_applies_entries_after_the_snapshot_up_to_a_torn_line() {
+        let dir = std::env::temp_dir().join(format!("nolanforge-autosave-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let snapshot = serde_json::json!({
+            "seq": 2,
+            "updated_at": 1000,
+            "project_path": null,
+            "project": { "schema_version": 1, "name": "Snapshot", "media": [], "tracks": [], "clips": [] },
+        });
+        fs::write(dir.join(SNAPSHOT_FILE), snapshot.to_string()).unwrap();
+
+        // Entry 2 is in the snapshot already, left by a crash during compaction;
+        // the last line was cut off by a crash mid-append
+        let journal = [
+            entry(2, 900, "Compacted"),
+            entry(3, 2000, "Renamed"),
+            entry(4, 3000, "Renamed again"),
+            r#"{"seq":5,"recorded_at":4000,"chan"#.to_string(),
+        ]
+        .join("\n");
+        fs::write(dir.join(JOURNAL_FILE), journal).unwrap();
+
+        let (replayed, applied) = replay(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(applied, 2);
+        assert_eq!(replayed.seq, 4);
+        assert_eq!(replayed.updated_at, 3000);
+        assert_eq!(replayed.project.name, "Renamed again");
+    }
+
+    #[test]
+    fn journal_is_compacted_at_its_limits() {
+        assert!(!journal_full(MAX_JOURNAL_ENTRIES - 1, MAX_JOURNAL_BYTES - 1));
+        assert!(journal_full(MAX_JOURNAL_ENTRIES, 0));
+        assert!(journal_full(1, MAX_JOURNAL_BYTES));
+        assert_eq!((MAX_JOURNAL_ENTRIES, MAX_JOURNAL_BYTES), (200, 4 * 1024 * 1024));
+    }
+}
@@ -0,0 +1,52 @@
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::error::NolanForgeError;
+
+/// Bytes hashed from each end of a file to fingerprint it.
+const FINGERPRINT_SAMPLE: u64 = 64 * 1024;
+
+/// Directory for one kind of cached media artifact inside the app data
+/// directory, created if needed.
+pub fn cache_dir(kind: &str) -> Result<PathBuf, NolanForgeError> {
+    let dir = Config::app_data_dir()?.join("cache").join(kind);
+    fs::create_dir_all(&dir).map_err(|e| NolanForgeError::io("Failed to create cache directory", e))?;
+    Ok(dir)
+}
+
+/// Content hash identifying a media file.
+///
+/// Hashing whole videos would take longer than the work being cached, so
+/// only the size and both ends of the file are hashed. That still tells
+/// re-encoded or replaced files apart while surviving renames and moves.
+pub fn source_fingerprint(path: &Path) -> Result<String, NolanForgeError> {
+    let mut file = File::open(path)
+        .map_err(|e| NolanForgeError::io(format!("Failed to open {}", path.display()), e))?;
+    let size = file
+        .metadata()
+        .map_err(|e| NolanForgeError::io(format!("Failed to read {}", path.display()), e))?
+        .len();
+
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+
+    let mut buf = vec![0; FINGERPRINT_SAMPLE.min(size) as usize];
+    let read_error = |e| NolanForgeError::io(format!("Failed to read {}", path.display()), e);
+    file.read_exact(&mut buf).map_err(read_error)?;
+    hasher.update(&buf);
+
+    if size > FINGERPRINT_SAMPLE {
+        file.seek(SeekFrom::End(-(buf.len() as i64))).map_err(read_error)?;
+        file.read_exact(&mut buf).map_err(read_error)?;
+        hasher.update(&buf);
+    }
+
+    Ok(hex(&hasher.finalize()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,654 @@
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use super::jobs::Job;
+use super::toolchain::Ffmpeg;
+use super::workspace::{process_alive, OWNER_FILE};
+use super::transcription::{
+    AudioFormat, Provider, Segment, SourceRange, TranscriptionProvider, TranscriptionRequest, TranscriptionResponse, Word,
+};
+use super::{cache, files};
+use crate::config::Config;
+use crate::error::NolanForgeError;
+
+/// Chunks are planned to fill this share of the upload limit, leaving room
+/// for cuts that move to a nearby silence.
+const CHUNK_FILL: f64 = 0.8;
+
+/// How far from the planned cut a silence may be, as a share of the chunk.
+const SILENCE_SEARCH: f64 = 0.1;
+
+/// Overlap added to a cut that found no silence, so a word cut in half
+/// is still heard whole by one of the two chunks.
+const SEAM_OVERLAP: f64 = 1.5;
+
+/// Chunks uploaded at the same time.
+pub const MAX_CONCURRENT_CHUNKS: usize = 3;
+
+/// Called with overall progress (0-100) across all chunks.
+pub type ProgressFn = Arc<dyn Fn(f64) + Send + Sync>;
+
+/// One piece of the audio, in seconds from the start of the full file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    /// Where the extracted audio starts, including any overlap.
+    pub start: f64,
+    pub end: f64,
+    /// Words and segments starting in `[owned_from, owned_until)` come from
+    /// this chunk; anything else was heard by a neighbour too.
+    pub owned_from: f64,
+    pub owned_until: f64,
+}
+
+/// Splits `duration` seconds of audio weighing `size` bytes into chunks of
+/// at most `max_bytes`, cutting in a silence near each boundary when there
+/// is one. Returns a single chunk when the file already fits.
+pub fn plan_chunks(duration: f64, size: u64, max_bytes: u64, silences: &[(f64, f64)]) -> Vec<Chunk> {
+    let count = ((size as f64 / (max_bytes as f64 * CHUNK_FILL)).ceil() as usize).max(1);
+    let target = duration / count as f64;
+    let window = target * SILENCE_SEARCH;
+
+    // (cut, overlap) for every boundary between chunks
+    let cuts: Vec<(f64, f64)> = (1..count)
+        .map(|k| {
+            let ideal = target * k as f64;
+            silences
+                .iter()
+                .map(|(start, end)| (start + end) / 2.0)
+                .filter(|mid| (mid - ideal).abs() <= window)
+                .min_by(|a, b| (a - ideal).abs().total_cmp(&(b - ideal).abs()))
+                .map(|mid| (mid, 0.0))
+                .unwrap_or((ideal, SEAM_OVERLAP))
+        })
+        .collect();
+
+    (0..count)
+        .map(|k| {
+            let (owned_from, overlap) = if k == 0 { (0.0, 0.0) } else { cuts[k - 1] };
+            let owned_until = cuts.get(k).map(|(cut, _)| *cut).unwrap_or(f64::INFINITY);
+            Chunk {
+                start: (owned_from - overlap).max(0.0),
+                end: owned_until.min(duration),
+                owned_from,
+                owned_until,
+            }
+        })
+        .collect()
+}
+
+/// Finds silences in an audio file with FFmpeg's `silencedetect`.
+pub async fn detect_silences(ffmpeg: &Ffmpeg, audio_path: &Path) -> Result<Vec<(f64, f64)>, NolanForgeError> {
+    let output = ffmpeg
+        .command()
+        .arg("-hide_banner")
+        .arg("-nostats")
+        .arg("-i")
+        .arg(audio_path)
+        .arg("-af")
+        .arg("silencedetect=noise=-35dB:d=0.3")
+        .arg("-f")
+        .arg("null")
+        .arg("-")
+        .output()
+        .await
+        .map_err(|e| NolanForgeError::launch("FFmpeg", e))?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(NolanForgeError::ffmpeg("Failed to find silences for chunking", stderr));
+    }
+
+    // Lines look like `[silencedetect @ 0x...] silence_end: 13.52 | silence_duration: 0.61`
+    let mut silences = Vec::new();
+    let mut current_start = None;
+    for line in stderr.lines() {
+        if let Some(value) = field(line, "silence_start:") {
+            current_start = Some(value);
+        } else if let Some(end) = field(line, "silence_end:") {
+            if let Some(start) = current_start.take() {
+                silences.push((start.max(0.0), end));
+            }
+        }
+    }
+    Ok(silences)
+}
+
+fn field(line: &str, name: &str) -> Option<f64> {
+    let (_, rest) = line.split_once(name)?;
+    rest.split_whitespace().next()?.parse().ok()
+}
+
+/// Copies one chunk out of the full audio file without re-encoding.
+async fn extract_chunk(ffmpeg: &Ffmpeg, audio_path: &Path, chunk: &Chunk, output: &Path) -> Result<(), NolanForgeError> {
+    let result = ffmpeg
+        .command()
+        .arg("-hide_banner")
+        .arg("-ss")
+        .arg(format!("{:.3}", chunk.start))
+        .arg("-to")
+        .arg(format!("{:.3}", chunk.end))
+        .arg("-i")
+        .arg(audio_path)
+        .arg("-acodec")
+        .arg("copy")
+        .arg("-y")
+        .arg(output)
+        .output()
+        .await
+        .map_err(|e| NolanForgeError::launch("FFmpeg", e))?;
+
+    if !result.status.success() {
+        return Err(NolanForgeError::ffmpeg(
+            "Failed to split audio for transcription",
+            String::from_utf8_lossy(&result.stderr),
+        ));
+    }
+    Ok(())
+}
+
+/// Transcribes `audio_path` piece by piece when it is larger than the
+/// provider accepts, then stitches the pieces into one transcript.
+/// Finished chunks are saved to `checkpoint` as they come in.
+#[allow(clippy::too_many_arguments)]
+pub async fn transcribe_in_chunks(
+    ffmpeg: &Ffmpeg,
+    job: Arc<Job>,
+    provider: Arc<Provider>,
+    checkpoint: &Checkpoint,
+    audio_path: &Path,
+    duration: f64,
+    language: Option<String>,
+    progress: ProgressFn,
+) -> Result<TranscriptionResponse, NolanForgeError> {
+    let size = fs::metadata(audio_path)
+        .map_err(|e| NolanForgeError::io("Failed to read extracted audio", e))?
+        .len();
+
+    let chunks = match provider.max_upload_bytes() {
+        Some(max_bytes) if size > max_bytes => {
+            let silences = detect_silences(ffmpeg, audio_path).await?;
+            plan_chunks(duration, size, max_bytes, &silences)
+        }
+        _ => vec![Chunk {
+            start: 0.0,
+            end: duration,
+            owned_from: 0.0,
+            owned_until: f64::INFINITY,
+        }],
+    };
+
+    if chunks.len() > 1 {
+        println!(
+            "🔍 Debug: Audio is {} bytes, transcribing in {} chunks",
+            size,
+            chunks.len()
+        );
+    }
+
+    // Chunks finished by an earlier attempt are not sent again
+    let mut responses: Vec<Option<TranscriptionResponse>> =
+        chunks.iter().enumerate().map(|(index, chunk)| checkpoint.load_chunk(index, chunk)).collect();
+    let done = responses.iter().filter(|r| r.is_some()).count();
+    if done > 0 {
+        println!("🔍 Debug: Resuming transcription, {} of {} chunks already done", done, chunks.len());
+    }
+
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_CHUNKS));
+    let chunk_progress = Arc::new(Mutex::new(
+        responses.iter().map(|r| if r.is_some() { 100.0 } else { 0.0 }).collect::<Vec<f64>>(),
+    ));
+    let mut tasks = JoinSet::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        if responses[index].is_some() {
+            continue;
+        }
+
+        if job.is_cancelled() {
+            return Err(NolanForgeError::Cancelled);
+        }
+
+        let path = if chunks.len() == 1 {
+            audio_path.to_path_buf()
+        } else {
+            let path = chunk_file(audio_path, index);
+            extract_chunk(ffmpeg, audio_path, chunk, &path).await?;
+            path
+        };
+
+        let job = job.clone();
+        let provider = provider.clone();
+        let semaphore = semaphore.clone();
+        let language = language.clone();
+        let chunk_progress = chunk_progress.clone();
+        let progress = progress.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await.expect("semaphore is never closed");
+            let report = |percent: f64| {
+                let mut all = chunk_progress.lock().unwrap();
+                all[index] = percent.clamp(0.0, 100.0);
+                progress(all.iter().sum::<f64>() / all.len() as f64);
+            };
+            let result = provider
+                .transcribe(&TranscriptionRequest {
+                    audio_path: &path,
+                    language: language.as_deref(),
+                    progress: &report,
+                    job: &job,
+                })
+                .await;
+            report(100.0);
+            (index, result)
+        });
+    }
+
+    // Let every chunk finish so as many as possible are checkpointed, then
+    // report the first failure
+    let mut first_error = None;
+    while let Some(joined) = tasks.join_next().await {
+        let (index, result) = joined.map_err(|e| NolanForgeError::Internal(format!("Transcription task failed: {}", e)))?;
+        match result {
+            Ok(response) => {
+                if let Err(e) = checkpoint.save_chunk(index, &chunks[index], &response) {
+                    eprintln!("Warning: Failed to checkpoint transcription chunk {}: {}", index, e);
+                }
+                responses[index] = Some(response);
+            }
+            Err(e) => {
+                eprintln!("Warning: Transcription chunk {} failed: {}", index, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if let Some(error) = first_error {
+        return Err(error);
+    }
+
+    let mut pieces: Vec<(Chunk, TranscriptionResponse)> = chunks.into_iter().zip(responses.into_iter().flatten()).collect();
+    if pieces.len() == 1 {
+        return Ok(pieces.remove(0).1);
+    }
+    Ok(stitch(pieces, duration))
+}
+
+/// Joins chunk transcripts into one, moving timestamps onto the full
+/// audio's timeline, renumbering segments and dropping anything a chunk
+/// heard in its neighbour's overlap.
+pub fn stitch(pieces: Vec<(Chunk, TranscriptionResponse)>, duration: f64) -> TranscriptionResponse {
+    let mut language = None;
+    let mut texts: Vec<String> = Vec::new();
+    let mut words = Vec::new();
+    let mut segments = Vec::new();
+    let mut has_words = false;
+    let mut has_segments = false;
+
+    for (chunk, response) in pieces {
+        let offset = chunk.start;
+        let owns = |start: f64| start + offset >= chunk.owned_from && start + offset < chunk.owned_until;
+        language = language.or(response.language);
+
+        if let Some(chunk_words) = response.words {
+            has_words = true;
+            for mut word in chunk_words.into_iter().filter(|w| owns(w.start)) {
+                word.start += offset;
+                word.end += offset;
+                let repeated = words.last().is_some_and(|last: &Word| {
+                    same_text(&last.word, &word.word) && (word.start - last.start).abs() < 0.5
+                });
+                if !repeated {
+                    words.push(word);
+                }
+            }
+        }
+
+        match response.segments {
+            Some(chunk_segments) => {
+                has_segments = true;
+                for mut segment in chunk_segments.into_iter().filter(|s| owns(s.start)) {
+                    segment.start += offset;
+                    segment.end += offset;
+                    segment.seek += offset * 100.0;
+                    let repeated = segments.last().is_some_and(|last: &Segment| {
+                        same_text(&last.text, &segment.text) && (segment.start - last.start).abs() < 1.0
+                    });
+                    if !repeated {
+                        segment.id = segments.len() as i32;
+                        texts.push(segment.text.trim().to_string());
+                        segments.push(segment);
+                    }
+                }
+            }
+            // Without segments there is nothing to trim at the seams
+            None => texts.push(response.text.trim().to_string()),
+        }
+    }
+
+    texts.retain(|text| !text.is_empty());
+    TranscriptionResponse {
+        text: texts.join(" "),
+        language,
+        duration: Some(duration),
+        words: has_words.then_some(words),
+        segments: has_segments.then_some(segments),
+    }
+}
+
+/// Compares words or sentences ignoring case, spacing and punctuation.
+fn same_text(a: &str, b: &str) -> bool {
+    let normalize = |s: &str| {
+        s.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect::<String>()
+    };
+    normalize(a) == normalize(b)
+}
+
+/// Chunks are written next to the full audio, in the same format.
+fn chunk_file(audio_path: &Path, index: usize) -> PathBuf {
+    let extension = audio_path.extension().and_then(|e| e.to_str()).unwrap_or("mp3");
+    audio_path.with_file_name(format!("chunk_{:03}.{}", index, extension))
+}
+
+/// Checkpoints not touched for this long are removed at startup.
+const CHECKPOINT_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+fn checkpoints_root() -> Result<PathBuf, NolanForgeError> {
+    Ok(Config::app_data_dir()?.join("transcriptions"))
+}
+
+/// Checkpoint directories claimed by running jobs of this process.
+static CLAIMED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// The chunks of a transcription finished so far, kept in the app data
+/// directory until it succeeds. Audio is extracted into the job's own
+/// [`super::workspace::Workspace`]; only the chunk records live here.
+///
+/// Transcribing the same ranges of the same sources again with the same
+/// backend, model and language picks up where the last attempt stopped.
+/// A checkpoint belongs to one job at a time: a second job transcribing the
+/// same thing while the first runs gets a directory of its own.
+pub struct Checkpoint {
+    dir: PathBuf,
+}
+
+/// A finished chunk as saved to disk.
+#[derive(Deserialize)]
+struct ChunkRecord {
+    start: f64,
+    end: f64,
+    response: TranscriptionResponse,
+}
+
+impl Checkpoint {
+    pub fn open(
+        ranges: &[SourceRange],
+        provider: &str,
+        language: Option<&str>,
+        format: AudioFormat,
+        job_id: &str,
+    ) -> Result<Self, NolanForgeError> {
+        let mut hasher = Sha256::new();
+        for range in ranges {
+            hasher.update(cache::source_fingerprint(&range.input_path)?);
+            hasher.update(format!(":{:.3}-{:?};", range.start, range.end.map(|end| format!("{:.3}", end))));
+        }
+        hasher.update([0]);
+        hasher.update(provider);
+        hasher.update([0]);
+        hasher.update(language.unwrap_or("auto"));
+        hasher.update([0]);
+        hasher.update(format.extension());
+        let key: String = hasher.finalize().iter().take(16).map(|b| format!("{:02x}", b)).collect();
+
+        let root = checkpoints_root()?;
+        let shared = root.join(&key);
+        if let Some(checkpoint) = Self::claim(shared)? {
+            return Ok(checkpoint);
+        }
+
+        println!("🔍 Debug: Checkpoint {} is in use by another transcription, not resuming", key);
+        Self::claim(root.join(format!("{}-{}", key, job_id)))?
+            .ok_or_else(|| NolanForgeError::Internal("Transcription checkpoint is already in use".to_string()))
+    }
+
+    /// Takes `dir` for this job, unless a running job owns it already.
+    fn claim(dir: PathBuf) -> Result<Option<Self>, NolanForgeError> {
+        let mut claimed = CLAIMED.lock().unwrap();
+        if claimed.contains(&dir) || owned_by_other_process(&dir) {
+            return Ok(None);
+        }
+
+        fs::create_dir_all(&dir).map_err(|e| NolanForgeError::io("Failed to create transcription checkpoint", e))?;
+        fs::write(dir.join(OWNER_FILE), std::process::id().to_string())
+            .map_err(|e| NolanForgeError::io("Failed to write checkpoint owner file", e))?;
+        claimed.push(dir.clone());
+        Ok(Some(Checkpoint { dir }))
+    }
+
+    fn chunk_record(&self, index: usize) -> PathBuf {
+        self.dir.join(format!("chunk_{:03}.done.json", index))
+    }
+
+    /// The saved transcript of a chunk, if it was cut at the same place.
+    fn load_chunk(&self, index: usize, chunk: &Chunk) -> Option<TranscriptionResponse> {
+        let content = fs::read_to_string(self.chunk_record(index)).ok()?;
+        let record: ChunkRecord = serde_json::from_str(&content).ok()?;
+        let same = (record.start - chunk.start).abs() < 1e-6 && (record.end - chunk.end).abs() < 1e-6;
+        same.then_some(record.response)
+    }
+
+    fn save_chunk(&self, index: usize, chunk: &Chunk, response: &TranscriptionResponse) -> Result<(), NolanForgeError> {
+        let record = serde_json::json!({ "start": chunk.start, "end": chunk.end, "response": response });
+        files::write_atomic(&self.chunk_record(index), record.to_string().as_bytes())
+    }
+
+    /// Removes the checkpoint once its transcript has been delivered.
+    pub fn discard(self) {
+        if let Err(e) = fs::remove_dir_all(&self.dir) {
+            eprintln!("Warning: Failed to remove transcription checkpoint: {}", e);
+        }
+    }
+}
+
+impl Drop for Checkpoint {
+    fn drop(&mut self) {
+        // Gone already when discarded
+        let _ = fs::remove_file(self.dir.join(OWNER_FILE));
+        CLAIMED.lock().unwrap().retain(|dir| dir != &self.dir);
+    }
+}
+
+/// Whether another NolanForge instance that is still running owns `dir`.
+fn owned_by_other_process(dir: &Path) -> bool {
+    fs::read_to_string(dir.join(OWNER_FILE))
+        .ok()
+        .and_then(|pid| pid.trim().parse::<u32>().ok())
+        .is_some_and(|pid| pid != std::process::id() && process_alive(pid))
+}
+
+/// Removes checkpoints of transcriptions that were never retried.
+pub fn sweep_stale_checkpoints() {
+    let Ok(entries) = checkpoints_root().and_then(|root| {
+        fs::read_dir(root).map_err(|e| NolanForgeError::io("Failed to list transcription checkpoints", e))
+    }) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        if owned_by_other_process(&entry.path()) {
+            continue;
+        }
+        let stale = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > CHECKPOINT_MAX_AGE);
+        if stale {
+            if let Err(e) = fs::remove_dir_all(entry.path()) {
+                eprintln!("Warning: Failed to remove stale transcription checkpoint: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    fn word(text: &str, start: f64, end: f64) -> Word {
+        Word {
+            word: text.to_string(),
+            start,
+            end,
+        }
+    }
+
+    fn segment(text: &str, start: f64, end: f64) -> Segment {
+        Segment {
+            id: 0,
+            seek: 0.0,
+            start,
+            end,
+            text: text.to_string(),
+            tokens: Vec::new(),
+            temperature: 0.0,
+            avg_logprob: 0.0,
+            compression_ratio: 0.0,
+            no_speech_prob: 0.0,
+        }
+    }
+
+    fn response(words: Vec<Word>, segments: Vec<Segment>) -> TranscriptionResponse {
+        TranscriptionResponse {
+            text: segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" "),
+            language: Some("en".to_string()),
+            duration: None,
+            words: Some(words),
+            segments: Some(segments),
+        }
+    }
+
+    #[test]
+    fn small_audio_is_one_chunk() {
+        let chunks = plan_chunks(60.0, 10 * MB, 25 * MB, &[]);
+        assert_eq!(
+            chunks,
+            vec![Chunk {
+                start: 0.0,
+                end: 60.0,
+                owned_from: 0.0,
+                owned_until: f64::INFINITY,
+            }]
+        );
+    }
+
+    #[test]
+    fn cut_snaps_to_the_nearest_silence() {
+        // Two chunks with the ideal cut at 50s; only the silence within 5s counts
+        let chunks = plan_chunks(100.0, 30 * MB, 25 * MB, &[(20.0, 21.0), (47.0, 48.0), (53.5, 55.5)]);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].end, 47.5);
+        assert_eq!(chunks[0].owned_until, 47.5);
+        // Cut in a silence, so no overlap
+        assert_eq!(chunks[1].start, 47.5);
+        assert_eq!(chunks[1].owned_from, 47.5);
+        assert_eq!(chunks[1].end, 100.0);
+    }
+
+    #[test]
+    fn cut_without_silence_overlaps() {
+        let chunks = plan_chunks(100.0, 30 * MB, 25 * MB, &[(30.0, 31.0)]);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].end, 50.0);
+        assert_eq!(chunks[1].owned_from, 50.0);
+        assert_eq!(chunks[1].start, 50.0 - SEAM_OVERLAP);
+    }
+
+    #[test]
+    fn overlap_is_stitched_once() {
+        let first = Chunk {
+            start: 0.0,
+            end: 50.0,
+            owned_from: 0.0,
+            owned_until: 50.0,
+        };
+        let second = Chunk {
+            start: 48.5,
+            end: 100.0,
+            owned_from: 50.0,
+            owned_until: f64::INFINITY,
+        };
+        let pieces = vec![
+            (
+                first,
+                response(
+                    vec![word("Hello", 10.0, 10.4), word("seam,", 49.9, 50.3)],
+                    vec![segment(" Hello seam,", 10.0, 50.3)],
+                ),
+            ),
+            (
+                second,
+                response(
+                    // Offsets within the chunk: 49.6s is the first chunk's,
+                    // 50.05s repeats its last word just past the cut
+                    vec![word("early", 1.1, 1.3), word("Seam", 1.55, 1.8), word("world", 2.0, 2.4)],
+                    vec![segment(" early", 1.1, 1.3), segment(" Seam world", 1.55, 2.4)],
+                ),
+            ),
+        ];
+
+        let stitched = stitch(pieces, 100.0);
+
+        let words: Vec<(&str, f64)> = stitched.words.as_ref().unwrap().iter().map(|w| (w.word.as_str(), w.start)).collect();
+        assert_eq!(words, vec![("Hello", 10.0), ("seam,", 49.9), ("world", 50.5)]);
+
+        let segments = stitched.segments.as_ref().unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1].id, 1);
+        assert!((segments[1].start - 50.05).abs() < 1e-9);
+        assert_eq!(segments[1].seek, 4850.0);
+        assert_eq!(stitched.text, "Hello seam, Seam world");
+        assert_eq!(stitched.duration, Some(100.0));
+    }
+
+    #[test]
+    fn checkpoint_ignores_chunks_cut_elsewhere() {
+        let dir = std::env::temp_dir().join(format!("nolanforge-checkpoint-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let checkpoint = Checkpoint { dir };
+        let chunk = Chunk {
+            start: 0.0,
+            end: 47.5,
+            owned_from: 0.0,
+            owned_until: 47.5,
+        };
+        checkpoint
+            .save_chunk(0, &chunk, &response(vec![word("Hello", 1.0, 1.4)], vec![segment(" Hello", 1.0, 1.4)]))
+            .unwrap();
+
+        let resumed = checkpoint.load_chunk(0, &chunk).expect("same bounds resume");
+        assert_eq!(resumed.text, " Hello");
+
+        // A different silence moved the cut, so the chunk is transcribed again
+        let moved = Chunk { end: 50.0, owned_until: 50.0, ..chunk.clone() };
+        assert!(checkpoint.load_chunk(0, &moved).is_none());
+        assert!(checkpoint.load_chunk(1, &chunk).is_none());
+
+        checkpoint.discard();
+    }
+
+    #[test]
+    fn text_compares_without_case_or_punctuation() {
+        assert!(same_text(" Seam,", "seam"));
+        assert!(!same_text("seam", "seem"));
+    }
+}
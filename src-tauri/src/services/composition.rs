@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+
+use super::probe::BasicInfo;
+use crate::error::NolanForgeError;
+
+/// Size and frame rate of the project, which every export renders to.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    pub frame_rate: f64,
+}
+
+impl Default for Canvas {
+    fn default() -> Self {
+        Canvas {
+            width: 1920,
+            height: 1080,
+            frame_rate: 30.0,
+        }
+    }
+}
+
+impl Canvas {
+    pub fn validate(&self) -> Result<(), NolanForgeError> {
+        // 4:2:0 encoders reject odd dimensions
+        if self.width == 0 || self.height == 0 || !self.width.is_multiple_of(2) || !self.height.is_multiple_of(2) {
+            return Err(NolanForgeError::InvalidInput(format!(
+                "Invalid canvas size {}x{}: width and height must be positive and even",
+                self.width, self.height
+            )));
+        }
+        if !(self.frame_rate > 0.0 && self.frame_rate <= 240.0) {
+            return Err(NolanForgeError::InvalidInput(format!("Invalid canvas frame rate: {}", self.frame_rate)));
+        }
+        Ok(())
+    }
+
+    /// A canvas matching the picture of a source file as displayed, for
+    /// exports that keep the source's format. Portrait phone footage is
+    /// stored landscape with a rotation, so quarter turns swap the sides.
+    pub fn for_source(info: &BasicInfo) -> Canvas {
+        let default = Canvas::default();
+        let (width, height) = if info.rotation % 180 == 90 {
+            (info.height, info.width)
+        } else {
+            (info.width, info.height)
+        };
+        let (default_width, default_height) = if info.rotation % 180 == 90 {
+            (default.height, default.width)
+        } else {
+            (default.width, default.height)
+        };
+        // Round odd sizes down so the encoder accepts them
+        let even = |size: Option<u32>, fallback: u32| size.map(|s| s & !1).filter(|s| *s > 0).unwrap_or(fallback);
+        Canvas {
+            width: even(width, default_width),
+            height: even(height, default_height),
+            frame_rate: info
+                .frame_rate
+                .filter(|rate| *rate <= 240.0)
+                .unwrap_or(default.frame_rate),
+        }
+    }
+
+    /// Source for a black frame covering the whole canvas.
+    pub fn color_source(&self, duration: f64) -> String {
+        format!(
+            "color=black:size={}x{}:rate={}:duration={}",
+            self.width, self.height, self.frame_rate, duration
+        )
+    }
+}
+
+/// How a clip whose aspect ratio differs from the canvas is placed on it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FitMode {
+    /// Scale to fit inside the canvas and letterbox/pillarbox the rest.
+    #[default]
+    Fit,
+    /// Scale to cover the canvas and crop what overhangs.
+    Fill,
+    /// Scale to the canvas size, ignoring the aspect ratio.
+    Stretch,
+}
+
+/// Filter chain that scales a frame into a `width` x `height` box according
+/// to `fit`.
+///
+/// FFmpeg applies the display rotation of phone footage while decoding, so
+/// portrait clips arrive here already upright.
+pub fn fit_to_box(fit: FitMode, width: i64, height: i64) -> String {
+    let (w, h) = (width, height);
+    let scale = match fit {
+        FitMode::Fit => format!(
+            "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2:color=black"
+        ),
+        FitMode::Fill => format!("scale={w}:{h}:force_original_aspect_ratio=increase,crop={w}:{h}"),
+        FitMode::Stretch => format!("scale={w}:{h}"),
+    };
+    format!("{},setsar=1", scale)
+}
+
+/// How an overlay clip is combined with what is below it. Matches the canvas
+/// composite operations the editor preview uses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Add,
+    Darken,
+    Lighten,
+    Difference,
+}
+
+impl BlendMode {
+    /// Mode name in FFmpeg's `blend` filter, `None` for a plain overlay.
+    fn ffmpeg_mode(self) -> Option<&'static str> {
+        match self {
+            BlendMode::Normal => None,
+            BlendMode::Multiply => Some("multiply"),
+            BlendMode::Screen => Some("screen"),
+            BlendMode::Overlay => Some("overlay"),
+            BlendMode::Add => Some("addition"),
+            BlendMode::Darken => Some("darken"),
+            BlendMode::Lighten => Some("lighten"),
+            BlendMode::Difference => Some("difference"),
+        }
+    }
+}
+
+/// Where and when an overlay clip appears on the canvas.
+#[derive(Debug, Clone, Copy)]
+pub struct OverlayPlacement {
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+    /// How the clip is scaled into its `width` x `height` box.
+    pub fit_mode: FitMode,
+    pub opacity: f64,
+    pub blend_mode: BlendMode,
+    /// Timeline seconds the overlay is shown for.
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Filter graph that lays the stream `overlay` over `base` according to
+/// `placement` and names the result `output`. `label` keeps the graph's
+/// intermediate pad names unique.
+pub fn composite_overlay(
+    base: &str,
+    overlay: &str,
+    output: &str,
+    label: &str,
+    placement: &OverlayPlacement,
+    canvas: &Canvas,
+) -> String {
+    let p = placement;
+    // Shift the clip to its timeline position so it starts playing when it
+    // appears instead of at the start of the export
+    let shifted = format!(
+        "[{}]setpts=PTS-STARTPTS+{}/TB,{}",
+        overlay,
+        p.start,
+        fit_to_box(p.fit_mode, p.width, p.height)
+    );
+    let enable = format!("enable='between(t,{},{})'", p.start, p.end);
+
+    let Some(mode) = p.blend_mode.ffmpeg_mode() else {
+        return format!(
+            "{},format=yuva420p,colorchannelmixer=aa={}[{label}];[{}][{label}]overlay={}:{}:eof_action=pass:{}[{}]",
+            shifted, p.opacity, base, p.x, p.y, enable, output
+        );
+    };
+
+    // The blend filter needs both inputs at the same size, so blend against
+    // the part of the canvas the overlay covers and put that back in place
+    let x0 = p.x.clamp(0, canvas.width as i64);
+    let y0 = p.y.clamp(0, canvas.height as i64);
+    let x1 = (p.x + p.width).clamp(0, canvas.width as i64);
+    let y1 = (p.y + p.height).clamp(0, canvas.height as i64);
+    if x1 <= x0 || y1 <= y0 {
+        // Entirely off the canvas
+        return format!("[{}]null[{}]", base, output);
+    }
+    let (w, h) = (x1 - x0, y1 - y0);
+
+    // Blending happens in RGB like the preview; the base is the first blend
+    // input so opacity fades the result back towards it
+    format!(
+        "{shifted},format=gbrp,crop={w}:{h}:{}:{}[{label}_top];\
+         [{base}]split[{label}_base][{label}_bg];\
+         [{label}_bg]crop={w}:{h}:{x0}:{y0},format=gbrp[{label}_region];\
+         [{label}_region][{label}_top]blend=all_mode={mode}:all_opacity={}:eof_action=pass[{label}_blended];\
+         [{label}_base][{label}_blended]overlay={x0}:{y0}:eof_action=pass:{enable}[{output}]",
+        x0 - p.x,
+        y0 - p.y,
+        p.opacity,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(width: u32, height: u32, rotation: u32) -> BasicInfo {
+        BasicInfo {
+            duration: Some(10.0),
+            start_time: 0.0,
+            video_codec: Some("h264".to_string()),
+            video_profile: Some("High".to_string()),
+            video_level: Some(40),
+            pixel_format: Some("yuv420p".to_string()),
+            width: Some(width),
+            height: Some(height),
+            rotation,
+            frame_rate: Some(30.0),
+            has_audio: true,
+        }
+    }
+
+    #[test]
+    fn canvas_follows_display_orientation() {
+        let portrait = Canvas::for_source(&source(1920, 1080, 90));
+        assert_eq!((portrait.width, portrait.height), (1080, 1920));
+
+        let upside_down = Canvas::for_source(&source(1920, 1080, 180));
+        assert_eq!((upside_down.width, upside_down.height), (1920, 1080));
+
+        let counter_clockwise = Canvas::for_source(&source(1920, 1080, 270));
+        assert_eq!((counter_clockwise.width, counter_clockwise.height), (1080, 1920));
+    }
+}
@@ -0,0 +1,313 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use super::files;
+use crate::config::Config;
+use crate::error::NolanForgeError;
+
+/// Output encoding settings accepted by every export command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncodingProfile {
+    pub id: String,
+    pub name: String,
+    pub video_codec: String,
+    pub preset: Option<String>,
+    /// Constant rate factor. Mutually exclusive with `video_bitrate`.
+    pub crf: Option<u32>,
+    /// Target video bitrate in FFmpeg notation, e.g. `"8M"`.
+    pub video_bitrate: Option<String>,
+    pub pixel_format: Option<String>,
+    pub audio_codec: String,
+    /// Audio bitrate in FFmpeg notation, e.g. `"192k"`.
+    pub audio_bitrate: Option<String>,
+    /// One of `mp4`, `mov`, `mkv` or `webm`.
+    pub container: String,
+    /// Move the index to the front of MP4/MOV files for progressive playback.
+    pub faststart: bool,
+    #[serde(default)]
+    pub built_in: bool,
+}
+
+impl Default for EncodingProfile {
+    /// The settings exports used before profiles existed.
+    fn default() -> Self {
+        EncodingProfile {
+            id: "default".to_string(),
+            name: "Default (H.264 / AAC)".to_string(),
+            video_codec: "libx264".to_string(),
+            preset: Some("fast".to_string()),
+            crf: None,
+            video_bitrate: None,
+            pixel_format: None,
+            audio_codec: "aac".to_string(),
+            audio_bitrate: None,
+            container: "mp4".to_string(),
+            faststart: false,
+            built_in: true,
+        }
+    }
+}
+
+impl EncodingProfile {
+    pub fn built_in_profiles() -> Vec<EncodingProfile> {
+        vec![
+            EncodingProfile::default(),
+            EncodingProfile {
+                id: "youtube_1080p".to_string(),
+                name: "YouTube (H.264 high quality)".to_string(),
+                preset: Some("slow".to_string()),
+                crf: Some(18),
+                pixel_format: Some("yuv420p".to_string()),
+                audio_bitrate: Some("384k".to_string()),
+                faststart: true,
+                ..EncodingProfile::default()
+            },
+            EncodingProfile {
+                id: "social".to_string(),
+                name: "Social media (H.264, 6 Mbps)".to_string(),
+                preset: Some("medium".to_string()),
+                video_bitrate: Some("6M".to_string()),
+                pixel_format: Some("yuv420p".to_string()),
+                audio_bitrate: Some("128k".to_string()),
+                faststart: true,
+                ..EncodingProfile::default()
+            },
+            EncodingProfile {
+                id: "archive".to_string(),
+                name: "Archive (near-lossless H.264 / FLAC, MKV)".to_string(),
+                preset: Some("slow".to_string()),
+                crf: Some(10),
+                audio_codec: "flac".to_string(),
+                container: "mkv".to_string(),
+                ..EncodingProfile::default()
+            },
+            EncodingProfile {
+                id: "hevc".to_string(),
+                name: "HEVC (H.265 / AAC)".to_string(),
+                video_codec: "libx265".to_string(),
+                preset: Some("medium".to_string()),
+                crf: Some(24),
+                pixel_format: Some("yuv420p".to_string()),
+                audio_bitrate: Some("192k".to_string()),
+                faststart: true,
+                ..EncodingProfile::default()
+            },
+        ]
+    }
+
+    pub fn validate(&self) -> Result<(), NolanForgeError> {
+        if self.id.trim().is_empty() || self.name.trim().is_empty() {
+            return Err(NolanForgeError::InvalidInput("Encoding profile needs an ID and a name".to_string()));
+        }
+        if self.video_codec.trim().is_empty() || self.audio_codec.trim().is_empty() {
+            return Err(NolanForgeError::InvalidInput(format!(
+                "Encoding profile '{}' needs a video and an audio codec",
+                self.name
+            )));
+        }
+        if self.crf.is_some() && self.video_bitrate.is_some() {
+            return Err(NolanForgeError::InvalidInput(format!(
+                "Encoding profile '{}' sets both CRF and a video bitrate; choose one",
+                self.name
+            )));
+        }
+        if matches!(self.crf, Some(crf) if crf > 63) {
+            return Err(NolanForgeError::InvalidInput(format!("Encoding profile '{}' has a CRF above 63", self.name)));
+        }
+        if container_format(&self.container).is_none() {
+            return Err(NolanForgeError::InvalidInput(format!("Unsupported container: {}", self.container)));
+        }
+        for (codec, video) in [(&self.video_codec, true), (&self.audio_codec, false)] {
+            if !fits_container(codec, &self.container, video) {
+                return Err(NolanForgeError::InvalidInput(format!(
+                    "Encoding profile '{}': {} cannot be stored in {}",
+                    self.name, codec, self.container
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn video_args(&self) -> Vec<String> {
+        let mut args = vec!["-c:v".to_string(), self.video_codec.clone()];
+        if let Some(preset) = &self.preset {
+            args.extend(["-preset".to_string(), preset.clone()]);
+        }
+        if let Some(crf) = self.crf {
+            args.extend(["-crf".to_string(), crf.to_string()]);
+        }
+        if let Some(bitrate) = &self.video_bitrate {
+            args.extend(["-b:v".to_string(), bitrate.clone()]);
+        }
+        if let Some(pixel_format) = &self.pixel_format {
+            args.extend(["-pix_fmt".to_string(), pixel_format.clone()]);
+        }
+        // Apple players only recognise HEVC in MP4/MOV with the hvc1 tag
+        if self.video_codec == "libx265" && matches!(self.container.as_str(), "mp4" | "mov") {
+            args.extend(["-tag:v".to_string(), "hvc1".to_string()]);
+        }
+        args
+    }
+
+    pub fn audio_args(&self) -> Vec<String> {
+        let mut args = vec!["-c:a".to_string(), self.audio_codec.clone()];
+        if let Some(bitrate) = &self.audio_bitrate {
+            args.extend(["-b:a".to_string(), bitrate.clone()]);
+        }
+        args
+    }
+
+    pub fn container_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(format) = container_format(&self.container) {
+            args.extend(["-f".to_string(), format.to_string()]);
+        }
+        if self.faststart && matches!(self.container.as_str(), "mp4" | "mov") {
+            args.extend(["-movflags".to_string(), "+faststart".to_string()]);
+        }
+        args
+    }
+
+    /// Video, audio and container arguments for an encoded output.
+    pub fn output_args(&self) -> Vec<String> {
+        let mut args = self.video_args();
+        args.extend(self.audio_args());
+        args.extend(self.container_args());
+        args
+    }
+}
+
+/// Maps a container name to FFmpeg's muxer name.
+fn container_format(container: &str) -> Option<&'static str> {
+    match container {
+        "mp4" => Some("mp4"),
+        "mov" => Some("mov"),
+        "mkv" => Some("matroska"),
+        "webm" => Some("webm"),
+        _ => None,
+    }
+}
+
+/// Whether FFmpeg can mux `codec` into `container`. Only known-bad pairs are rejected,
+/// except for WebM, which takes nothing but VP8/VP9/AV1 and Opus/Vorbis.
+fn fits_container(codec: &str, container: &str, video: bool) -> bool {
+    match container {
+        "webm" if video => matches!(codec, "libvpx" | "libvpx-vp9" | "libaom-av1" | "libsvtav1" | "copy"),
+        "webm" => matches!(codec, "libopus" | "libvorbis" | "copy"),
+        "mp4" => !codec.starts_with("pcm_") && !matches!(codec, "prores" | "prores_ks" | "prores_aw" | "libvorbis"),
+        "mov" => !matches!(codec, "libopus" | "libvorbis" | "flac"),
+        _ => true,
+    }
+}
+
+fn user_profiles_file() -> Result<PathBuf, NolanForgeError> {
+    Ok(Config::app_data_dir()?.join("encoding_profiles.json"))
+}
+
+/// Loads the user-defined profiles stored in the NolanForge data directory.
+pub fn load_user_profiles() -> Result<Vec<EncodingProfile>, NolanForgeError> {
+    let file = user_profiles_file()?;
+    if !file.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&file)
+        .map_err(|e| NolanForgeError::io("Failed to read encoding profiles", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| NolanForgeError::Config(format!("Failed to parse encoding profiles: {}", e)))
+}
+
+fn save_user_profiles(profiles: &[EncodingProfile]) -> Result<(), NolanForgeError> {
+    let content = serde_json::to_string_pretty(profiles)
+        .map_err(|e| NolanForgeError::Internal(format!("Failed to serialize encoding profiles: {}", e)))?;
+    // A crash mid-save must not lose every saved profile
+    files::write_atomic(&user_profiles_file()?, content.as_bytes())
+}
+
+/// Built-in profiles followed by the user's own.
+pub fn all_profiles() -> Result<Vec<EncodingProfile>, NolanForgeError> {
+    let mut profiles = EncodingProfile::built_in_profiles();
+    profiles.extend(load_user_profiles()?);
+    Ok(profiles)
+}
+
+/// Adds a user profile, or replaces the one with the same ID.
+pub fn upsert_user_profile(mut profile: EncodingProfile) -> Result<(), NolanForgeError> {
+    profile.validate()?;
+    if EncodingProfile::built_in_profiles().iter().any(|p| p.id == profile.id) {
+        return Err(NolanForgeError::InvalidInput(format!(
+            "'{}' is a built-in profile and cannot be changed",
+            profile.id
+        )));
+    }
+    profile.built_in = false;
+
+    let mut profiles = load_user_profiles()?;
+    match profiles.iter_mut().find(|p| p.id == profile.id) {
+        Some(existing) => *existing = profile,
+        None => profiles.push(profile),
+    }
+    save_user_profiles(&profiles)
+}
+
+pub fn delete_user_profile(id: &str) -> Result<(), NolanForgeError> {
+    let mut profiles = load_user_profiles()?;
+    let count = profiles.len();
+    profiles.retain(|p| p.id != id);
+    if profiles.len() == count {
+        return Err(NolanForgeError::InvalidInput(format!("No user encoding profile with ID {}", id)));
+    }
+    save_user_profiles(&profiles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(video_codec: &str, audio_codec: &str, container: &str) -> EncodingProfile {
+        EncodingProfile {
+            id: "custom".to_string(),
+            name: "Custom".to_string(),
+            video_codec: video_codec.to_string(),
+            audio_codec: audio_codec.to_string(),
+            container: container.to_string(),
+            built_in: false,
+            ..EncodingProfile::default()
+        }
+    }
+
+    #[test]
+    fn built_in_profiles_are_valid() {
+        for profile in EncodingProfile::built_in_profiles() {
+            assert!(profile.validate().is_ok(), "{} is invalid", profile.id);
+        }
+    }
+
+    #[test]
+    fn rejects_codecs_the_container_cannot_hold() {
+        for (video, audio, container) in [
+            ("libx264", "aac", "webm"),
+            ("libvpx-vp9", "aac", "webm"),
+            ("libx264", "pcm_s16le", "mp4"),
+            ("prores_ks", "aac", "mp4"),
+            ("libx264", "libopus", "mov"),
+        ] {
+            assert!(
+                matches!(profile(video, audio, container).validate(), Err(NolanForgeError::InvalidInput(_))),
+                "{} + {} in {} passed",
+                video,
+                audio,
+                container
+            );
+        }
+        for (video, audio, container) in [
+            ("libvpx-vp9", "libopus", "webm"),
+            ("libaom-av1", "libvorbis", "webm"),
+            ("prores_ks", "pcm_s16le", "mov"),
+            ("libx264", "flac", "mkv"),
+        ] {
+            assert!(profile(video, audio, container).validate().is_ok(), "{} + {} in {} failed", video, audio, container);
+        }
+    }
+}
@@ -0,0 +1,316 @@
+use serde::Serialize;
+use std::process::Stdio;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::{Child, Command};
+
+use super::jobs::Job;
+use super::toolchain::Ffmpeg;
+use crate::error::NolanForgeError;
+
+/// Event emitted while an export is running.
+pub const EXPORT_PROGRESS_EVENT: &str = "export-progress";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportProgress {
+    pub job_id: String,
+    pub stage: String,
+    pub percent: f64,
+    pub stage_percent: f64,
+    pub frame: Option<u64>,
+    pub out_time: f64,
+    pub speed: Option<f64>,
+    pub eta_seconds: Option<f64>,
+}
+
+/// Tracks overall progress of a multi-step export.
+///
+/// Every FFmpeg invocation is weighted by the amount of media it has to
+/// produce, so a 10 minute composite counts for more than a 5 second trim.
+pub struct ProgressReporter<'a> {
+    app: AppHandle,
+    event: &'static str,
+    job: &'a Job,
+    total_work: f64,
+    completed_work: f64,
+    started_at: Instant,
+    /// Labels for parts of the next FFmpeg run by the output time they start
+    /// at, so a single pass over many clips still says which one it is on.
+    stage_labels: Vec<(f64, String)>,
+}
+
+impl<'a> ProgressReporter<'a> {
+    pub fn new(app: &AppHandle, job: &'a Job, total_work: f64) -> Self {
+        Self::with_event(app, EXPORT_PROGRESS_EVENT, job, total_work)
+    }
+
+    /// A reporter for jobs other than exports, emitting [`ExportProgress`]
+    /// payloads as `event` instead.
+    pub fn with_event(app: &AppHandle, event: &'static str, job: &'a Job, total_work: f64) -> Self {
+        ProgressReporter {
+            app: app.clone(),
+            event,
+            job,
+            total_work: total_work.max(f64::EPSILON),
+            completed_work: 0.0,
+            started_at: Instant::now(),
+            stage_labels: Vec::new(),
+        }
+    }
+
+    /// Reports the next stage under `labels` instead of its own name, each
+    /// from the output time it starts at. Cleared once the stage completes.
+    pub fn set_stage_labels(&mut self, labels: Vec<(f64, String)>) {
+        self.stage_labels = labels;
+    }
+
+    fn report(&self, stage: &str, stage_duration: f64, sample: &ProgressSample) {
+        let stage_percent = if stage_duration > 0.0 {
+            (sample.out_time / stage_duration * 100.0).clamp(0.0, 100.0)
+        } else {
+            0.0
+        };
+        let done = self.completed_work + stage_duration * stage_percent / 100.0;
+        let percent = (done / self.total_work * 100.0).clamp(0.0, 100.0);
+
+        let elapsed = self.started_at.elapsed().as_secs_f64();
+        let eta_seconds = if percent > 0.5 && percent < 100.0 {
+            Some(elapsed * (100.0 - percent) / percent)
+        } else {
+            None
+        };
+
+        let stage = self
+            .stage_labels
+            .iter()
+            .rev()
+            .find(|(from, _)| sample.out_time >= *from)
+            .map_or(stage, |(_, label)| label.as_str());
+
+        self.emit(ExportProgress {
+            job_id: self.job.id().to_string(),
+            stage: stage.to_string(),
+            percent,
+            stage_percent,
+            frame: sample.frame,
+            out_time: sample.out_time,
+            speed: sample.speed,
+            eta_seconds,
+        });
+    }
+
+    fn finish_stage(&mut self, stage_duration: f64) {
+        self.completed_work = (self.completed_work + stage_duration).min(self.total_work);
+        self.stage_labels.clear();
+    }
+
+    /// Emits the final 100% event once every stage has completed.
+    pub fn finish(&self, stage: &str) {
+        self.emit(ExportProgress {
+            job_id: self.job.id().to_string(),
+            stage: stage.to_string(),
+            percent: 100.0,
+            stage_percent: 100.0,
+            frame: None,
+            out_time: 0.0,
+            speed: None,
+            eta_seconds: Some(0.0),
+        });
+    }
+
+    fn emit(&self, progress: ExportProgress) {
+        if let Err(e) = self.app.emit(self.event, progress) {
+            eprintln!("Warning: Failed to emit {}: {}", self.event, e);
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct ProgressSample {
+    frame: Option<u64>,
+    out_time: f64,
+    speed: Option<f64>,
+}
+
+impl ProgressSample {
+    /// Reads one `key=value` line of `-progress` output. Returns true on the
+    /// `progress=` line that closes each block, i.e. when the sample is complete.
+    fn apply(&mut self, line: &str) -> bool {
+        let Some((key, value)) = line.split_once('=') else {
+            return false;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "frame" => self.frame = value.parse().ok(),
+            // Despite its name, out_time_ms is reported in microseconds
+            "out_time_us" | "out_time_ms" => {
+                if let Ok(us) = value.parse::<i64>() {
+                    self.out_time = us.max(0) as f64 / 1_000_000.0;
+                }
+            }
+            "speed" => self.speed = value.trim_end_matches('x').trim().parse().ok(),
+            "progress" => return true,
+            _ => {}
+        }
+        false
+    }
+}
+
+/// Creates an FFmpeg command that writes machine-readable progress to stdout.
+pub fn ffmpeg_command(ffmpeg: &Ffmpeg) -> Command {
+    let mut cmd = ffmpeg.command();
+    cmd.arg("-hide_banner")
+        .arg("-nostats")
+        .arg("-progress")
+        .arg("pipe:1");
+    cmd
+}
+
+/// Runs an FFmpeg command built with [`ffmpeg_command`], forwarding its
+/// progress output to the reporter.
+///
+/// `stage_duration` is the length in seconds of the media this invocation
+/// produces. On failure the error carries FFmpeg's stderr. If the job is
+/// cancelled the FFmpeg process tree is killed and an error is returned.
+pub async fn run_ffmpeg(
+    mut cmd: Command,
+    reporter: &mut ProgressReporter<'_>,
+    stage: &str,
+    stage_duration: f64,
+) -> Result<(), NolanForgeError> {
+    if reporter.job.is_cancelled() {
+        return Err(NolanForgeError::Cancelled);
+    }
+
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    // Put FFmpeg in its own process group so cancelling can take down
+    // anything it spawned as well
+    #[cfg(unix)]
+    cmd.process_group(0);
+
+    let mut child = cmd.spawn().map_err(|e| NolanForgeError::launch("FFmpeg", e))?;
+
+    // Drain stderr concurrently so FFmpeg never blocks on a full pipe
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let stderr_task = tokio::spawn(async move {
+        let mut buf = String::new();
+        let _ = stderr.read_to_string(&mut buf).await;
+        buf
+    });
+
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let mut lines = BufReader::new(stdout).lines();
+    let mut sample = ProgressSample::default();
+
+    loop {
+        let line = tokio::select! {
+            line = lines.next_line() => line,
+            _ = reporter.job.cancelled() => {
+                kill_process_tree(&mut child).await;
+                return Err(NolanForgeError::Cancelled);
+            }
+        };
+        let Ok(Some(line)) = line else {
+            break;
+        };
+        if sample.apply(&line) {
+            reporter.report(stage, stage_duration, &sample);
+        }
+    }
+
+    let status = tokio::select! {
+        status = child.wait() => status.map_err(|e| NolanForgeError::io("Failed to wait for FFmpeg", e))?,
+        _ = reporter.job.cancelled() => {
+            kill_process_tree(&mut child).await;
+            return Err(NolanForgeError::Cancelled);
+        }
+    };
+    let stderr_output = stderr_task.await.unwrap_or_default();
+
+    if !status.success() {
+        return Err(NolanForgeError::ffmpeg(format!("{} failed", stage), stderr_output));
+    }
+
+    reporter.finish_stage(stage_duration);
+    Ok(())
+}
+
+/// Kills FFmpeg together with any processes it spawned.
+async fn kill_process_tree(child: &mut Child) {
+    if let Some(pid) = child.id() {
+        #[cfg(unix)]
+        {
+            // The child leads its own process group, see run_ffmpeg
+            unsafe {
+                libc::kill(-(pid as i32), libc::SIGKILL);
+            }
+        }
+
+        #[cfg(windows)]
+        {
+            let _ = Command::new("taskkill")
+                .args(["/PID", &pid.to_string(), "/T", "/F"])
+                .output()
+                .await;
+        }
+    }
+
+    let _ = child.kill().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds `block` to a sample, returning the lines that completed it.
+    fn apply_all(sample: &mut ProgressSample, block: &str) -> Vec<String> {
+        block
+            .lines()
+            .filter(|line| sample.apply(line))
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn reads_a_progress_block() {
+        let mut sample = ProgressSample::default();
+        let ends = apply_all(
+            &mut sample,
+            "frame=240\nfps=59.94\nbitrate=4821.3kbits/s\nout_time_us=8008000\n\
+             out_time_ms=8008000\nout_time=00:00:08.008000\nspeed=2.01x\nprogress=continue\n",
+        );
+        assert_eq!(ends, vec!["progress=continue"]);
+        assert_eq!(sample.frame, Some(240));
+        assert_eq!(sample.out_time, 8.008);
+        assert_eq!(sample.speed, Some(2.01));
+    }
+
+    #[test]
+    fn last_block_ends_with_progress_end() {
+        let mut sample = ProgressSample::default();
+        apply_all(&mut sample, "out_time_us=1000000\nspeed=1x\nprogress=continue\n");
+
+        // FFmpeg reports N/A before the first packet and may do so again at
+        // the end; the last known position is kept
+        let ends = apply_all(&mut sample, "frame=30\nout_time_us=N/A\nspeed=N/A\nprogress=end\n");
+        assert_eq!(ends, vec!["progress=end"]);
+        assert_eq!(sample.out_time, 1.0);
+        assert_eq!(sample.speed, None);
+        assert_eq!(sample.frame, Some(30));
+    }
+
+    #[test]
+    fn negative_out_time_counts_as_zero() {
+        // Reported while the first frames of an edit list are still being skipped
+        let mut sample = ProgressSample::default();
+        assert!(!sample.apply("out_time_us=500000"));
+        assert!(!sample.apply("out_time_us=-23220"));
+        assert_eq!(sample.out_time, 0.0);
+        assert!(!sample.apply("not a progress line"));
+    }
+}
@@ -0,0 +1,29 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use crate::error::NolanForgeError;
+
+/// Writes `contents` to a temporary file next to `path` and renames it into
+/// place, so a crash mid-write leaves either the old file or the new one,
+/// never a truncated mix.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), NolanForgeError> {
+    let write_error = |e| NolanForgeError::io(format!("Failed to write {}", path.display()), e);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(write_error)?;
+    }
+
+    let temp_path = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+    let result = File::create(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            // Make sure the data is on disk before the rename makes it visible
+            file.sync_all()
+        })
+        .and_then(|_| fs::rename(&temp_path, path));
+
+    result.map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        write_error(e)
+    })
+}
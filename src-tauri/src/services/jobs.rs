@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::NolanForgeError;
+
+/// Event emitted with the job ID as soon as an export has been registered.
+pub const EXPORT_STARTED_EVENT: &str = "export-started";
+
+/// A long-running export that can be cancelled from the frontend.
+pub struct Job {
+    id: String,
+    cancel_token: CancellationToken,
+    cleanup_paths: Mutex<Vec<PathBuf>>,
+}
+
+impl Job {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_token.is_cancelled()
+    }
+
+    /// Resolves once the job has been cancelled.
+    pub async fn cancelled(&self) {
+        self.cancel_token.cancelled().await
+    }
+
+    /// Registers a file or directory that must be removed if the job is
+    /// cancelled (intermediate files, partially written output).
+    pub fn track_path(&self, path: impl Into<PathBuf>) {
+        self.cleanup_paths.lock().unwrap().push(path.into());
+    }
+
+    fn remove_tracked_paths(&self) {
+        for path in self.cleanup_paths.lock().unwrap().drain(..) {
+            let result = if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else if path.exists() {
+                fs::remove_file(&path)
+            } else {
+                Ok(())
+            };
+            if let Err(e) = result {
+                eprintln!("Warning: Failed to clean up {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// Registry of running jobs, kept in Tauri managed state.
+#[derive(Default)]
+pub struct JobRegistry {
+    jobs: Arc<Mutex<HashMap<String, Arc<Job>>>>,
+}
+
+impl JobRegistry {
+    /// Registers a new job and returns a guard that unregisters it when
+    /// dropped.
+    pub fn start(&self) -> JobGuard {
+        let job = Arc::new(Job {
+            id: uuid::Uuid::new_v4().to_string(),
+            cancel_token: CancellationToken::new(),
+            cleanup_paths: Mutex::new(Vec::new()),
+        });
+
+        self.jobs.lock().unwrap().insert(job.id.clone(), job.clone());
+
+        JobGuard {
+            job,
+            jobs: self.jobs.clone(),
+        }
+    }
+
+    /// Requests cancellation of a running job. The job's FFmpeg process is
+    /// killed by whoever is driving it.
+    pub fn cancel(&self, job_id: &str) -> Result<(), NolanForgeError> {
+        let jobs = self.jobs.lock().unwrap();
+        let job = jobs
+            .get(job_id)
+            .ok_or_else(|| NolanForgeError::InvalidInput(format!("No running job with ID {}", job_id)))?;
+        job.cancel_token.cancel();
+        Ok(())
+    }
+}
+
+/// Keeps a job registered for as long as the export is running.
+///
+/// When dropped the job is removed from the registry, and if it was
+/// cancelled its tracked paths are deleted.
+pub struct JobGuard {
+    job: Arc<Job>,
+    jobs: Arc<Mutex<HashMap<String, Arc<Job>>>>,
+}
+
+impl JobGuard {
+    /// A shared handle to the job for tasks spawned while it runs.
+    pub fn handle(&self) -> Arc<Job> {
+        self.job.clone()
+    }
+}
+
+impl std::ops::Deref for JobGuard {
+    type Target = Job;
+
+    fn deref(&self) -> &Job {
+        &self.job
+    }
+}
+
+impl Drop for JobGuard {
+    fn drop(&mut self) {
+        self.jobs.lock().unwrap().remove(&self.job.id);
+        if self.job.is_cancelled() {
+            self.job.remove_tracked_paths();
+        }
+    }
+}
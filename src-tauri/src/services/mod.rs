@@ -1 +1,22 @@
+pub mod autosave;
+pub mod cache;
+pub mod chunking;
+pub mod composition;
+pub mod encoding;
+pub mod ffmpeg;
+pub mod files;
+pub mod jobs;
 pub mod openai;
+pub mod preflight;
+pub mod probe;
+pub mod project;
+pub mod proxy;
+pub mod relink;
+pub mod thumbnails;
+pub mod timeline;
+pub mod toolchain;
+pub mod transcription;
+pub mod trim;
+pub mod waveform;
+pub mod whisper_cpp;
+pub mod workspace;
@@ -1,99 +1,241 @@
-use serde::{Deserialize, Serialize};
-use std::path::Path;
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TranscriptionResponse {
-    pub text: String,
-    pub language: Option<String>,
-    pub duration: Option<f64>,
-    pub words: Option<Vec<Word>>,
-    pub segments: Option<Vec<Segment>>,
+use std::time::Duration;
+
+use super::transcription::{TranscriptionProvider, TranscriptionRequest, TranscriptionResponse};
+use crate::error::NolanForgeError;
+
+/// Where the OpenAI API lives.
+pub const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+
+/// The Whisper API rejects files over 25 MB.
+pub const OPENAI_MAX_UPLOAD_BYTES: u64 = 25 * 1024 * 1024;
+
+/// Attempts per request, including the first.
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Covers uploading a full 25 MB chunk on a slow connection and the
+/// server transcribing it.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Transcribes through `POST {base_url}/audio/transcriptions`, which the
+/// OpenAI Whisper API and compatible servers (faster-whisper servers, local
+/// gateways) all accept.
+pub struct OpenAiProvider {
+    name: String,
+    base_url: String,
+    /// Self-hosted servers often need none.
+    api_key: Option<String>,
+    model: String,
+    max_upload_bytes: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Word {
-    pub word: String,
-    pub start: f64,
-    pub end: f64,
+impl OpenAiProvider {
+    pub fn whisper_api(api_key: String, model: String) -> Self {
+        OpenAiProvider {
+            name: format!("OpenAI Whisper API ({})", model),
+            base_url: OPENAI_BASE_URL.to_string(),
+            api_key: Some(api_key),
+            model,
+            max_upload_bytes: Some(OPENAI_MAX_UPLOAD_BYTES),
+        }
+    }
+
+    /// A server at `base_url`, e.g. `http://localhost:8000/v1`.
+    pub fn compatible(base_url: String, api_key: Option<String>, model: String) -> Self {
+        OpenAiProvider {
+            name: format!("OpenAI-compatible server at {} ({})", base_url, model),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.filter(|key| !key.trim().is_empty()),
+            model,
+            // Self-hosted servers set their own limits, if any
+            max_upload_bytes: None,
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Segment {
-    pub id: i32,
-    pub seek: f64,
-    pub start: f64,
-    pub end: f64,
-    pub text: String,
-    pub tokens: Vec<i32>,
-    pub temperature: f64,
-    pub avg_logprob: f64,
-    pub compression_ratio: f64,
-    pub no_speech_prob: f64,
+impl TranscriptionProvider for OpenAiProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn max_upload_bytes(&self) -> Option<u64> {
+        self.max_upload_bytes
+    }
+
+    async fn transcribe(&self, request: &TranscriptionRequest<'_>) -> Result<TranscriptionResponse, NolanForgeError> {
+        let client = reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| NolanForgeError::Network(e.to_string()))?;
+
+        println!("🔍 Debug: Sending transcription request to {}...", self.name);
+        println!("🔍 Debug: Audio file: {}", request.audio_path.display());
+        println!("🔍 Debug: Model: {}", self.model);
+
+        let mut attempt = 1;
+        loop {
+            let sent = tokio::select! {
+                sent = self.send(&client, request) => sent,
+                _ = request.job.cancelled() => return Err(NolanForgeError::Cancelled),
+            };
+            let (error, retry_after) = match sent {
+                Ok(transcription) => {
+                    println!("🔍 Debug: Transcription completed successfully");
+                    println!("🔍 Debug: Text length: {} characters", transcription.text.len());
+                    return Ok(transcription);
+                }
+                Err(failure) => failure,
+            };
+
+            if attempt >= MAX_ATTEMPTS || !should_retry(&error) {
+                return Err(error);
+            }
+
+            // Exponential backoff, unless the server says how long to wait
+            let delay = retry_after
+                .unwrap_or_else(|| INITIAL_BACKOFF * 2u32.pow(attempt - 1))
+                .min(MAX_BACKOFF);
+            eprintln!(
+                "Warning: Transcription attempt {} of {} failed ({}), retrying in {:.1}s",
+                attempt,
+                MAX_ATTEMPTS,
+                error,
+                delay.as_secs_f64()
+            );
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = request.job.cancelled() => return Err(NolanForgeError::Cancelled),
+            }
+            attempt += 1;
+        }
+    }
 }
 
-pub async fn transcribe_audio(
-    audio_path: &Path,
-    api_key: &str,
-    model: &str,
-    language: Option<&str>,
-) -> Result<TranscriptionResponse, String> {
-    use reqwest::Client;
-    use tokio::fs::File;
-    use tokio_util::codec::{BytesCodec, FramedRead};
-
-    let client = Client::new();
-    
-    // Read the audio file
-    let file = File::open(audio_path)
-        .await
-        .map_err(|e| format!("Failed to open audio file: {}", e))?;
-    
-    let stream = FramedRead::new(file, BytesCodec::new());
-    let file_body = reqwest::Body::wrap_stream(stream);
-    
-    // Create multipart form
-    let part = reqwest::multipart::Part::stream(file_body)
-        .file_name(audio_path.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("audio.mp3")
-            .to_string());
-    
-    let mut form = reqwest::multipart::Form::new()
-        .part("file", part)
-        .text("model", model.to_string())
-        .text("response_format", "verbose_json".to_string())
-        .text("timestamp_granularities", r#"["word", "segment"]"#.to_string());
-    
-    // Add language if specified
-    if let Some(lang) = language {
-        form = form.text("language", lang.to_string());
+impl OpenAiProvider {
+    /// Makes one request. Errors come with the server's `Retry-After`, if any.
+    async fn send(
+        &self,
+        client: &reqwest::Client,
+        request: &TranscriptionRequest<'_>,
+    ) -> Result<TranscriptionResponse, (NolanForgeError, Option<Duration>)> {
+        use tokio::fs::File;
+        use tokio_util::codec::{BytesCodec, FramedRead};
+
+        let audio_path = request.audio_path;
+
+        // Read the audio file; the stream is used up by each attempt
+        let file = File::open(audio_path)
+            .await
+            .map_err(|e| (NolanForgeError::io("Failed to open audio file", e), None))?;
+
+        let stream = FramedRead::new(file, BytesCodec::new());
+        let file_body = reqwest::Body::wrap_stream(stream);
+
+        // Create multipart form
+        let part = reqwest::multipart::Part::stream(file_body)
+            .file_name(audio_path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("audio.mp3")
+                .to_string());
+
+        let mut form = reqwest::multipart::Form::new()
+            .part("file", part)
+            .text("model", self.model.clone())
+            .text("response_format", "verbose_json".to_string())
+            .text("timestamp_granularities", r#"["word", "segment"]"#.to_string());
+
+        // Add language if specified
+        if let Some(lang) = request.language {
+            form = form.text("language", lang.to_string());
+        }
+
+        let mut http_request = client
+            .post(format!("{}/audio/transcriptions", self.base_url))
+            .multipart(form);
+        if let Some(api_key) = &self.api_key {
+            http_request = http_request.header("Authorization", format!("Bearer {}", api_key));
+        }
+        let response = http_request
+            .send()
+            .await
+            .map_err(|e| (NolanForgeError::Network(e.to_string()), None))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after);
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err((NolanForgeError::openai(status.as_u16(), error_text), retry_after));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| {
+                // A body cut off mid-way is a network problem, not a bad response
+                let error = if e.is_decode() {
+                    NolanForgeError::Internal(format!("Failed to parse transcription response: {}", e))
+                } else {
+                    NolanForgeError::Network(e.to_string())
+                };
+                (error, None)
+            })
+    }
+}
+
+/// Rate limits are retried, but a 429 for an exhausted quota will not go
+/// away by waiting.
+fn should_retry(error: &NolanForgeError) -> bool {
+    match error {
+        NolanForgeError::OpenAiRateLimited { diagnostic } => !diagnostic.contains("insufficient_quota"),
+        _ => error.retryable(),
     }
-    
-    println!("🔍 Debug: Sending transcription request to OpenAI...");
-    println!("🔍 Debug: Audio file: {}", audio_path.display());
-    println!("🔍 Debug: Model: {}", model);
-    
-    let response = client
-        .post("https://api.openai.com/v1/audio/transcriptions")
-        .header("Authorization", format!("Bearer {}", api_key))
-        .multipart(form)
-        .send()
-        .await
-        .map_err(|e| format!("API request failed: {}", e))?;
-    
-    if !response.status().is_success() {
-        let status = response.status();
-        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("OpenAI API error ({}): {}", status, error_text));
+}
+
+/// `Retry-After` in seconds. The HTTP-date form is not used by OpenAI and
+/// falls back to the usual backoff.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+        .map(|seconds| Duration::from_secs_f64(seconds.min(MAX_BACKOFF.as_secs_f64())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_is_read_in_seconds() {
+        assert_eq!(parse_retry_after("7"), Some(Duration::from_secs(7)));
+        assert_eq!(parse_retry_after(" 1.5 "), Some(Duration::from_millis(1500)));
+        // Never wait longer than the backoff cap
+        assert_eq!(parse_retry_after("3600"), Some(MAX_BACKOFF));
+    }
+
+    #[test]
+    fn unusable_retry_after_falls_back_to_backoff() {
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+        assert_eq!(parse_retry_after("-1"), None);
+        assert_eq!(parse_retry_after("NaN"), None);
+        assert_eq!(parse_retry_after(""), None);
+    }
+
+    #[test]
+    fn exhausted_quota_is_not_retried() {
+        let rate_limited = NolanForgeError::openai(429, "Rate limit reached for requests".to_string());
+        let no_quota = NolanForgeError::openai(429, r#"{"error":{"code":"insufficient_quota"}}"#.to_string());
+        assert!(should_retry(&rate_limited));
+        assert!(!should_retry(&no_quota));
+        assert!(should_retry(&NolanForgeError::openai(503, String::new())));
+        assert!(!should_retry(&NolanForgeError::openai(400, String::new())));
     }
-    
-    let transcription: TranscriptionResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
-    
-    println!("🔍 Debug: Transcription completed successfully");
-    println!("🔍 Debug: Text length: {} characters", transcription.text.len());
-    
-    Ok(transcription)
 }
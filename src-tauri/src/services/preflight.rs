@@ -0,0 +1,459 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::ErrorKind;
+use std::path::Path;
+
+use super::encoding::EncodingProfile;
+use super::probe::{self, BasicInfo};
+use super::timeline::TimelineDocument;
+use super::toolchain::Ffmpeg;
+
+/// Clips on the main track may touch by this much without counting as
+/// overlapping, to allow for rounding in the editor.
+const OVERLAP_TOLERANCE: f64 = 0.01;
+
+/// The estimate is rough, so warn unless the free space exceeds it by this
+/// fraction.
+const DISK_SPACE_MARGIN: f64 = 1.0;
+
+/// Audio bitrate assumed when a profile does not set one.
+const DEFAULT_AUDIO_BITRATE: f64 = 192_000.0;
+
+/// Something found while checking an export before it starts.
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightIssue {
+    /// Stable identifier the UI can match on, e.g. `source_missing`.
+    pub code: &'static str,
+    pub message: String,
+    /// File the issue is about, if any.
+    pub path: Option<String>,
+}
+
+/// Everything wrong with an export request. It can start when `errors` is
+/// empty; `warnings` are worth showing but do not block it.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PreflightReport {
+    pub errors: Vec<PreflightIssue>,
+    pub warnings: Vec<PreflightIssue>,
+    /// Rough size of the output in bytes.
+    pub estimated_size: Option<u64>,
+    /// Free space where the output goes, in bytes.
+    pub available_space: Option<u64>,
+}
+
+impl PreflightReport {
+    pub fn error(&mut self, code: &'static str, message: impl Into<String>, path: Option<&str>) {
+        self.errors.push(PreflightIssue {
+            code,
+            message: message.into(),
+            path: path.map(str::to_string),
+        });
+    }
+
+    pub fn warning(&mut self, code: &'static str, message: impl Into<String>, path: Option<&str>) {
+        self.warnings.push(PreflightIssue {
+            code,
+            message: message.into(),
+            path: path.map(str::to_string),
+        });
+    }
+}
+
+/// How an export produces its output, which decides what FFmpeg needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportKind {
+    /// Needs no encoders and comes out about as large as the sources.
+    StreamCopy,
+    /// A re-encoding trim, which does not use the timeline filter graph.
+    Reencode,
+    /// Rendered through the timeline filter graph.
+    FilterGraph,
+}
+
+/// Checks an export of `timeline` to `output_path` without starting FFmpeg.
+pub async fn validate_export(
+    ffmpeg: &Ffmpeg,
+    report: &mut PreflightReport,
+    timeline: &TimelineDocument,
+    output_path: &Path,
+    profile: &EncodingProfile,
+    kind: ExportKind,
+) {
+    if let Err(e) = timeline.validate() {
+        report.error("invalid_timeline", e.to_string(), None);
+    }
+    if let Err(e) = profile.validate() {
+        report.error("invalid_profile", e.to_string(), None);
+    }
+
+    let sources = check_sources(ffmpeg, report, timeline).await;
+    check_trim_ranges(report, timeline, &sources);
+    check_main_track(report, timeline);
+    check_output(report, timeline, output_path);
+    if kind != ExportKind::StreamCopy {
+        check_ffmpeg_support(ffmpeg, report, timeline, profile, kind).await;
+    }
+    check_disk_space(report, timeline, output_path, profile, kind != ExportKind::StreamCopy, &sources);
+
+    println!(
+        "🔍 Debug: Export pre-flight found {} errors and {} warnings",
+        report.errors.len(),
+        report.warnings.len()
+    );
+}
+
+/// A readable, probed source and its size on disk.
+struct Source {
+    info: BasicInfo,
+    size: u64,
+}
+
+/// Checks every source can be opened and probed once, however many clips
+/// use it.
+async fn check_sources(
+    ffmpeg: &Ffmpeg,
+    report: &mut PreflightReport, timeline: &TimelineDocument) -> HashMap<String, Source> {
+    let mut sources = HashMap::new();
+    let mut seen = Vec::new();
+
+    for clip in timeline.tracks.iter().flat_map(|track| &track.clips) {
+        let path = clip.input_path.as_str();
+        if seen.contains(&path) {
+            continue;
+        }
+        seen.push(path);
+
+        let size = match File::open(path).and_then(|file| file.metadata()) {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                report.error("source_missing", format!("Source file not found: {}", path), Some(path));
+                continue;
+            }
+            Err(e) => {
+                report.error("source_unreadable", format!("Cannot read {}: {}", path, e), Some(path));
+                continue;
+            }
+        };
+
+        match probe::basic_info(ffmpeg, Path::new(path)).await {
+            Ok(info) => {
+                if info.video_codec.is_none() && !info.has_audio {
+                    report.error("source_empty", format!("{} has no audio or video", path), Some(path));
+                }
+                sources.insert(path.to_string(), Source { info, size });
+            }
+            Err(e) => report.error("source_unreadable", e.to_string(), Some(path)),
+        }
+    }
+    sources
+}
+
+fn check_trim_ranges(report: &mut PreflightReport, timeline: &TimelineDocument, sources: &HashMap<String, Source>) {
+    for clip in timeline.tracks.iter().flat_map(|track| &track.clips) {
+        let Some(source) = sources.get(&clip.input_path) else {
+            continue;
+        };
+        let path = Path::new(&clip.input_path);
+        if let Err(e) = source.info.check_range(path, clip.trim_start, clip.trim_end) {
+            report.error("trim_out_of_range", e.to_string(), Some(&clip.input_path));
+        }
+    }
+}
+
+/// Main track clips play one after another, so they must not overlap.
+fn check_main_track(report: &mut PreflightReport, timeline: &TimelineDocument) {
+    let Some(main) = timeline.tracks.iter().find(|track| track.id == 0) else {
+        return;
+    };
+
+    let mut clips: Vec<_> = main.clips.iter().collect();
+    clips.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+    for pair in clips.windows(2) {
+        let end = pair[0].start_time + pair[0].duration();
+        if pair[1].start_time < end - OVERLAP_TOLERANCE {
+            report.error(
+                "main_track_overlap",
+                format!(
+                    "Clips on the main track overlap at {:.2}s-{:.2}s",
+                    pair[1].start_time,
+                    end.min(pair[1].start_time + pair[1].duration())
+                ),
+                Some(&pair[1].input_path),
+            );
+        }
+    }
+}
+
+fn check_output(report: &mut PreflightReport, timeline: &TimelineDocument, output_path: &Path) {
+    let output = output_path.to_string_lossy();
+    let Some(dir) = output_path.parent().filter(|dir| !dir.as_os_str().is_empty()) else {
+        report.error("output_invalid", format!("Invalid output path: {}", output), Some(&output));
+        return;
+    };
+
+    if timeline
+        .tracks
+        .iter()
+        .flat_map(|track| &track.clips)
+        .any(|clip| Path::new(&clip.input_path) == output_path)
+    {
+        report.error("output_is_source", "The output would overwrite one of its sources", Some(&output));
+    }
+
+    if !dir.is_dir() {
+        report.error(
+            "output_dir_missing",
+            format!("Output folder does not exist: {}", dir.display()),
+            Some(&output),
+        );
+        return;
+    }
+
+    // Permissions alone do not tell, e.g. on read-only mounts, so try it
+    let probe_file = dir.join(format!(".nolanforge-write-test-{}", uuid::Uuid::new_v4()));
+    match File::create(&probe_file) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe_file);
+        }
+        Err(e) => report.error(
+            "output_not_writable",
+            format!("Cannot write to {}: {}", dir.display(), e),
+            Some(&output),
+        ),
+    }
+
+    if output_path.exists() {
+        report.warning("output_exists", format!("{} will be overwritten", output), Some(&output));
+    }
+}
+
+async fn check_ffmpeg_support(
+    ffmpeg: &Ffmpeg,
+    report: &mut PreflightReport,
+    timeline: &TimelineDocument,
+    profile: &EncodingProfile,
+    kind: ExportKind,
+) {
+    let capabilities = match ffmpeg.capabilities().await {
+        Ok(capabilities) => capabilities,
+        Err(e) => {
+            report.error("ffmpeg_unavailable", e.to_string(), None);
+            return;
+        }
+    };
+
+    for codec in [&profile.video_codec, &profile.audio_codec] {
+        if codec != "copy" && !capabilities.has_encoder(codec) {
+            report.error(
+                "encoder_unavailable",
+                format!("This FFmpeg build has no {} encoder", codec),
+                None,
+            );
+        }
+    }
+
+    if kind == ExportKind::FilterGraph {
+        // Blend modes only need the `blend` filter when a clip uses one
+        let needed: &[&str] = if timeline.uses_blend_modes() {
+            &["export", "blend_modes"]
+        } else {
+            &["export"]
+        };
+        let features = ffmpeg.features().await.unwrap_or_default();
+        for feature in features
+            .iter()
+            .filter(|feature| needed.contains(&feature.id) && !feature.available)
+        {
+            report.error(
+                "feature_unavailable",
+                format!("{} needs {}", feature.name, feature.missing.join(", ")),
+                None,
+            );
+        }
+    }
+}
+
+fn check_disk_space(
+    report: &mut PreflightReport,
+    timeline: &TimelineDocument,
+    output_path: &Path,
+    profile: &EncodingProfile,
+    reencode: bool,
+    sources: &HashMap<String, Source>,
+) {
+    let range = timeline.render_range();
+    let duration = (range.end - range.start).max(0.0);
+
+    let estimate = if reencode {
+        let canvas = &timeline.canvas;
+        let video = profile.video_bitrate.as_deref().and_then(parse_bitrate).unwrap_or_else(|| {
+            // Rule of thumb: 0.1 bits per pixel at CRF 23, halving every 6 steps
+            let crf = profile.crf.unwrap_or(23) as f64;
+            let bits_per_pixel = 0.1 * 2f64.powf((23.0 - crf) / 6.0);
+            bits_per_pixel * canvas.width as f64 * canvas.height as f64 * canvas.frame_rate
+        });
+        let audio = profile
+            .audio_bitrate
+            .as_deref()
+            .and_then(parse_bitrate)
+            .unwrap_or(DEFAULT_AUDIO_BITRATE);
+        (video + audio) / 8.0 * duration
+    } else {
+        // Stream copies are as large as the part of each source they keep
+        timeline
+            .tracks
+            .iter()
+            .flat_map(|track| &track.clips)
+            .filter_map(|clip| {
+                let source = sources.get(&clip.input_path)?;
+                let source_duration = source.info.duration.filter(|d| *d > 0.0)?;
+                Some(source.size as f64 * clip.duration() / source_duration)
+            })
+            .sum()
+    };
+    let estimate = estimate as u64;
+    report.estimated_size = Some(estimate);
+
+    let Some(dir) = output_path.parent().filter(|dir| dir.is_dir()) else {
+        return;
+    };
+    let Some(available) = available_space(dir) else {
+        report.warning("disk_space_unknown", "Could not determine free disk space", None);
+        return;
+    };
+    report.available_space = Some(available);
+
+    if available < estimate {
+        report.error(
+            "insufficient_disk_space",
+            format!(
+                "The export needs about {} but only {} is free",
+                format_bytes(estimate),
+                format_bytes(available)
+            ),
+            None,
+        );
+    } else if (available as f64) < estimate as f64 * (1.0 + DISK_SPACE_MARGIN) {
+        report.warning(
+            "low_disk_space",
+            format!(
+                "Only {} is free for an export of about {}",
+                format_bytes(available),
+                format_bytes(estimate)
+            ),
+            None,
+        );
+    }
+}
+
+/// Parses a bitrate in FFmpeg notation, e.g. `"8M"`, `"192k"` or
+/// `"128000"`, to bits per second.
+fn parse_bitrate(bitrate: &str) -> Option<f64> {
+    let bitrate = bitrate.trim();
+    let (number, multiplier) = match bitrate.chars().last()? {
+        'k' | 'K' => (&bitrate[..bitrate.len() - 1], 1_000.0),
+        'm' | 'M' => (&bitrate[..bitrate.len() - 1], 1_000_000.0),
+        'g' | 'G' => (&bitrate[..bitrate.len() - 1], 1_000_000_000.0),
+        _ => (bitrate, 1.0),
+    };
+    number.parse::<f64>().ok().map(|n| n * multiplier)
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+    const MIB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= GIB {
+        format!("{:.1} GB", bytes as f64 / GIB)
+    } else {
+        format!("{:.0} MB", (bytes as f64 / MIB).ceil())
+    }
+}
+
+#[cfg(unix)]
+fn available_space(dir: &Path) -> Option<u64> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(dir.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+    #[allow(clippy::useless_conversion)]
+    Some(u64::from(stat.f_bavail) * u64::from(stat.f_frsize))
+}
+
+#[cfg(windows)]
+fn available_space(dir: &Path) -> Option<u64> {
+    let root = dir.ancestors().last()?.to_string_lossy().replace('\'', "''");
+    std::process::Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-Command",
+            &format!("([System.IO.DriveInfo]'{}').AvailableFreeSpace", root),
+        ])
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8_lossy(&output.stdout).trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A timeline of `(track, start_time, duration)` clips.
+    fn timeline(clips: &[(i32, f64, f64)]) -> TimelineDocument {
+        let tracks: Vec<_> = [0, 1]
+            .iter()
+            .map(|&id| {
+                let clips: Vec<_> = clips
+                    .iter()
+                    .filter(|(track, _, _)| *track == id)
+                    .map(|&(_, start_time, duration)| {
+                        serde_json::json!({
+                            "input_path": format!("clip_{}.mp4", start_time),
+                            "start_time": start_time,
+                            "trim_start": 0.0,
+                            "trim_end": duration,
+                        })
+                    })
+                    .collect();
+                serde_json::json!({ "id": id, "clips": clips })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({ "tracks": tracks })).unwrap()
+    }
+
+    #[test]
+    fn overlapping_main_track_clips_are_errors() {
+        let mut report = PreflightReport::default();
+        // Touching within the tolerance, then a real overlap; overlays may overlap
+        let clips = [(0, 0.0, 5.0), (0, 4.995, 5.0), (0, 12.0, 5.0), (0, 11.0, 2.0), (1, 0.0, 20.0), (1, 1.0, 2.0)];
+        check_main_track(&mut report, &timeline(&clips));
+
+        assert_eq!(report.errors.len(), 1);
+        let issue = &report.errors[0];
+        assert_eq!(issue.code, "main_track_overlap");
+        assert_eq!(issue.message, "Clips on the main track overlap at 12.00s-13.00s");
+        assert_eq!(issue.path.as_deref(), Some("clip_12.mp4"));
+    }
+
+    #[test]
+    fn back_to_back_clips_pass() {
+        let mut report = PreflightReport::default();
+        check_main_track(&mut report, &timeline(&[(0, 5.0, 5.0), (0, 0.0, 5.0)]));
+        assert!(report.errors.is_empty());
+    }
+
+    #[test]
+    fn bitrates_are_read_in_ffmpeg_notation() {
+        assert_eq!(parse_bitrate("8M"), Some(8_000_000.0));
+        assert_eq!(parse_bitrate("192k"), Some(192_000.0));
+        assert_eq!(parse_bitrate(" 2.5m "), Some(2_500_000.0));
+        assert_eq!(parse_bitrate("1G"), Some(1_000_000_000.0));
+        assert_eq!(parse_bitrate("128000"), Some(128_000.0));
+        assert_eq!(parse_bitrate("k"), None);
+        assert_eq!(parse_bitrate("fast"), None);
+        assert_eq!(parse_bitrate(""), None);
+    }
+}
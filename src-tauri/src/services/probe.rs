@@ -0,0 +1,393 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+
+use super::toolchain::Ffmpeg;
+use crate::error::NolanForgeError;
+
+/// Runs ffprobe and returns its stdout.
+async fn run_ffprobe(ffmpeg: &Ffmpeg, args: &[&str], path: &Path) -> Result<String, NolanForgeError> {
+    let output = ffmpeg
+        .ffprobe_command()
+        .arg("-v")
+        .arg("error")
+        .args(args)
+        .arg(path)
+        .output()
+        .await
+        .map_err(|e| NolanForgeError::launch("ffprobe", e))?;
+
+    if !output.status.success() {
+        return Err(NolanForgeError::ffmpeg(
+            format!("ffprobe could not read {}", path.display()),
+            String::from_utf8_lossy(&output.stderr),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Timestamps closer than this are treated as the same instant.
+const KEYFRAME_TOLERANCE: f64 = 0.001;
+
+/// Trim points may overshoot the probed duration by this much, since
+/// container and stream durations rarely agree to the frame.
+const DURATION_TOLERANCE: f64 = 0.05;
+
+/// How much of the start of a file is scanned to measure the keyframe
+/// interval.
+const KEYFRAME_SAMPLE_SECONDS: f64 = 30.0;
+
+fn parse_seconds(value: Option<&Value>) -> Option<f64> {
+    value.and_then(|v| v.as_str()).and_then(|s| s.parse().ok())
+}
+
+/// Parses an ffprobe rate such as `30000/1001`. ffprobe reports `0/0` when it
+/// does not know the rate.
+fn parse_rate(rate: &str) -> Option<f64> {
+    let (num, den) = rate.split_once('/')?;
+    let (num, den) = (num.parse::<f64>().ok()?, den.parse::<f64>().ok()?);
+    (num > 0.0 && den > 0.0).then(|| num / den)
+}
+
+/// Container-level timing, the first video stream's format and whether there
+/// is any audio.
+#[derive(Debug, Clone)]
+pub struct BasicInfo {
+    pub duration: Option<f64>,
+    pub start_time: f64,
+    pub video_codec: Option<String>,
+    /// Codec profile as ffprobe names it, e.g. `High`.
+    pub video_profile: Option<String>,
+    /// Codec level, e.g. `41` for H.264 level 4.1.
+    pub video_level: Option<i64>,
+    pub pixel_format: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Clockwise rotation in degrees applied on playback: 0, 90, 180 or 270.
+    /// `width` and `height` are the coded size, before rotation.
+    pub rotation: u32,
+    pub frame_rate: Option<f64>,
+    pub has_audio: bool,
+}
+
+impl BasicInfo {
+    /// Checks that `start..end` lies within the file.
+    pub fn check_range(&self, path: &Path, start: f64, end: f64) -> Result<(), NolanForgeError> {
+        if start < 0.0 || end <= start {
+            return Err(NolanForgeError::InvalidInput(format!(
+                "Invalid trim range {}s-{}s for {}",
+                start,
+                end,
+                path.display()
+            )));
+        }
+        if let Some(duration) = self.duration {
+            if end > duration + DURATION_TOLERANCE {
+                return Err(NolanForgeError::InvalidInput(format!(
+                    "Trim end {:.3}s is past the end of {} ({:.3}s long)",
+                    end,
+                    path.display(),
+                    duration
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+pub async fn basic_info(ffmpeg: &Ffmpeg, path: &Path) -> Result<BasicInfo, NolanForgeError> {
+    let stdout = run_ffprobe(
+        ffmpeg,
+        &[
+            "-show_entries",
+            "format=duration,start_time:stream=codec_type,codec_name,profile,level,pix_fmt,width,height,avg_frame_rate:stream_tags=rotate:stream_side_data=rotation:stream_disposition=attached_pic",
+            "-of",
+            "json",
+        ],
+        path,
+    )
+    .await?;
+
+    let json: Value = serde_json::from_str(&stdout)
+        .map_err(|e| NolanForgeError::Internal(format!("Failed to parse ffprobe output: {}", e)))?;
+
+    let streams = json["streams"].as_array().map(Vec::as_slice).unwrap_or_default();
+    // Cover art shows up as a video stream too
+    let video_stream = streams
+        .iter()
+        .find(|s| s["codec_type"] == "video" && s["disposition"]["attached_pic"] != 1);
+
+    Ok(BasicInfo {
+        duration: parse_seconds(json["format"].get("duration")),
+        start_time: parse_seconds(json["format"].get("start_time")).unwrap_or(0.0),
+        video_codec: video_stream.and_then(|s| s["codec_name"].as_str()).map(str::to_string),
+        video_profile: video_stream.and_then(|s| s["profile"].as_str()).map(str::to_string),
+        video_level: video_stream.and_then(|s| s["level"].as_i64()).filter(|level| *level > 0),
+        pixel_format: video_stream.and_then(|s| s["pix_fmt"].as_str()).map(str::to_string),
+        width: video_stream.and_then(|s| s["width"].as_u64()).map(|w| w as u32),
+        height: video_stream.and_then(|s| s["height"].as_u64()).map(|h| h as u32),
+        rotation: video_stream.map(stream_rotation).unwrap_or(0),
+        frame_rate: video_stream.and_then(|s| parse_rate(s["avg_frame_rate"].as_str()?)),
+        has_audio: streams.iter().any(|s| s["codec_type"] == "audio"),
+    })
+}
+
+/// Lists keyframe timestamps of the first video stream that ffprobe reads
+/// for the interval `from..to`, relative to the start of the file (the same
+/// timeline FFmpeg's `-ss` uses).
+///
+/// ffprobe seeks to the keyframe at or before `from`, so that keyframe is
+/// included as well.
+async fn keyframe_packets(ffmpeg: &Ffmpeg, path: &Path, from: f64, to: f64) -> Result<Vec<f64>, NolanForgeError> {
+    let info = basic_info(ffmpeg, path).await?;
+    let start = (from + info.start_time).max(0.0);
+    let end = to + info.start_time;
+
+    // Packet flags are enough to spot keyframes, so nothing has to be decoded
+    let interval = format!("{}%{}", start, end);
+    let stdout = run_ffprobe(
+        ffmpeg,
+        &[
+            "-select_streams",
+            "v:0",
+            "-show_entries",
+            "packet=pts_time,flags",
+            "-of",
+            "csv=p=0",
+            "-read_intervals",
+            &interval,
+        ],
+        path,
+    )
+    .await?;
+
+    let mut keyframes: Vec<f64> = stdout
+        .lines()
+        .filter_map(|line| {
+            let (pts, flags) = line.split_once(',')?;
+            if !flags.starts_with('K') {
+                return None;
+            }
+            // ffprobe prints `N/A` or `nan` for packets without a timestamp
+            pts.parse::<f64>().ok().filter(|t| t.is_finite()).map(|t| t - info.start_time)
+        })
+        .collect();
+
+    keyframes.sort_by(f64::total_cmp);
+    keyframes.dedup();
+    Ok(keyframes)
+}
+
+/// Returns the keyframes between `from` and `to` seconds.
+pub async fn keyframes_between(ffmpeg: &Ffmpeg, path: &Path, from: f64, to: f64) -> Result<Vec<f64>, NolanForgeError> {
+    Ok(keyframe_packets(ffmpeg, path, from, to)
+        .await?
+        .into_iter()
+        .filter(|t| *t >= from - KEYFRAME_TOLERANCE && *t <= to + KEYFRAME_TOLERANCE)
+        .collect())
+}
+
+/// Returns the last keyframe at or before `time`, which is where a stream
+/// copy that seeks to `time` actually starts.
+pub async fn keyframe_at_or_before(ffmpeg: &Ffmpeg, path: &Path, time: f64) -> Result<Option<f64>, NolanForgeError> {
+    Ok(keyframe_packets(ffmpeg, path, time, time + KEYFRAME_TOLERANCE)
+        .await?
+        .into_iter()
+        .rfind(|t| *t <= time + KEYFRAME_TOLERANCE))
+}
+
+/// Returns the duration in seconds of a media file.
+pub async fn media_duration(ffmpeg: &Ffmpeg, path: &Path) -> Result<f64, NolanForgeError> {
+    basic_info(ffmpeg, path)
+        .await?
+        .duration
+        .ok_or_else(|| NolanForgeError::InvalidInput(format!("Could not determine duration of {}", path.display())))
+}
+
+/// Everything NolanForge needs to know about a media file, as reported by
+/// ffprobe.
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaInfo {
+    pub path: String,
+    /// FFmpeg's demuxer names, e.g. `mov,mp4,m4a,3gp,3g2,mj2`.
+    pub container: String,
+    pub container_long_name: Option<String>,
+    pub duration: Option<f64>,
+    /// Overall bitrate in bits per second.
+    pub bitrate: Option<u64>,
+    pub size_bytes: Option<u64>,
+    pub streams: Vec<StreamInfo>,
+    /// The first real video stream, skipping cover art.
+    pub video: Option<VideoStreamInfo>,
+    /// The first audio stream.
+    pub audio: Option<AudioStreamInfo>,
+    /// Average seconds between keyframes near the start of the video.
+    pub keyframe_interval: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamInfo {
+    pub index: u32,
+    /// `video`, `audio`, `subtitle`, `data` or `attachment`.
+    pub kind: String,
+    pub codec: Option<String>,
+    pub codec_long_name: Option<String>,
+    pub duration: Option<f64>,
+    pub bitrate: Option<u64>,
+    pub language: Option<String>,
+    /// Embedded cover art rather than a video track.
+    pub attached_picture: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VideoStreamInfo {
+    pub index: u32,
+    pub codec: String,
+    pub profile: Option<String>,
+    /// Coded size, before rotation.
+    pub width: u32,
+    pub height: u32,
+    /// Size as displayed, after rotation.
+    pub display_width: u32,
+    pub display_height: u32,
+    pub frame_rate: Option<f64>,
+    pub pixel_format: Option<String>,
+    /// Clockwise rotation in degrees applied on playback: 0, 90, 180 or 270.
+    pub rotation: u32,
+    pub bitrate: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioStreamInfo {
+    pub index: u32,
+    pub codec: String,
+    pub channels: u32,
+    pub channel_layout: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub bitrate: Option<u64>,
+}
+
+fn parse_number<T: std::str::FromStr>(value: &Value) -> Option<T> {
+    value.as_str().and_then(|s| s.parse().ok())
+}
+
+/// Rotation a player applies to the stream, from its display matrix or, for
+/// older files, its `rotate` tag.
+fn stream_rotation(stream: &Value) -> u32 {
+    let display_matrix = stream["side_data_list"]
+        .as_array()
+        .and_then(|list| list.iter().find_map(|data| data["rotation"].as_f64()))
+        // The display matrix rotates counter-clockwise
+        .map(|rotation| -rotation);
+    let tag = parse_number::<f64>(&stream["tags"]["rotate"]);
+
+    let degrees = display_matrix.or(tag).unwrap_or(0.0).round() as i64;
+    // Snap to quarter turns, other angles do not occur in practice
+    ((degrees.rem_euclid(360) + 45) / 90 % 4 * 90) as u32
+}
+
+fn stream_info(stream: &Value) -> StreamInfo {
+    StreamInfo {
+        index: stream["index"].as_u64().unwrap_or_default() as u32,
+        kind: stream["codec_type"].as_str().unwrap_or("unknown").to_string(),
+        codec: stream["codec_name"].as_str().map(str::to_string),
+        codec_long_name: stream["codec_long_name"].as_str().map(str::to_string),
+        duration: parse_seconds(stream.get("duration")),
+        bitrate: parse_number(&stream["bit_rate"]),
+        language: stream["tags"]["language"].as_str().map(str::to_string),
+        attached_picture: stream["disposition"]["attached_pic"].as_u64() == Some(1),
+    }
+}
+
+fn video_stream_info(stream: &Value) -> VideoStreamInfo {
+    let width = stream["width"].as_u64().unwrap_or_default() as u32;
+    let height = stream["height"].as_u64().unwrap_or_default() as u32;
+    let rotation = stream_rotation(stream);
+    let (display_width, display_height) = if rotation % 180 == 90 { (height, width) } else { (width, height) };
+
+    VideoStreamInfo {
+        index: stream["index"].as_u64().unwrap_or_default() as u32,
+        codec: stream["codec_name"].as_str().unwrap_or("unknown").to_string(),
+        profile: stream["profile"].as_str().map(str::to_string),
+        width,
+        height,
+        display_width,
+        display_height,
+        frame_rate: stream["avg_frame_rate"]
+            .as_str()
+            .and_then(parse_rate)
+            .or_else(|| stream["r_frame_rate"].as_str().and_then(parse_rate)),
+        pixel_format: stream["pix_fmt"].as_str().map(str::to_string),
+        rotation,
+        bitrate: parse_number(&stream["bit_rate"]),
+    }
+}
+
+fn audio_stream_info(stream: &Value) -> AudioStreamInfo {
+    AudioStreamInfo {
+        index: stream["index"].as_u64().unwrap_or_default() as u32,
+        codec: stream["codec_name"].as_str().unwrap_or("unknown").to_string(),
+        channels: stream["channels"].as_u64().unwrap_or_default() as u32,
+        channel_layout: stream["channel_layout"].as_str().map(str::to_string),
+        sample_rate: parse_number(&stream["sample_rate"]),
+        bitrate: parse_number(&stream["bit_rate"]),
+    }
+}
+
+/// Average distance between the keyframes found in `keyframes`.
+fn average_interval(keyframes: &[f64]) -> Option<f64> {
+    if keyframes.len() < 2 {
+        return None;
+    }
+    Some((keyframes[keyframes.len() - 1] - keyframes[0]) / (keyframes.len() - 1) as f64)
+}
+
+/// Probes containers, streams and keyframe spacing of a media file.
+pub async fn probe_media(ffmpeg: &Ffmpeg, path: &Path) -> Result<MediaInfo, NolanForgeError> {
+    if !path.exists() {
+        return Err(NolanForgeError::FileNotFound {
+            message: format!("File not found: {}", path.display()),
+            diagnostic: path.display().to_string(),
+        });
+    }
+
+    let stdout = run_ffprobe(ffmpeg, &["-show_format", "-show_streams", "-of", "json"], path).await?;
+    let json: Value = serde_json::from_str(&stdout)
+        .map_err(|e| NolanForgeError::Internal(format!("Failed to parse ffprobe output: {}", e)))?;
+
+    let format = &json["format"];
+    let raw_streams = json["streams"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let streams: Vec<StreamInfo> = raw_streams.iter().map(stream_info).collect();
+
+    let video = raw_streams
+        .iter()
+        .zip(&streams)
+        .find(|(_, info)| info.kind == "video" && !info.attached_picture)
+        .map(|(stream, _)| video_stream_info(stream));
+    let audio = raw_streams
+        .iter()
+        .find(|s| s["codec_type"] == "audio")
+        .map(audio_stream_info);
+
+    let duration = parse_seconds(format.get("duration"));
+    let keyframe_interval = if video.is_some() {
+        let sample_end = duration.unwrap_or(KEYFRAME_SAMPLE_SECONDS).min(KEYFRAME_SAMPLE_SECONDS);
+        average_interval(&keyframes_between(ffmpeg, path, 0.0, sample_end).await?)
+    } else {
+        None
+    };
+
+    Ok(MediaInfo {
+        path: path.to_string_lossy().to_string(),
+        container: format["format_name"].as_str().unwrap_or("unknown").to_string(),
+        container_long_name: format["format_long_name"].as_str().map(str::to_string),
+        duration,
+        bitrate: parse_number(&format["bit_rate"]),
+        size_bytes: parse_number(&format["size"]),
+        streams,
+        video,
+        audio,
+        keyframe_interval,
+    })
+}
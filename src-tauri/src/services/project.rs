@@ -0,0 +1,415 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use super::cache;
+use super::composition::{BlendMode, Canvas};
+use super::encoding::EncodingProfile;
+use super::files;
+use super::trim::TrimMode;
+use crate::error::NolanForgeError;
+
+/// File extension of project documents.
+pub const PROJECT_EXTENSION: &str = "nolanforge";
+
+/// Schema version written by this build. Bump it together with a new entry
+/// in `MIGRATIONS` whenever the document format changes.
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// Upgrades a raw document from the version it is keyed by to the next.
+type Migration = fn(Value) -> Result<Value, NolanForgeError>;
+
+/// Migrations by the version they upgrade from, in order. Version 1 is the
+/// first on-disk format, so there is nothing to upgrade yet.
+const MIGRATIONS: &[(u32, Migration)] = &[];
+
+/// Everything needed to reopen an edit: the media it uses, its tracks and
+/// clips, and how it is exported.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectDocument {
+    pub schema_version: u32,
+    #[serde(default)]
+    pub name: String,
+    pub media: Vec<MediaReference>,
+    pub tracks: Vec<ProjectTrack>,
+    pub clips: Vec<ProjectClip>,
+    #[serde(default)]
+    pub global_trim: GlobalTrim,
+    #[serde(default)]
+    pub export_settings: ExportSettings,
+}
+
+/// A media file used by the project.
+///
+/// Commands always exchange absolute paths. On disk `path` is relative to
+/// the project file where possible, with `absolute_path` as a fallback for
+/// when the relative one does not resolve.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaReference {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub absolute_path: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub duration: Option<f64>,
+    /// `video` or `audio`.
+    pub media_type: String,
+    /// Content fingerprint of the file when last seen, used to find it
+    /// again if it goes missing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectTrack {
+    pub id: i32,
+    pub name: String,
+    #[serde(default)]
+    pub muted: bool,
+    #[serde(default)]
+    pub solo: bool,
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(default = "visible")]
+    pub visible: bool,
+    #[serde(default)]
+    pub height: Option<f64>,
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+fn visible() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Size {
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A clip on the timeline, mirroring the editor's `TimelineClip`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectClip {
+    pub id: String,
+    pub media_id: String,
+    pub track_id: i32,
+    pub start_time: f64,
+    pub duration: f64,
+    pub trim_start: f64,
+    pub trim_end: f64,
+    pub source_duration: f64,
+    #[serde(default)]
+    pub overlay_position: Option<Point>,
+    #[serde(default)]
+    pub overlay_size: Option<Size>,
+    #[serde(default)]
+    pub overlay_opacity: Option<f64>,
+    #[serde(default)]
+    pub overlay_blend_mode: Option<BlendMode>,
+    #[serde(default)]
+    pub overlay_visible: Option<bool>,
+}
+
+/// The range of the timeline that gets exported; no end means up to the
+/// last clip.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct GlobalTrim {
+    pub start: f64,
+    pub end: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportSettings {
+    #[serde(default)]
+    pub profile: Option<EncodingProfile>,
+    #[serde(default)]
+    pub canvas: Canvas,
+    #[serde(default)]
+    pub trim_mode: TrimMode,
+}
+
+/// A project read from disk.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadedProject {
+    pub path: String,
+    pub project: ProjectDocument,
+    /// Schema version the file was written with, older than
+    /// `CURRENT_SCHEMA_VERSION` if it was migrated.
+    pub loaded_schema_version: u32,
+    /// IDs of media whose file could not be found.
+    pub missing_media: Vec<String>,
+}
+
+impl ProjectDocument {
+    pub fn validate(&self) -> Result<(), NolanForgeError> {
+        for clip in &self.clips {
+            if !self.media.iter().any(|media| media.id == clip.media_id) {
+                return Err(NolanForgeError::InvalidInput(format!(
+                    "Clip {} references unknown media {}",
+                    clip.id, clip.media_id
+                )));
+            }
+            if !self.tracks.iter().any(|track| track.id == clip.track_id) {
+                return Err(NolanForgeError::InvalidInput(format!(
+                    "Clip {} is on unknown track {}",
+                    clip.id, clip.track_id
+                )));
+            }
+        }
+        self.export_settings.canvas.validate()
+    }
+}
+
+/// Adds the project extension unless `path` already has it.
+pub fn with_project_extension(path: &Path) -> PathBuf {
+    if path.extension().is_some_and(|ext| ext == PROJECT_EXTENSION) {
+        path.to_path_buf()
+    } else {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".");
+        name.push(PROJECT_EXTENSION);
+        PathBuf::from(name)
+    }
+}
+
+/// Writes `project` to `path` atomically and returns the path written, with
+/// the project extension added if missing.
+pub fn save_project(path: &Path, project: &ProjectDocument) -> Result<PathBuf, NolanForgeError> {
+    project.validate()?;
+    let path = with_project_extension(path);
+    let project_dir = project_dir(&path)?;
+
+    let mut document = project.clone();
+    document.schema_version = CURRENT_SCHEMA_VERSION;
+    for media in &mut document.media {
+        let absolute = PathBuf::from(&media.path);
+        if absolute.exists() {
+            media.fingerprint = Some(cache::source_fingerprint(&absolute)?);
+        }
+        if let Some(relative) = relative_path(&project_dir, &absolute) {
+            media.path = relative;
+            media.absolute_path = Some(absolute.to_string_lossy().to_string());
+        }
+    }
+
+    let content = serde_json::to_string_pretty(&document)
+        .map_err(|e| NolanForgeError::Internal(format!("Failed to serialize project: {}", e)))?;
+    files::write_atomic(&path, content.as_bytes())?;
+
+    println!("🔍 Debug: Saved project with {} clips to {}", document.clips.len(), path.display());
+    Ok(path)
+}
+
+/// Reads the project at `path`, migrating it to the current schema and
+/// resolving its media paths.
+pub fn load_project(path: &Path) -> Result<LoadedProject, NolanForgeError> {
+    let content =
+        fs::read_to_string(path).map_err(|e| NolanForgeError::io(format!("Failed to read {}", path.display()), e))?;
+    let raw: Value = serde_json::from_str(&content)
+        .map_err(|e| NolanForgeError::InvalidInput(format!("{} is not a NolanForge project: {}", path.display(), e)))?;
+
+    let loaded_schema_version = schema_version(&raw)?;
+    let mut project: ProjectDocument = serde_json::from_value(migrate(raw, loaded_schema_version)?)
+        .map_err(|e| NolanForgeError::InvalidInput(format!("Invalid project file {}: {}", path.display(), e)))?;
+    project.schema_version = CURRENT_SCHEMA_VERSION;
+
+    let project_dir = project_dir(path)?;
+    let mut missing_media = Vec::new();
+    for media in &mut project.media {
+        match resolve_media_path(&project_dir, media) {
+            Some(resolved) => media.path = resolved.to_string_lossy().to_string(),
+            None => {
+                // Keep the best guess so the user can see where it used to be
+                if let Some(absolute) = &media.absolute_path {
+                    media.path = absolute.clone();
+                }
+                missing_media.push(media.id.clone());
+            }
+        }
+        media.absolute_path = None;
+    }
+
+    Ok(LoadedProject {
+        path: path.to_string_lossy().to_string(),
+        project,
+        loaded_schema_version,
+        missing_media,
+    })
+}
+
+fn schema_version(raw: &Value) -> Result<u32, NolanForgeError> {
+    let version = raw
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| NolanForgeError::InvalidInput("Project file has no schema version".to_string()))?
+        as u32;
+
+    if version > CURRENT_SCHEMA_VERSION {
+        return Err(NolanForgeError::InvalidInput(format!(
+            "Project was saved by a newer version of NolanForge (schema {}, this version reads up to {})",
+            version, CURRENT_SCHEMA_VERSION
+        )));
+    }
+    Ok(version)
+}
+
+/// Runs every migration from `version` up to the current schema.
+fn migrate(mut raw: Value, mut version: u32) -> Result<Value, NolanForgeError> {
+    while version < CURRENT_SCHEMA_VERSION {
+        let (_, migration) = MIGRATIONS
+            .iter()
+            .find(|(from, _)| *from == version)
+            .ok_or_else(|| NolanForgeError::Internal(format!("No migration from project schema {}", version)))?;
+        raw = migration(raw)?;
+        version += 1;
+        println!("🔍 Debug: Migrated project to schema {}", version);
+    }
+    Ok(raw)
+}
+
+fn project_dir(path: &Path) -> Result<PathBuf, NolanForgeError> {
+    let absolute = std::path::absolute(path)
+        .map_err(|e| NolanForgeError::io(format!("Failed to resolve {}", path.display()), e))?;
+    Ok(absolute.parent().map(Path::to_path_buf).unwrap_or_default())
+}
+
+/// The stored path of `media` if it exists, relative paths taken from the
+/// project directory, else its absolute fallback.
+fn resolve_media_path(project_dir: &Path, media: &MediaReference) -> Option<PathBuf> {
+    let stored = Path::new(&media.path);
+    let primary = if stored.is_absolute() {
+        stored.to_path_buf()
+    } else {
+        project_dir.join(stored)
+    };
+    if primary.exists() {
+        return Some(primary);
+    }
+    media
+        .absolute_path
+        .as_ref()
+        .map(PathBuf::from)
+        .filter(|path| path.exists())
+}
+
+/// `target` relative to `base`, with `/` separators so it reads the same on
+/// every platform. `None` when the two share no root, e.g. different
+/// Windows drives.
+fn relative_path(base: &Path, target: &Path) -> Option<String> {
+    if !target.is_absolute() {
+        return None;
+    }
+    let base: Vec<Component> = base.components().collect();
+    let target: Vec<Component> = target.components().collect();
+    if base.first() != target.first() {
+        return None;
+    }
+
+    let common = base.iter().zip(&target).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_string(); base.len() - common];
+    parts.extend(target[common..].iter().map(|c| c.as_os_str().to_string_lossy().to_string()));
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_root() -> PathBuf {
+        let root = std::env::temp_dir().join(format!("nolanforge-project-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn project_with_media(path: &Path) -> ProjectDocument {
+        serde_json::from_value(serde_json::json!({
+            "schema_version": CURRENT_SCHEMA_VERSION,
+            "name": "Demo",
+            "media": [{ "id": "m1", "name": "clip.mp4", "path": path, "media_type": "video" }],
+            "tracks": [],
+            "clips": [],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn relative_paths_climb_to_the_common_directory() {
+        let base = Path::new("/home/user/projects/edit");
+        assert_eq!(
+            relative_path(base, Path::new("/home/user/media/clip.mp4")).as_deref(),
+            Some("../../media/clip.mp4")
+        );
+        assert_eq!(
+            relative_path(base, Path::new("/home/user/projects/edit/clip.mp4")).as_deref(),
+            Some("clip.mp4")
+        );
+        assert_eq!(relative_path(base, Path::new("clip.mp4")), None);
+    }
+
+    #[test]
+    fn media_paths_round_trip_with_absolute_fallback() {
+        let root = temp_root();
+        let media = root.join("media").join("clip.mp4");
+        fs::create_dir_all(media.parent().unwrap()).unwrap();
+        fs::write(&media, b"not really video").unwrap();
+
+        let saved = save_project(&root.join("projects").join("demo"), &project_with_media(&media)).unwrap();
+        assert_eq!(saved.extension().unwrap(), PROJECT_EXTENSION);
+
+        // Stored relative to the project, with the absolute path as fallback
+        let raw: Value = serde_json::from_str(&fs::read_to_string(&saved).unwrap()).unwrap();
+        assert_eq!(raw["media"][0]["path"], "../media/clip.mp4");
+        assert_eq!(raw["media"][0]["absolute_path"], media.to_string_lossy().as_ref());
+
+        let loaded = load_project(&saved).unwrap();
+        assert!(loaded.missing_media.is_empty());
+        assert_eq!(
+            fs::canonicalize(&loaded.project.media[0].path).unwrap(),
+            fs::canonicalize(&media).unwrap()
+        );
+        assert_eq!(loaded.project.media[0].absolute_path, None);
+
+        // Moved somewhere the relative path no longer leads to the media
+        let moved = root.join("elsewhere").join("deeper").join("demo.nolanforge");
+        fs::create_dir_all(moved.parent().unwrap()).unwrap();
+        fs::copy(&saved, &moved).unwrap();
+        let loaded = load_project(&moved).unwrap();
+        assert!(loaded.missing_media.is_empty());
+        assert_eq!(loaded.project.media[0].path, media.to_string_lossy());
+
+        // Neither path resolves: the old location is kept for relinking
+        fs::remove_file(&media).unwrap();
+        let loaded = load_project(&moved).unwrap();
+        assert_eq!(loaded.missing_media, vec!["m1".to_string()]);
+        assert_eq!(loaded.project.media[0].path, media.to_string_lossy());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn files_from_a_newer_schema_are_rejected() {
+        let root = temp_root();
+        let path = root.join("future.nolanforge");
+        let mut raw = serde_json::to_value(project_with_media(Path::new("/media/clip.mp4"))).unwrap();
+        raw["schema_version"] = (CURRENT_SCHEMA_VERSION + 1).into();
+        fs::write(&path, raw.to_string()).unwrap();
+
+        let result = load_project(&path);
+        fs::remove_dir_all(&root).unwrap();
+        assert!(matches!(result, Err(NolanForgeError::InvalidInput(_))));
+
+        assert!(schema_version(&serde_json::json!({ "name": "No version" })).is_err());
+        assert_eq!(schema_version(&serde_json::json!({ "schema_version": CURRENT_SCHEMA_VERSION })).unwrap(), CURRENT_SCHEMA_VERSION);
+    }
+}
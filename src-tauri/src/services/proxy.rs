@@ -0,0 +1,284 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::AppHandle;
+use tokio::sync::Semaphore;
+
+use super::cache;
+use super::files;
+use super::ffmpeg::{ffmpeg_command, run_ffmpeg, ProgressReporter};
+use super::jobs::Job;
+use super::probe;
+use super::timeline::TimelineDocument;
+use super::toolchain::Ffmpeg;
+use crate::config::Config;
+use crate::error::NolanForgeError;
+
+/// Event carrying the progress of a proxy job, with the same payload as
+/// export progress.
+pub const PROXY_PROGRESS_EVENT: &str = "proxy-progress";
+
+/// Event emitted when a proxy job has finished, failed or been cancelled.
+pub const PROXY_FINISHED_EVENT: &str = "proxy-finished";
+
+pub const DEFAULT_PROXY_HEIGHT: u32 = 540;
+
+/// Proxies are transcoded one at a time; several 4K decodes in parallel
+/// would starve the editor itself.
+static PROXY_SLOTS: Semaphore = Semaphore::const_new(1);
+
+/// Serializes read-modify-write cycles of the proxy index.
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// A low resolution, edit-friendly copy of a source file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyEntry {
+    pub original_path: String,
+    pub proxy_path: String,
+    /// Content fingerprint of the original when the proxy was made, so a
+    /// replaced original does not keep a stale proxy.
+    pub fingerprint: String,
+    pub height: u32,
+    pub duration: f64,
+}
+
+/// Payload of [`PROXY_FINISHED_EVENT`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ProxyFinished {
+    pub job_id: String,
+    pub original_path: String,
+    pub proxy: Option<ProxyEntry>,
+    pub error: Option<NolanForgeError>,
+}
+
+fn proxies_dir() -> Result<PathBuf, NolanForgeError> {
+    let dir = Config::app_data_dir()?.join("proxies");
+    fs::create_dir_all(&dir).map_err(|e| NolanForgeError::io("Failed to create proxy directory", e))?;
+    Ok(dir)
+}
+
+fn index_file() -> Result<PathBuf, NolanForgeError> {
+    Ok(Config::app_data_dir()?.join("proxies.json"))
+}
+
+fn load_index() -> Result<Vec<ProxyEntry>, NolanForgeError> {
+    let file = index_file()?;
+    if !file.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&file).map_err(|e| NolanForgeError::io("Failed to read proxy index", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| NolanForgeError::Config(format!("Failed to parse proxy index: {}", e)))
+}
+
+fn save_index(entries: &[ProxyEntry]) -> Result<(), NolanForgeError> {
+    let content = serde_json::to_string_pretty(entries)
+        .map_err(|e| NolanForgeError::Internal(format!("Failed to serialize proxy index: {}", e)))?;
+    files::write_atomic(&index_file()?, content.as_bytes())
+}
+
+/// Applies `change` to the stored index.
+fn update_index(change: impl FnOnce(&mut Vec<ProxyEntry>)) -> Result<(), NolanForgeError> {
+    let _lock = INDEX_LOCK.lock().unwrap();
+    let mut entries = load_index()?;
+    change(&mut entries);
+    save_index(&entries)
+}
+
+/// Proxies whose file still exists.
+pub fn list_proxies() -> Result<Vec<ProxyEntry>, NolanForgeError> {
+    Ok(load_index()?
+        .into_iter()
+        .filter(|entry| Path::new(&entry.proxy_path).exists())
+        .collect())
+}
+
+/// The proxy of `original`, if there is one and it was made from the file's
+/// current content.
+pub fn find_proxy(original: &Path) -> Result<Option<ProxyEntry>, NolanForgeError> {
+    let original_path = original.to_string_lossy();
+    let Some(entry) = list_proxies()?
+        .into_iter()
+        .find(|entry| entry.original_path == original_path)
+    else {
+        return Ok(None);
+    };
+
+    if cache::source_fingerprint(original)? != entry.fingerprint {
+        println!("🔍 Debug: Proxy of {} is stale, original has changed", original_path);
+        return Ok(None);
+    }
+    Ok(Some(entry))
+}
+
+/// Drops the index entries of `original_path` and returns the proxy files
+/// no remaining entry uses. Identical copies of a file share one proxy.
+fn remove_entries(entries: &mut Vec<ProxyEntry>, original_path: &str) -> Vec<String> {
+    let mut removed: Vec<String> = entries
+        .iter()
+        .filter(|entry| entry.original_path == original_path)
+        .map(|entry| entry.proxy_path.clone())
+        .collect();
+    entries.retain(|entry| entry.original_path != original_path);
+    removed.retain(|path| !entries.iter().any(|entry| &entry.proxy_path == path));
+    removed
+}
+
+fn remove_files(paths: Vec<String>) {
+    for path in paths {
+        if let Err(e) = fs::remove_file(&path) {
+            eprintln!("Warning: Failed to remove proxy {}: {}", path, e);
+        }
+    }
+}
+
+/// Removes the proxy of `original` and its file.
+pub fn delete_proxy(original: &Path) -> Result<(), NolanForgeError> {
+    let original_path = original.to_string_lossy().to_string();
+    let mut found = false;
+    let mut unused = Vec::new();
+    update_index(|entries| {
+        found = entries.iter().any(|entry| entry.original_path == original_path);
+        unused = remove_entries(entries, &original_path);
+    })?;
+
+    if !found {
+        return Err(NolanForgeError::InvalidInput(format!("No proxy for {}", original_path)));
+    }
+    remove_files(unused);
+    Ok(())
+}
+
+/// Maps a proxy file back to its original. Any other path is returned as is,
+/// so exports always read full quality media even if the frontend hands them
+/// a proxy.
+pub fn resolve_original(path: &str) -> String {
+    match load_index() {
+        Ok(entries) => entries
+            .into_iter()
+            .find(|entry| entry.proxy_path == path)
+            .map(|entry| {
+                println!("🔍 Debug: Exporting {} instead of its proxy", entry.original_path);
+                entry.original_path
+            })
+            .unwrap_or_else(|| path.to_string()),
+        Err(e) => {
+            eprintln!("Warning: Failed to read proxy index: {}", e);
+            path.to_string()
+        }
+    }
+}
+
+/// Points every clip of `document` at its original media.
+pub fn resolve_timeline_originals(document: &mut TimelineDocument) {
+    for clip in document.tracks.iter_mut().flat_map(|track| &mut track.clips) {
+        clip.input_path = resolve_original(&clip.input_path);
+    }
+}
+
+/// Transcodes `original` to a proxy at most `height` pixels tall and records
+/// it in the index.
+///
+/// Proxies keep the original's timing, with short GOPs so scrubbing only has
+/// to decode a few frames.
+pub async fn generate_proxy(
+    ffmpeg: &Ffmpeg,
+    app: &AppHandle,
+    job: &Job,
+    original: &Path,
+    height: u32,
+) -> Result<ProxyEntry, NolanForgeError> {
+    if height == 0 || !height.is_multiple_of(2) {
+        return Err(NolanForgeError::InvalidInput(format!(
+            "Invalid proxy height {}: must be positive and even",
+            height
+        )));
+    }
+
+    let info = probe::basic_info(ffmpeg, original).await?;
+    if info.video_codec.is_none() {
+        return Err(NolanForgeError::InvalidInput(format!(
+            "{} has no video stream to make a proxy of",
+            original.display()
+        )));
+    }
+    let duration = info.duration.unwrap_or(0.0);
+
+    // Wait for any running proxy job to finish first
+    let _slot = tokio::select! {
+        slot = PROXY_SLOTS.acquire() => slot.map_err(|e| NolanForgeError::Internal(e.to_string()))?,
+        _ = job.cancelled() => return Err(NolanForgeError::Cancelled),
+    };
+
+    let fingerprint = cache::source_fingerprint(original)?;
+    let dir = proxies_dir()?;
+    let proxy_path = dir.join(format!("{}_{}p.mp4", fingerprint, height));
+    let part_path = dir.join(format!("{}_{}p.part.mp4", fingerprint, height));
+    job.track_path(&part_path);
+
+    let mut reporter = ProgressReporter::with_event(app, PROXY_PROGRESS_EVENT, job, duration);
+    let mut cmd = ffmpeg_command(ffmpeg);
+    cmd.arg("-i")
+        .arg(original)
+        .arg("-map")
+        .arg("0:v:0")
+        .arg("-map")
+        .arg("0:a:0?")
+        .arg("-vf")
+        // Never upscale sources that are already small
+        .arg(format!("scale=-2:min({}\\,ih)", height))
+        .arg("-c:v")
+        .arg("libx264")
+        .arg("-preset")
+        .arg("veryfast")
+        .arg("-crf")
+        .arg("23")
+        .arg("-g")
+        .arg("12")
+        .arg("-pix_fmt")
+        .arg("yuv420p")
+        .arg("-c:a")
+        .arg("aac")
+        .arg("-b:a")
+        .arg("128k")
+        .arg("-movflags")
+        .arg("+faststart")
+        .arg("-y")
+        .arg(&part_path);
+
+    let result = match run_ffmpeg(cmd, &mut reporter, "Generating proxy", duration).await {
+        Ok(()) => fs::rename(&part_path, &proxy_path).map_err(|e| NolanForgeError::io("Failed to save proxy", e)),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        // A failed or cancelled run leaves a partial file nothing would reuse
+        if part_path.exists() {
+            if let Err(remove_error) = fs::remove_file(&part_path) {
+                eprintln!("Warning: Failed to remove {}: {}", part_path.display(), remove_error);
+            }
+        }
+        return Err(e);
+    }
+    reporter.finish("Proxy ready");
+
+    let entry = ProxyEntry {
+        original_path: original.to_string_lossy().to_string(),
+        proxy_path: proxy_path.to_string_lossy().to_string(),
+        fingerprint,
+        height,
+        duration,
+    };
+    // Replaces any earlier proxy of the file, e.g. one at another height
+    let mut unused = Vec::new();
+    update_index(|entries| {
+        unused = remove_entries(entries, &entry.original_path);
+        unused.retain(|path| *path != entry.proxy_path);
+        entries.push(entry.clone());
+    })?;
+    remove_files(unused);
+
+    println!("🔍 Debug: Proxy of {} written to {}", entry.original_path, entry.proxy_path);
+    Ok(entry)
+}
@@ -0,0 +1,251 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::cache;
+use super::probe;
+use super::project::MediaReference;
+use super::toolchain::Ffmpeg;
+use crate::error::NolanForgeError;
+
+/// How deep below each search directory files are looked for.
+const MAX_SEARCH_DEPTH: usize = 8;
+
+/// Stop walking after this many files, so pointing the search at a whole
+/// drive cannot hang the app.
+const MAX_SEARCH_FILES: usize = 50_000;
+
+/// Durations closer than this count as the same, containers round them
+/// differently.
+const DURATION_TOLERANCE: f64 = 0.1;
+
+/// What a candidate has in common with the missing file, best first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// Same content, whatever it is called now.
+    Fingerprint,
+    NameAndSize,
+    /// Renamed but otherwise untouched.
+    SizeAndDuration,
+    /// Re-exported under the same name.
+    NameAndDuration,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RelinkCandidate {
+    pub path: String,
+    pub matched_by: MatchKind,
+}
+
+/// Files that could replace a missing one, best match first.
+#[derive(Debug, Clone, Serialize)]
+pub struct RelinkSuggestion {
+    pub media_id: String,
+    pub original_path: String,
+    pub candidates: Vec<RelinkCandidate>,
+}
+
+/// Points a media reference at a new file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Relink {
+    pub media_id: String,
+    pub path: String,
+}
+
+/// The paths in `paths` that do not exist, each once.
+pub fn missing_paths<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Vec<String> {
+    let mut missing: Vec<String> = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        // Paths that are not valid UTF-8 are reported in their lossy form
+        let display = path.to_string_lossy();
+        if !path.exists() && !missing.iter().any(|m| *m == display) {
+            missing.push(display.into_owned());
+        }
+    }
+    missing
+}
+
+/// Fails with [`NolanForgeError::MediaMissing`] unless every path exists,
+/// so exports stop before FFmpeg does with a far less helpful message.
+pub fn ensure_present<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Result<(), NolanForgeError> {
+    let paths = missing_paths(paths);
+    if paths.is_empty() {
+        Ok(())
+    } else {
+        Err(NolanForgeError::MediaMissing { paths })
+    }
+}
+
+/// The media of `media` whose file no longer exists.
+pub fn find_missing(media: Vec<MediaReference>) -> Vec<MediaReference> {
+    media
+        .into_iter()
+        .filter(|media| !Path::new(&media.path).exists())
+        .collect()
+}
+
+/// A file found while searching, with what is cheap to know about it.
+struct FoundFile {
+    path: PathBuf,
+    name: String,
+    size: u64,
+}
+
+/// Every file below `directories`, skipping hidden directories and not
+/// following symlinked ones.
+fn walk(directories: &[PathBuf]) -> Vec<FoundFile> {
+    let mut found = Vec::new();
+    let mut pending: Vec<(PathBuf, usize)> = directories.iter().map(|dir| (dir.clone(), 0)).collect();
+
+    while let Some((dir, depth)) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let name = entry.file_name().to_string_lossy().to_string();
+            if file_type.is_dir() {
+                if depth < MAX_SEARCH_DEPTH && !name.starts_with('.') {
+                    pending.push((entry.path(), depth + 1));
+                }
+            } else if let Ok(metadata) = fs::metadata(entry.path()) {
+                if metadata.is_file() {
+                    found.push(FoundFile {
+                        path: entry.path(),
+                        name,
+                        size: metadata.len(),
+                    });
+                    if found.len() >= MAX_SEARCH_FILES {
+                        println!("🔍 Debug: Relink search stopped after {} files", found.len());
+                        return found;
+                    }
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Searches `directories` for files that look like the missing `media`,
+/// judged by file name, size, duration and content fingerprint.
+pub async fn search_candidates(
+    ffmpeg: &Ffmpeg,
+    media: &[MediaReference],
+    directories: &[PathBuf],
+) -> Result<Vec<RelinkSuggestion>, NolanForgeError> {
+    for dir in directories {
+        if !dir.is_dir() {
+            return Err(NolanForgeError::InvalidInput(format!(
+                "Not a directory: {}",
+                dir.display()
+            )));
+        }
+    }
+
+    let walk_dirs = directories.to_vec();
+    let files = tokio::task::spawn_blocking(move || walk(&walk_dirs))
+        .await
+        .map_err(|e| NolanForgeError::Internal(format!("Relink search failed: {}", e)))?;
+    println!("🔍 Debug: Relink search found {} files", files.len());
+
+    // Each file is hashed or probed at most once, however many missing
+    // files it is compared against
+    let mut fingerprints: HashMap<PathBuf, Option<String>> = HashMap::new();
+    let mut durations: HashMap<PathBuf, Option<f64>> = HashMap::new();
+
+    let mut suggestions = Vec::new();
+    for missing in media {
+        let missing_name = Path::new(&missing.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| missing.name.clone());
+
+        let mut candidates = Vec::new();
+        for file in &files {
+            let name_match = file.name.eq_ignore_ascii_case(&missing_name);
+            let size_match = missing.size == Some(file.size);
+            if !name_match && !size_match {
+                continue;
+            }
+
+            // The fingerprint covers the size, so only same-sized files can match
+            if let (Some(expected), true) = (&missing.fingerprint, size_match) {
+                let fingerprint = fingerprints
+                    .entry(file.path.clone())
+                    .or_insert_with(|| cache::source_fingerprint(&file.path).ok());
+                if fingerprint.as_ref() == Some(expected) {
+                    candidates.push(candidate(file, MatchKind::Fingerprint));
+                    continue;
+                }
+            }
+
+            if name_match && size_match {
+                candidates.push(candidate(file, MatchKind::NameAndSize));
+                continue;
+            }
+
+            let Some(expected) = missing.duration else {
+                continue;
+            };
+            if !durations.contains_key(&file.path) {
+                let duration = probe::basic_info(ffmpeg, &file.path).await.ok().and_then(|info| info.duration);
+                durations.insert(file.path.clone(), duration);
+            }
+            if durations[&file.path].is_some_and(|duration| (duration - expected).abs() <= DURATION_TOLERANCE) {
+                let kind = if size_match {
+                    MatchKind::SizeAndDuration
+                } else {
+                    MatchKind::NameAndDuration
+                };
+                candidates.push(candidate(file, kind));
+            }
+        }
+
+        candidates.sort_by_key(|candidate| candidate.matched_by);
+        suggestions.push(RelinkSuggestion {
+            media_id: missing.id.clone(),
+            original_path: missing.path.clone(),
+            candidates,
+        });
+    }
+    Ok(suggestions)
+}
+
+fn candidate(file: &FoundFile, matched_by: MatchKind) -> RelinkCandidate {
+    RelinkCandidate {
+        path: file.path.to_string_lossy().to_string(),
+        matched_by,
+    }
+}
+
+/// Applies every relink to `media` at once, or none of them if any target
+/// does not exist. Sizes and fingerprints are refreshed from the new files.
+pub fn apply_relinks(
+    mut media: Vec<MediaReference>,
+    relinks: &[Relink],
+) -> Result<Vec<MediaReference>, NolanForgeError> {
+    ensure_present(relinks.iter().map(|relink| relink.path.as_str()))?;
+
+    for relink in relinks {
+        let reference = media
+            .iter_mut()
+            .find(|media| media.id == relink.media_id)
+            .ok_or_else(|| NolanForgeError::InvalidInput(format!("Unknown media {}", relink.media_id)))?;
+
+        let path = Path::new(&relink.path);
+        let size = fs::metadata(path)
+            .map_err(|e| NolanForgeError::io(format!("Failed to read {}", path.display()), e))?
+            .len();
+        reference.path = relink.path.clone();
+        reference.absolute_path = None;
+        reference.size = Some(size);
+        reference.fingerprint = Some(cache::source_fingerprint(path)?);
+        println!("🔍 Debug: Relinked {} to {}", reference.id, reference.path);
+    }
+    Ok(media)
+}
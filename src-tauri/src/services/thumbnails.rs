@@ -0,0 +1,245 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use super::cache;
+use super::probe::{self, BasicInfo};
+use super::toolchain::Ffmpeg;
+use crate::error::NolanForgeError;
+
+/// Upper bound on frames per request, so a tiny interval on a long file
+/// cannot queue thousands of FFmpeg runs.
+const MAX_THUMBNAILS: usize = 300;
+
+/// Frames are taken at least this far before the end, where a seek can land
+/// past the last frame.
+const END_MARGIN: f64 = 0.1;
+
+const DEFAULT_WIDTH: u32 = 160;
+
+/// How thumbnails are handed back to the frontend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThumbnailFormat {
+    /// Only the path of the cached JPEG.
+    #[default]
+    Path,
+    /// The JPEG inlined as a `data:` URL as well.
+    Base64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThumbnailRequest {
+    pub path: String,
+    /// Source timestamps to grab. Takes precedence over `interval`.
+    #[serde(default)]
+    pub times: Option<Vec<f64>>,
+    /// Grab a frame every `interval` seconds over the whole file.
+    #[serde(default)]
+    pub interval: Option<f64>,
+    #[serde(default = "default_width")]
+    pub width: u32,
+    /// Without a height the source aspect ratio is kept; with one the frame
+    /// is scaled to fit inside `width` x `height`.
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub format: ThumbnailFormat,
+}
+
+fn default_width() -> u32 {
+    DEFAULT_WIDTH
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Thumbnail {
+    /// The requested timestamp.
+    pub time: f64,
+    pub path: String,
+    pub data_url: Option<String>,
+}
+
+/// Returns a thumbnail for every requested time, extracting the ones not yet
+/// in the cache.
+///
+/// Thumbnails are cached under the source's content fingerprint, so they
+/// survive restarts and are shared between copies of the same file.
+pub async fn generate_thumbnails(ffmpeg: &Ffmpeg, request: &ThumbnailRequest) -> Result<Vec<Thumbnail>, NolanForgeError> {
+    if request.width == 0 || request.height == Some(0) {
+        return Err(NolanForgeError::InvalidInput("Thumbnail size must be positive".to_string()));
+    }
+
+    let source = Path::new(&request.path);
+    let fingerprint = cache::source_fingerprint(source)?;
+    let cache_dir = cache::cache_dir("thumbnails")?.join(&fingerprint);
+    fs::create_dir_all(&cache_dir).map_err(|e| NolanForgeError::io("Failed to create thumbnail cache", e))?;
+
+    // Only probe when something actually needs the source's length
+    let mut info: Option<BasicInfo> = None;
+    let times = match (&request.times, request.interval) {
+        (Some(times), _) => times.clone(),
+        (None, Some(interval)) => {
+            let probed = probe_video(ffmpeg, source).await?;
+            let times = interval_times(probed.duration.unwrap_or(0.0), interval)?;
+            info = Some(probed);
+            times
+        }
+        (None, None) => {
+            return Err(NolanForgeError::InvalidInput(
+                "Either times or an interval is required".to_string(),
+            ))
+        }
+    };
+    if times.len() > MAX_THUMBNAILS {
+        return Err(NolanForgeError::InvalidInput(format!(
+            "Too many thumbnails requested ({}, at most {})",
+            times.len(),
+            MAX_THUMBNAILS
+        )));
+    }
+
+    let mut thumbnails = Vec::with_capacity(times.len());
+    let mut generated = 0;
+    for time in times {
+        if !time.is_finite() || time < 0.0 {
+            return Err(NolanForgeError::InvalidInput(format!("Invalid thumbnail time: {}", time)));
+        }
+
+        let path = cache_dir.join(cache_file_name(time, request.width, request.height));
+        if !path.exists() {
+            if info.is_none() {
+                info = Some(probe_video(ffmpeg, source).await?);
+            }
+            let duration = info.as_ref().and_then(|i| i.duration);
+            let seek = duration.map_or(time, |d| time.min((d - END_MARGIN).max(0.0)));
+            extract_frame(ffmpeg, source, seek, request.width, request.height, &path).await?;
+            generated += 1;
+        }
+
+        let data_url = match request.format {
+            ThumbnailFormat::Path => None,
+            ThumbnailFormat::Base64 => Some(data_url(&path)?),
+        };
+        thumbnails.push(Thumbnail {
+            time,
+            path: path.to_string_lossy().to_string(),
+            data_url,
+        });
+    }
+
+    println!(
+        "🔍 Debug: {} thumbnails for {} ({} generated, {} cached)",
+        thumbnails.len(),
+        request.path,
+        generated,
+        thumbnails.len() - generated
+    );
+    Ok(thumbnails)
+}
+
+async fn probe_video(ffmpeg: &Ffmpeg, source: &Path) -> Result<BasicInfo, NolanForgeError> {
+    let info = probe::basic_info(ffmpeg, source).await?;
+    if info.video_codec.is_none() {
+        return Err(NolanForgeError::InvalidInput(format!(
+            "{} has no video stream",
+            source.display()
+        )));
+    }
+    Ok(info)
+}
+
+/// Timestamps every `interval` seconds from the start of a file `duration`
+/// seconds long.
+fn interval_times(duration: f64, interval: f64) -> Result<Vec<f64>, NolanForgeError> {
+    if !interval.is_finite() || interval <= 0.0 {
+        return Err(NolanForgeError::InvalidInput(format!("Invalid thumbnail interval: {}", interval)));
+    }
+    let count = ((duration / interval).ceil() as usize).max(1);
+    if count > MAX_THUMBNAILS {
+        return Err(NolanForgeError::InvalidInput(format!(
+            "An interval of {}s gives too many thumbnails ({}, at most {})",
+            interval, count, MAX_THUMBNAILS
+        )));
+    }
+    Ok((0..count).map(|i| i as f64 * interval).collect())
+}
+
+/// Cache entries are keyed by millisecond and output size.
+fn cache_file_name(time: f64, width: u32, height: Option<u32>) -> String {
+    let millis = (time * 1000.0).round() as u64;
+    match height {
+        Some(height) => format!("{}_{}x{}.jpg", millis, width, height),
+        None => format!("{}_{}w.jpg", millis, width),
+    }
+}
+
+/// Decodes the frame at `time` and writes it to `output` as a JPEG.
+async fn extract_frame(
+    ffmpeg: &Ffmpeg,
+    source: &Path,
+    time: f64,
+    width: u32,
+    height: Option<u32>,
+    output: &Path,
+) -> Result<(), NolanForgeError> {
+    let scale = match height {
+        Some(height) => format!("scale={}:{}:force_original_aspect_ratio=decrease", width, height),
+        None => format!("scale={}:-2", width),
+    };
+
+    // Write next to the cache entry and rename, so an interrupted run never
+    // leaves a truncated image behind
+    let temp_path: PathBuf = output.with_extension(format!("{}.tmp", uuid::Uuid::new_v4()));
+
+    // Seeking before -i jumps to the nearest keyframe and decodes from there,
+    // which keeps this fast even deep into long files
+    let result = ffmpeg
+        .command()
+        .arg("-hide_banner")
+        .arg("-v")
+        .arg("error")
+        .arg("-ss")
+        .arg(time.to_string())
+        .arg("-i")
+        .arg(source)
+        .arg("-frames:v")
+        .arg("1")
+        .arg("-an")
+        .arg("-vf")
+        .arg(&scale)
+        .arg("-c:v")
+        .arg("mjpeg")
+        .arg("-q:v")
+        .arg("4")
+        .arg("-f")
+        .arg("image2")
+        .arg("-y")
+        .arg(&temp_path)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| NolanForgeError::launch("FFmpeg", e))?;
+
+    if !result.status.success() || !temp_path.exists() {
+        let _ = fs::remove_file(&temp_path);
+        return Err(NolanForgeError::ffmpeg(
+            format!("Failed to extract a frame at {}s from {}", time, source.display()),
+            String::from_utf8_lossy(&result.stderr),
+        ));
+    }
+
+    fs::rename(&temp_path, output).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        NolanForgeError::io("Failed to write thumbnail", e)
+    })
+}
+
+fn data_url(path: &Path) -> Result<String, NolanForgeError> {
+    let bytes = fs::read(path).map_err(|e| NolanForgeError::io("Failed to read thumbnail", e))?;
+    Ok(format!(
+        "data:image/jpeg;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(bytes)
+    ))
+}
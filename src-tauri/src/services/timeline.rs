@@ -0,0 +1,429 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::composition::{composite_overlay, BlendMode, Canvas, FitMode, OverlayPlacement};
+use super::encoding::EncodingProfile;
+use super::ffmpeg::{ffmpeg_command, run_ffmpeg, ProgressReporter};
+use super::probe;
+use super::toolchain::Ffmpeg;
+use crate::error::NolanForgeError;
+
+/// Sample rate and layout every clip's audio is converted to before mixing.
+const MIX_SAMPLE_RATE: u32 = 48000;
+const MIX_CHANNEL_LAYOUT: &str = "stereo";
+
+/// A whole edit: tracks of clips laid out on a shared timeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineDocument {
+    #[serde(default)]
+    pub canvas: Canvas,
+    /// Tracks from bottom to top; later tracks are drawn over earlier ones.
+    pub tracks: Vec<Track>,
+    /// Part of the timeline to render. Defaults to everything up to the end
+    /// of the last clip.
+    #[serde(default)]
+    pub range: Option<TimeRange>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TimeRange {
+    pub start: f64,
+    pub end: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Track {
+    pub id: i32,
+    #[serde(default)]
+    pub muted: bool,
+    #[serde(default)]
+    pub solo: bool,
+    pub clips: Vec<Clip>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Clip {
+    pub input_path: String,
+    /// Timeline position the clip starts at.
+    pub start_time: f64,
+    pub trim_start: f64,
+    pub trim_end: f64,
+    #[serde(default)]
+    pub transform: Transform,
+    #[serde(default)]
+    pub audio: ClipAudio,
+}
+
+/// Where and how a clip's picture is drawn.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transform {
+    /// Top-left corner on the canvas. Together with `size` this places the
+    /// clip in a box; without them it covers the whole canvas.
+    #[serde(default)]
+    pub position: Option<(f64, f64)>,
+    #[serde(default)]
+    pub size: Option<(f64, f64)>,
+    /// How the clip is scaled into its box.
+    #[serde(default)]
+    pub fit_mode: FitMode,
+    #[serde(default = "full_opacity")]
+    pub opacity: f64,
+    #[serde(default)]
+    pub blend_mode: BlendMode,
+}
+
+fn full_opacity() -> f64 {
+    1.0
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            position: None,
+            size: None,
+            fit_mode: FitMode::default(),
+            opacity: full_opacity(),
+            blend_mode: BlendMode::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipAudio {
+    /// Linear volume multiplier.
+    #[serde(default = "unity_gain")]
+    pub gain: f64,
+    #[serde(default)]
+    pub muted: bool,
+}
+
+fn unity_gain() -> f64 {
+    1.0
+}
+
+impl Default for ClipAudio {
+    fn default() -> Self {
+        ClipAudio {
+            gain: unity_gain(),
+            muted: false,
+        }
+    }
+}
+
+impl Clip {
+    pub fn duration(&self) -> f64 {
+        self.trim_end - self.trim_start
+    }
+}
+
+impl TimelineDocument {
+    pub fn validate(&self) -> Result<(), NolanForgeError> {
+        self.canvas.validate()?;
+
+        if self.tracks.iter().all(|track| track.clips.is_empty()) {
+            return Err(NolanForgeError::InvalidInput("Timeline has no clips".to_string()));
+        }
+
+        for track in &self.tracks {
+            for (i, clip) in track.clips.iter().enumerate() {
+                if clip.trim_start < 0.0 || clip.duration() <= 0.0 {
+                    return Err(NolanForgeError::InvalidInput(format!(
+                        "Invalid trim range for clip {} on track {}",
+                        i, track.id
+                    )));
+                }
+                if !(0.0..=1.0).contains(&clip.transform.opacity) {
+                    return Err(NolanForgeError::InvalidInput(format!(
+                        "Invalid opacity for clip {} on track {}",
+                        i, track.id
+                    )));
+                }
+            }
+        }
+
+        let range = self.render_range();
+        if range.start < 0.0 || range.end <= range.start {
+            return Err(NolanForgeError::InvalidInput("Invalid timeline range".to_string()));
+        }
+        Ok(())
+    }
+
+    /// The requested range, or the whole timeline.
+    pub fn render_range(&self) -> TimeRange {
+        self.range.unwrap_or_else(|| TimeRange {
+            start: 0.0,
+            end: self
+                .tracks
+                .iter()
+                .flat_map(|track| &track.clips)
+                .map(|clip| clip.start_time + clip.duration())
+                .fold(0.0, f64::max),
+        })
+    }
+
+    /// Whether any rendered clip is composited with a blend mode, which
+    /// needs FFmpeg's `blend` filter.
+    pub fn uses_blend_modes(&self) -> bool {
+        clip_spans(self)
+            .iter()
+            .any(|span| span.clip.transform.blend_mode != BlendMode::Normal)
+    }
+
+    /// Whether a track's audio ends up in the mix. As soon as any track is
+    /// soloed, only soloed tracks are heard.
+    pub fn track_audible(&self, track: &Track) -> bool {
+        !track.muted && (track.solo || !self.tracks.iter().any(|t| t.solo))
+    }
+}
+
+/// The part of a clip that falls inside the rendered range.
+struct ClipSpan<'a> {
+    clip: &'a Clip,
+    track_id: i32,
+    /// Position in the source file to read from.
+    source_start: f64,
+    /// Position in the output to place it at.
+    offset: f64,
+    duration: f64,
+    audible: bool,
+}
+
+/// Cuts every clip down to the rendered range, bottom track first.
+fn clip_spans(document: &TimelineDocument) -> Vec<ClipSpan<'_>> {
+    let range = document.render_range();
+    let range_duration = range.end - range.start;
+    let mut spans = Vec::new();
+
+    for track in &document.tracks {
+        let audible = document.track_audible(track);
+
+        let mut clips: Vec<_> = track.clips.iter().collect();
+        clips.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+
+        for clip in clips {
+            // Clips that start before the range lose their head
+            let timeline_offset = clip.start_time - range.start;
+            let skipped = (-timeline_offset).max(0.0);
+            let offset = timeline_offset.max(0.0);
+            let duration = (clip.duration() - skipped).min(range_duration - offset);
+            if duration <= 0.0 {
+                continue;
+            }
+
+            spans.push(ClipSpan {
+                clip,
+                track_id: track.id,
+                source_start: clip.trim_start + skipped,
+                offset,
+                duration,
+                audible: audible && !clip.audio.muted && clip.audio.gain > 0.0,
+            });
+        }
+    }
+
+    spans
+}
+
+/// Builds the filter graph that composites every span onto the canvas and
+/// mixes their audio. Input 0 is the canvas background, input 1 silence
+/// covering the whole range, and span `i` is input `i + 2`. The result is
+/// available as `[video]` and `[audio]`.
+fn build_filter_graph(spans: &[ClipSpan<'_>], has_audio: &[bool], canvas: &Canvas) -> String {
+    let mut parts = Vec::new();
+
+    // Video: each clip is drawn over everything below it
+    let mut current = "0:v".to_string();
+    for (i, span) in spans.iter().enumerate() {
+        let transform = &span.clip.transform;
+        let output = if i == spans.len() - 1 { "video".to_string() } else { format!("v{}", i) };
+
+        let (x, y, width, height) = match (transform.position, transform.size) {
+            (Some((x, y)), Some((width, height))) => (x as i64, y as i64, width as i64, height as i64),
+            _ => (0, 0, canvas.width as i64, canvas.height as i64),
+        };
+        let placement = OverlayPlacement {
+            x,
+            y,
+            width,
+            height,
+            fit_mode: transform.fit_mode,
+            opacity: transform.opacity,
+            blend_mode: transform.blend_mode,
+            start: span.offset,
+            end: span.offset + span.duration,
+        };
+
+        parts.push(composite_overlay(
+            &current,
+            &format!("{}:v", i + 2),
+            &output,
+            &format!("c{}", i),
+            &placement,
+            canvas,
+        ));
+        current = output;
+    }
+    if spans.is_empty() {
+        parts.push("[0:v]null[video]".to_string());
+    }
+
+    // Audio: every audible clip at its position, mixed onto the silence so
+    // gaps stay silent
+    let mut mix_inputs = String::from("[1:a]");
+    let mut mix_count = 1;
+    for (i, span) in spans.iter().enumerate() {
+        if !span.audible || !has_audio[i] {
+            continue;
+        }
+        let delay_ms = (span.offset * 1000.0).round() as i64;
+        parts.push(format!(
+            "[{}:a]aformat=sample_rates={}:channel_layouts={},volume={},adelay={}:all=1[a{}]",
+            i + 2,
+            MIX_SAMPLE_RATE,
+            MIX_CHANNEL_LAYOUT,
+            span.clip.audio.gain,
+            delay_ms,
+            i
+        ));
+        mix_inputs.push_str(&format!("[a{}]", i));
+        mix_count += 1;
+    }
+    // No normalization, so adding a clip does not turn the others down
+    parts.push(format!(
+        "{}amix=inputs={}:duration=first:normalize=0[audio]",
+        mix_inputs, mix_count
+    ));
+
+    parts.join(";")
+}
+
+/// Progress labels naming the main track clip being rendered, by where it
+/// starts in the output. Empty for a single clip.
+fn stage_labels(spans: &[ClipSpan<'_>]) -> Vec<(f64, String)> {
+    let main_track = spans.iter().map(|span| span.track_id).min_by_key(|id| (*id != 0, *id));
+    let mut starts: Vec<f64> = spans
+        .iter()
+        .filter(|span| Some(span.track_id) == main_track)
+        .map(|span| span.offset)
+        .collect();
+    if starts.len() < 2 {
+        return Vec::new();
+    }
+    starts.sort_by(f64::total_cmp);
+    let count = starts.len();
+    starts
+        .into_iter()
+        .enumerate()
+        .map(|(i, start)| (start, format!("Rendering clip {}/{}", i + 1, count)))
+        .collect()
+}
+
+/// Renders the document to `output` in a single FFmpeg pass.
+pub async fn render_timeline(
+    ffmpeg: &Ffmpeg,
+    reporter: &mut ProgressReporter<'_>,
+    document: &TimelineDocument,
+    output: &Path,
+    profile: &EncodingProfile,
+) -> Result<(), NolanForgeError> {
+    document.validate()?;
+    profile.validate()?;
+
+    // Probe every source once and check the trims against its real length
+    let mut sources: HashMap<&str, probe::BasicInfo> = HashMap::new();
+    for clip in document.tracks.iter().flat_map(|track| &track.clips) {
+        let path = clip.input_path.as_str();
+        if !sources.contains_key(path) {
+            sources.insert(path, probe::basic_info(ffmpeg, Path::new(path)).await?);
+        }
+        sources[path].check_range(Path::new(path), clip.trim_start, clip.trim_end)?;
+    }
+
+    let range = document.render_range();
+    let duration = range.end - range.start;
+    let spans = clip_spans(document);
+
+    // Clips without an audio stream have no [n:a] pad to mix
+    let has_audio: Vec<bool> = spans
+        .iter()
+        .map(|span| sources[span.clip.input_path.as_str()].has_audio)
+        .collect();
+
+    let filter_graph = build_filter_graph(&spans, &has_audio, &document.canvas);
+    println!("🔍 Debug: Timeline filter graph: {}", filter_graph);
+
+    let mut cmd = ffmpeg_command(ffmpeg);
+    cmd.arg("-f")
+        .arg("lavfi")
+        .arg("-i")
+        .arg(document.canvas.color_source(duration))
+        .arg("-f")
+        .arg("lavfi")
+        .arg("-i")
+        .arg(format!(
+            "anullsrc=r={}:cl={}:d={}",
+            MIX_SAMPLE_RATE, MIX_CHANNEL_LAYOUT, duration
+        ));
+    for span in &spans {
+        cmd.arg("-ss")
+            .arg(span.source_start.to_string())
+            .arg("-t")
+            .arg(span.duration.to_string())
+            .arg("-i")
+            .arg(&span.clip.input_path);
+    }
+
+    cmd.arg("-filter_complex")
+        .arg(&filter_graph)
+        .arg("-map")
+        .arg("[video]")
+        .arg("-map")
+        .arg("[audio]")
+        .args(profile.output_args())
+        .arg("-t")
+        .arg(duration.to_string())
+        .arg("-y")
+        .arg(output);
+
+    reporter.set_stage_labels(stage_labels(&spans));
+    run_ffmpeg(cmd, reporter, "Rendering timeline", duration).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document(range: Option<(f64, f64)>) -> TimelineDocument {
+        let clip = |start_time: f64, duration: f64| {
+            serde_json::json!({ "input_path": "clip.mp4", "start_time": start_time, "trim_start": 0.0, "trim_end": duration })
+        };
+        serde_json::from_value(serde_json::json!({
+            "tracks": [
+                { "id": 0, "clips": [clip(5.0, 5.0), clip(0.0, 5.0)] },
+                { "id": 1, "clips": [clip(2.0, 4.0)] },
+            ],
+            "range": range.map(|(start, end)| serde_json::json!({ "start": start, "end": end })),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn progress_names_the_main_track_clip() {
+        let doc = document(None);
+        assert_eq!(
+            stage_labels(&clip_spans(&doc)),
+            vec![(0.0, "Rendering clip 1/2".to_string()), (5.0, "Rendering clip 2/2".to_string())]
+        );
+
+        // Positions are in the output, which starts at the range
+        let doc = document(Some((3.0, 10.0)));
+        assert_eq!(
+            stage_labels(&clip_spans(&doc)),
+            vec![(0.0, "Rendering clip 1/2".to_string()), (2.0, "Rendering clip 2/2".to_string())]
+        );
+
+        let doc = document(Some((6.0, 10.0)));
+        assert!(stage_labels(&clip_spans(&doc)).is_empty());
+    }
+}
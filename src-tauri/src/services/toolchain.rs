@@ -0,0 +1,564 @@
+use serde::Serialize;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::process::Command;
+use tokio::sync::{Mutex, OnceCell};
+
+use crate::config::Config;
+use crate::error::NolanForgeError;
+
+/// Oldest FFmpeg whose filters take every option the export graph uses
+/// (`amix=normalize` arrived in 4.4).
+pub const MIN_FFMPEG_VERSION: (u32, u32) = (4, 4);
+
+/// What a NolanForge feature needs from FFmpeg.
+struct FeatureRequirements {
+    id: &'static str,
+    name: &'static str,
+    encoders: &'static [&'static str],
+    filters: &'static [&'static str],
+    /// Muxers or demuxers, by their `-formats` name.
+    formats: &'static [&'static str],
+    /// Whether it runs the export filter graph, which needs
+    /// [`MIN_FFMPEG_VERSION`].
+    filter_graph: bool,
+}
+
+const FEATURES: &[FeatureRequirements] = &[
+    FeatureRequirements {
+        id: "export",
+        name: "Timeline export",
+        encoders: &[],
+        filters: &[
+            "color", "anullsrc", "null", "scale", "pad", "crop", "setsar", "setpts", "format", "overlay",
+            "colorchannelmixer", "aformat", "volume", "adelay", "amix",
+        ],
+        formats: &["lavfi"],
+        filter_graph: true,
+    },
+    FeatureRequirements {
+        id: "blend_modes",
+        name: "Overlay blend modes",
+        encoders: &[],
+        filters: &["blend"],
+        formats: &[],
+        filter_graph: true,
+    },
+    FeatureRequirements {
+        id: "h264_export",
+        name: "H.264 / AAC export (default profiles)",
+        encoders: &["libx264", "aac"],
+        filters: &[],
+        formats: &["mp4"],
+        filter_graph: false,
+    },
+    FeatureRequirements {
+        id: "hevc_export",
+        name: "HEVC (libx265) export",
+        encoders: &["libx265"],
+        filters: &[],
+        formats: &[],
+        filter_graph: true,
+    },
+    FeatureRequirements {
+        id: "flac_audio",
+        name: "FLAC audio in MKV",
+        encoders: &["flac"],
+        filters: &[],
+        formats: &["matroska"],
+        filter_graph: false,
+    },
+    FeatureRequirements {
+        id: "proxies",
+        name: "Proxy generation",
+        encoders: &["libx264", "aac"],
+        filters: &["scale"],
+        formats: &["mp4"],
+        filter_graph: false,
+    },
+    FeatureRequirements {
+        id: "thumbnails",
+        name: "Thumbnails",
+        encoders: &["mjpeg"],
+        filters: &["scale"],
+        formats: &["image2"],
+        filter_graph: false,
+    },
+    FeatureRequirements {
+        id: "waveforms",
+        name: "Audio waveforms",
+        encoders: &["pcm_s16le"],
+        filters: &["concat"],
+        formats: &["s16le"],
+        filter_graph: false,
+    },
+    FeatureRequirements {
+        id: "transcription_audio",
+        name: "Audio extraction for transcription",
+        encoders: &["libmp3lame"],
+        // Several clips are joined, long recordings split at silences
+        filters: &["concat", "silencedetect"],
+        formats: &["mp3"],
+        filter_graph: false,
+    },
+    FeatureRequirements {
+        id: "local_transcription_audio",
+        name: "Audio extraction for offline transcription",
+        encoders: &["pcm_s16le"],
+        filters: &["concat"],
+        formats: &["wav"],
+        filter_graph: false,
+    },
+    FeatureRequirements {
+        id: "subtitle_burn_in",
+        name: "Subtitle burn-in (libass)",
+        encoders: &[],
+        filters: &["subtitles"],
+        formats: &[],
+        filter_graph: true,
+    },
+    FeatureRequirements {
+        id: "loudnorm",
+        name: "Loudness normalization",
+        encoders: &[],
+        filters: &["loudnorm"],
+        formats: &[],
+        filter_graph: true,
+    },
+    FeatureRequirements {
+        id: "drawtext",
+        name: "Text overlays (drawtext)",
+        encoders: &[],
+        filters: &["drawtext"],
+        formats: &[],
+        filter_graph: true,
+    },
+];
+
+/// Where the FFmpeg in use was found. Locations are tried in this order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FfmpegSource {
+    /// Chosen in Settings or with `NOLANFORGE_FFMPEG`.
+    Configured,
+    /// Bundled with the app as a Tauri sidecar, next to its executable.
+    Sidecar,
+    /// Installed into `ffmpeg/` in the app data directory.
+    AppData,
+    Path,
+}
+
+/// The FFmpeg and ffprobe binaries every service runs.
+#[derive(Debug, Serialize)]
+pub struct Ffmpeg {
+    pub ffmpeg_path: PathBuf,
+    pub ffprobe_path: PathBuf,
+    pub source: FfmpegSource,
+    /// First line of `ffmpeg -version`.
+    pub version: String,
+    #[serde(skip)]
+    capabilities: OnceCell<FfmpegCapabilities>,
+}
+
+impl Ffmpeg {
+    pub fn command(&self) -> Command {
+        Command::new(&self.ffmpeg_path)
+    }
+
+    pub fn ffprobe_command(&self) -> Command {
+        Command::new(&self.ffprobe_path)
+    }
+
+    /// What this build can do, listed on first use.
+    pub async fn capabilities(&self) -> Result<&FfmpegCapabilities, NolanForgeError> {
+        self.capabilities
+            .get_or_try_init(|| async {
+                let capabilities = FfmpegCapabilities {
+                    encoders: self.list("-encoders").await?,
+                    decoders: self.list("-decoders").await?,
+                    filters: self.list("-filters").await?,
+                    formats: self.list("-formats").await?,
+                    hwaccels: self.list("-hwaccels").await?,
+                };
+                println!(
+                    "🔍 Debug: FFmpeg has {} encoders, {} decoders, {} filters, {} formats, {} hwaccels",
+                    capabilities.encoders.len(),
+                    capabilities.decoders.len(),
+                    capabilities.filters.len(),
+                    capabilities.formats.len(),
+                    capabilities.hwaccels.len()
+                );
+                Ok(capabilities)
+            })
+            .await
+    }
+
+    /// `major.minor` of this build, `None` for development builds (which
+    /// report a git revision instead) and anything else unrecognised.
+    pub fn version_number(&self) -> Option<(u32, u32)> {
+        parse_version(&self.version)
+    }
+
+    /// False only for builds known to be older than [`MIN_FFMPEG_VERSION`];
+    /// development builds are assumed recent.
+    pub fn meets_minimum_version(&self) -> bool {
+        self.version_number().is_none_or(|version| version >= MIN_FFMPEG_VERSION)
+    }
+
+    /// Every NolanForge feature and whether this build supports it.
+    pub async fn features(&self) -> Result<Vec<FeatureSupport>, NolanForgeError> {
+        let capabilities = self.capabilities().await?;
+        Ok(FEATURES
+            .iter()
+            .map(|feature| {
+                let mut missing = Vec::new();
+                if feature.filter_graph && !self.meets_minimum_version() {
+                    missing.push(format!(
+                        "FFmpeg {}.{} or newer",
+                        MIN_FFMPEG_VERSION.0, MIN_FFMPEG_VERSION.1
+                    ));
+                }
+                let lacking = |kind: &str, names: &[&str], list: &[String]| {
+                    names
+                        .iter()
+                        .filter(|name| !list.iter().any(|item| item == *name))
+                        .map(|name| format!("{} {}", kind, name))
+                        .collect::<Vec<_>>()
+                };
+                missing.extend(lacking("encoder", feature.encoders, &capabilities.encoders));
+                missing.extend(lacking("filter", feature.filters, &capabilities.filters));
+                missing.extend(lacking("format", feature.formats, &capabilities.formats));
+
+                FeatureSupport {
+                    id: feature.id,
+                    name: feature.name,
+                    available: missing.is_empty(),
+                    missing,
+                }
+            })
+            .collect())
+    }
+
+    pub async fn capability_report(&self) -> Result<CapabilityReport, NolanForgeError> {
+        Ok(CapabilityReport {
+            version: self.version.clone(),
+            minimum_version: format!("{}.{}", MIN_FFMPEG_VERSION.0, MIN_FFMPEG_VERSION.1),
+            meets_minimum_version: self.meets_minimum_version(),
+            features: self.features().await?,
+            capabilities: self.capabilities().await?.clone(),
+        })
+    }
+
+    /// Fails with [`NolanForgeError::FeatureUnavailable`] unless this build
+    /// supports the feature `id`.
+    pub async fn require(&self, id: &str) -> Result<(), NolanForgeError> {
+        let features = self.features().await?;
+        let Some(feature) = features.into_iter().find(|feature| feature.id == id) else {
+            return Err(NolanForgeError::Internal(format!("Unknown FFmpeg feature {}", id)));
+        };
+        if feature.available {
+            return Ok(());
+        }
+        Err(NolanForgeError::FeatureUnavailable {
+            feature: feature.name.to_string(),
+            missing: feature.missing,
+        })
+    }
+
+    /// Names from one of FFmpeg's listing options, e.g. `-encoders`.
+    async fn list(&self, option: &str) -> Result<Vec<String>, NolanForgeError> {
+        let output = self
+            .command()
+            .arg("-hide_banner")
+            .arg(option)
+            .output()
+            .await
+            .map_err(|e| NolanForgeError::launch("FFmpeg", e))?;
+        if !output.status.success() {
+            return Err(NolanForgeError::ffmpeg(
+                format!("Failed to run ffmpeg {}", option),
+                String::from_utf8_lossy(&output.stderr),
+            ));
+        }
+        Ok(parse_list(option, &String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+/// Encoders, decoders, filters, formats and hardware accelerations of an
+/// FFmpeg build, so features it lacks can be turned off instead of failing.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FfmpegCapabilities {
+    pub encoders: Vec<String>,
+    pub decoders: Vec<String>,
+    pub filters: Vec<String>,
+    /// Muxers and demuxers.
+    pub formats: Vec<String>,
+    pub hwaccels: Vec<String>,
+}
+
+impl FfmpegCapabilities {
+    pub fn has_encoder(&self, name: &str) -> bool {
+        self.encoders.iter().any(|encoder| encoder == name)
+    }
+}
+
+/// Whether the FFmpeg in use supports a NolanForge feature, and if not what
+/// it lacks.
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureSupport {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub available: bool,
+    /// E.g. `encoder libx265` or `FFmpeg 4.4 or newer`.
+    pub missing: Vec<String>,
+}
+
+/// What the `ffmpeg_capabilities` command reports.
+#[derive(Debug, Clone, Serialize)]
+pub struct CapabilityReport {
+    pub version: String,
+    pub minimum_version: String,
+    pub meets_minimum_version: bool,
+    pub features: Vec<FeatureSupport>,
+    #[serde(flatten)]
+    pub capabilities: FfmpegCapabilities,
+}
+
+/// Parses `major.minor` from a `-version` line such as `ffmpeg version
+/// 6.1.1-3ubuntu5` or `ffmpeg version n7.0`.
+fn parse_version(line: &str) -> Option<(u32, u32)> {
+    let version = line.split_whitespace().skip_while(|word| *word != "version").nth(1)?;
+    let mut numbers = version
+        .trim_start_matches('n')
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|number| number.parse::<u32>().ok());
+    let major = numbers.next()??;
+    let minor = numbers.next().flatten().unwrap_or(0);
+    Some((major, minor))
+}
+
+fn parse_list(option: &str, stdout: &str) -> Vec<String> {
+    let lines = stdout.lines().map(str::trim).filter(|line| !line.is_empty());
+    if option == "-hwaccels" {
+        // A `Hardware acceleration methods:` heading, then one name per line
+        return lines.skip(1).map(str::to_string).collect();
+    }
+
+    // A legend of `<flag> = <meaning>` lines, ended by a `--` line except
+    // for `-filters`, then one `<flags> <name> ...` line per entry
+    let body: Vec<&str> = if option == "-filters" {
+        lines.skip_while(|line| !line.contains("=")).skip_while(|line| line.contains("=")).collect()
+    } else {
+        lines.skip_while(|line| !line.starts_with("--")).skip(1).collect()
+    };
+    body.into_iter()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        // Formats list aliases together, e.g. `matroska,webm`
+        .flat_map(|names| names.split(','))
+        .map(str::to_string)
+        .collect()
+}
+
+/// The resolved FFmpeg, kept in Tauri managed state.
+///
+/// It is looked for on first use and again after the configured path
+/// changes, rather than on every command.
+#[derive(Default)]
+pub struct FfmpegState {
+    ffmpeg: Mutex<Option<Arc<Ffmpeg>>>,
+}
+
+impl FfmpegState {
+    pub async fn get(&self) -> Result<Arc<Ffmpeg>, NolanForgeError> {
+        let mut ffmpeg = self.ffmpeg.lock().await;
+        if let Some(ffmpeg) = ffmpeg.as_ref() {
+            return Ok(ffmpeg.clone());
+        }
+        let resolved = Arc::new(resolve().await?);
+        *ffmpeg = Some(resolved.clone());
+        Ok(resolved)
+    }
+
+    /// Uses the FFmpeg at `path` from now on, or goes back to searching for
+    /// one when `None`. A path that does not run is rejected and not saved.
+    pub async fn configure(&self, path: Option<PathBuf>) -> Result<Arc<Ffmpeg>, NolanForgeError> {
+        if let Some(path) = &path {
+            version(&executable_in(path, "ffmpeg")).await?;
+        }
+        Config::store_ffmpeg_path(path.as_deref())?;
+        *self.ffmpeg.lock().await = None;
+        self.get().await
+    }
+}
+
+fn exe_name(name: &str) -> String {
+    format!("{}{}", name, env::consts::EXE_SUFFIX)
+}
+
+/// `path` itself, or the binary called `name` in it if it is a directory.
+fn executable_in(path: &Path, name: &str) -> PathBuf {
+    if path.is_dir() {
+        path.join(exe_name(name))
+    } else {
+        path.to_path_buf()
+    }
+}
+
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(exe_name(name)))
+        .find(|path| path.is_file())
+}
+
+/// Every place FFmpeg could be, in the order they are tried.
+fn candidates() -> Vec<(FfmpegSource, PathBuf)> {
+    let mut candidates = Vec::new();
+    if let Some(path) = Config::ffmpeg_path() {
+        candidates.push((FfmpegSource::Configured, executable_in(&path, "ffmpeg")));
+    }
+    // Tauri places `externalBin` sidecars next to the main executable
+    if let Some(dir) = env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        candidates.push((FfmpegSource::Sidecar, dir.join(exe_name("ffmpeg"))));
+    }
+    if let Ok(dir) = Config::app_data_dir() {
+        candidates.push((FfmpegSource::AppData, dir.join("ffmpeg").join(exe_name("ffmpeg"))));
+    }
+    if let Some(path) = find_on_path("ffmpeg") {
+        candidates.push((FfmpegSource::Path, path));
+    }
+    candidates
+}
+
+async fn version(ffmpeg: &Path) -> Result<String, NolanForgeError> {
+    let output = Command::new(ffmpeg)
+        .arg("-version")
+        .output()
+        .await
+        .map_err(|e| NolanForgeError::launch(&ffmpeg.to_string_lossy(), e))?;
+    if !output.status.success() {
+        return Err(NolanForgeError::ffmpeg(
+            format!("{} returned an error", ffmpeg.display()),
+            String::from_utf8_lossy(&output.stderr),
+        ));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().next().unwrap_or("Unknown version").to_string())
+}
+
+/// Finds the first FFmpeg that runs, with the ffprobe next to it or else the
+/// one on PATH.
+async fn resolve() -> Result<Ffmpeg, NolanForgeError> {
+    let mut tried = Vec::new();
+    for (source, ffmpeg_path) in candidates() {
+        // Only a configured path is worth complaining about when missing
+        if source != FfmpegSource::Configured && !ffmpeg_path.is_file() {
+            continue;
+        }
+        let version = match version(&ffmpeg_path).await {
+            Ok(version) => version,
+            Err(e) => {
+                eprintln!("Warning: Skipping FFmpeg at {}: {}", ffmpeg_path.display(), e);
+                tried.push(format!("{}: {}", ffmpeg_path.display(), e));
+                continue;
+            }
+        };
+
+        let ffprobe_path = ffmpeg_path
+            .parent()
+            .map(|dir| dir.join(exe_name("ffprobe")))
+            .filter(|path| path.is_file())
+            .or_else(|| find_on_path("ffprobe"))
+            .unwrap_or_else(|| PathBuf::from(exe_name("ffprobe")));
+
+        println!(
+            "🔍 Debug: Using FFmpeg at {} ({:?}), ffprobe at {}",
+            ffmpeg_path.display(),
+            source,
+            ffprobe_path.display()
+        );
+        return Ok(Ffmpeg {
+            ffmpeg_path,
+            ffprobe_path,
+            source,
+            version,
+            capabilities: OnceCell::new(),
+        });
+    }
+
+    tried.push("ffmpeg: not found on PATH".to_string());
+    Err(NolanForgeError::FfmpegNotFound {
+        diagnostic: tried.join("\n"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(banner_version: &str) -> Option<(u32, u32)> {
+        parse_version(&format!("ffmpeg version {} Copyright (c) 2000-2024 the FFmpeg developers", banner_version))
+    }
+
+    #[test]
+    fn versions_are_read_from_the_banner() {
+        assert_eq!(version("6.1.1"), Some((6, 1)));
+        assert_eq!(version("n6.1"), Some((6, 1)));
+        assert_eq!(version("7.0-full_build-www.gyan.dev"), Some((7, 0)));
+        assert_eq!(version("4.4.2-0ubuntu0.22.04.1"), Some((4, 4)));
+        assert_eq!(version("7"), Some((7, 0)));
+        // Git snapshots carry no release number, so they are not held back
+        assert_eq!(version("N-112345-gabc1234"), None);
+        assert_eq!(parse_version("ffmpeg: command not found"), None);
+    }
+
+    #[test]
+    fn minimum_version_compares_major_then_minor() {
+        assert!(version("4.4.2-0ubuntu0.22.04.1").unwrap() >= MIN_FFMPEG_VERSION);
+        assert!(version("5.0").unwrap() >= MIN_FFMPEG_VERSION);
+        assert!(version("4.3.6").unwrap() < MIN_FFMPEG_VERSION);
+        assert!(version("3.4.13").unwrap() < MIN_FFMPEG_VERSION);
+    }
+
+    #[test]
+    fn filter_list_skips_the_legend() {
+        let stdout = "Filters:
+  T.. = Timeline support
+  .S. = Slice threading
+  ..C = Command support
+  A = Audio input/output
+  V = Video input/output
+  N = Dynamic number and/or type of input/output
+  | = Source or sink filter
+ ... abench            A->A       Benchmark part of a filtergraph.
+ TSC blend             VV->V      Blend two video frames into each other.
+ ... color             |->V       Provide an uniformly colored input.
+";
+        assert_eq!(parse_list("-filters", stdout), vec!["abench", "blend", "color"]);
+    }
+
+    #[test]
+    fn encoder_and_format_lists_start_after_the_separator() {
+        let encoders = "Encoders:
+ V..... = Video
+ A..... = Audio
+ ------
+ V....D libx264              libx264 H.264 / AVC / MPEG-4 AVC (codec h264)
+ A....D aac                  AAC (Advanced Audio Coding)
+";
+        assert_eq!(parse_list("-encoders", encoders), vec!["libx264", "aac"]);
+
+        let formats = "File formats:
+ D. = Demuxing supported
+ .E = Muxing supported
+ --
+ DE matroska,webm       Matroska / WebM
+  E mp4             MP4 (MPEG-4 Part 14)
+";
+        assert_eq!(parse_list("-formats", formats), vec!["matroska", "webm", "mp4"]);
+
+        let hwaccels = "Hardware acceleration methods:\nvdpau\ncuda\n";
+        assert_eq!(parse_list("-hwaccels", hwaccels), vec!["vdpau", "cuda"]);
+    }
+}
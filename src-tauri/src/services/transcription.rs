@@ -0,0 +1,345 @@
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+use super::jobs::Job;
+use super::openai::OpenAiProvider;
+use super::whisper_cpp::WhisperCppProvider;
+use crate::config::{Config, TranscriptionBackend};
+use crate::error::NolanForgeError;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TranscriptionResponse {
+    pub text: String,
+    pub language: Option<String>,
+    pub duration: Option<f64>,
+    pub words: Option<Vec<Word>>,
+    pub segments: Option<Vec<Segment>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Word {
+    pub word: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Segment {
+    pub id: i32,
+    pub seek: f64,
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+    pub tokens: Vec<i32>,
+    pub temperature: f64,
+    pub avg_logprob: f64,
+    pub compression_ratio: f64,
+    pub no_speech_prob: f64,
+}
+
+/// Event emitted with the job ID once `transcribe_video` has registered
+/// its job, so it can be cancelled.
+pub const TRANSCRIPTION_STARTED_EVENT: &str = "transcription-started";
+
+/// Event emitted while `transcribe_video` runs.
+pub const TRANSCRIPTION_PROGRESS_EVENT: &str = "transcription-progress";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptionStarted {
+    pub job_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptionProgress {
+    pub stage: String,
+    pub percent: f64,
+}
+
+/// Audio a provider accepts, extracted from the video by FFmpeg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AudioFormat {
+    /// 16 kHz mono MP3, small enough to upload.
+    Mp3,
+    /// 16 kHz mono 16-bit PCM, which whisper.cpp reads directly.
+    Wav,
+}
+
+impl AudioFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Wav => "wav",
+        }
+    }
+
+    /// The FFmpeg feature needed to extract it.
+    pub fn feature(self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "transcription_audio",
+            AudioFormat::Wav => "local_transcription_audio",
+        }
+    }
+
+    pub fn codec(self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::Wav => "pcm_s16le",
+        }
+    }
+}
+
+/// Part of a source to transcribe. Ranges are extracted back to back into
+/// one audio file.
+#[derive(Debug, Clone)]
+pub struct SourceRange {
+    pub input_path: PathBuf,
+    /// Seconds into the source.
+    pub start: f64,
+    /// `None` runs to the end of the source.
+    pub end: Option<f64>,
+    /// Where `start` sits on the timeline.
+    pub timeline_start: f64,
+}
+
+/// Which clock the timestamps of a transcript follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptTimebase {
+    /// Seconds into the source file of each range.
+    #[default]
+    Source,
+    /// Seconds on the timeline, from each range's `timeline_start`.
+    Timeline,
+}
+
+/// Moves timestamps of a transcript of `ranges`, which count from the start
+/// of the extracted audio, onto `timebase`. Each word and segment moves with
+/// the range it starts in.
+pub fn rebase(response: &mut TranscriptionResponse, ranges: &[SourceRange], timebase: TranscriptTimebase) {
+    // (start in the extracted audio, shift to apply) for every range
+    let mut offset = 0.0;
+    let shifts: Vec<(f64, f64)> = ranges
+        .iter()
+        .map(|range| {
+            let base = match timebase {
+                TranscriptTimebase::Source => range.start,
+                TranscriptTimebase::Timeline => range.timeline_start,
+            };
+            let shift = (offset, base - offset);
+            offset += range.end.map(|end| end - range.start).unwrap_or(f64::INFINITY);
+            shift
+        })
+        .collect();
+    let shift_at = |time: f64| {
+        shifts
+            .iter()
+            .rev()
+            .find(|(from, _)| time >= *from)
+            .map(|(_, shift)| *shift)
+            .unwrap_or(0.0)
+    };
+
+    for word in response.words.iter_mut().flatten() {
+        let shift = shift_at(word.start);
+        word.start += shift;
+        word.end += shift;
+    }
+    for segment in response.segments.iter_mut().flatten() {
+        let shift = shift_at(segment.start);
+        segment.start += shift;
+        segment.end += shift;
+        // Whisper counts `seek` in 10 ms frames
+        segment.seek += shift * 100.0;
+    }
+}
+
+/// Audio to transcribe, already extracted from the video.
+pub struct TranscriptionRequest<'a> {
+    pub audio_path: &'a Path,
+    /// ISO 639-1 code, or `None` to let the model detect it.
+    pub language: Option<&'a str>,
+    /// Called with 0-100 by providers that can tell how far along they are.
+    pub progress: &'a (dyn Fn(f64) + Send + Sync),
+    /// Providers stop early once this job is cancelled.
+    pub job: &'a Job,
+}
+
+/// A service or program that turns audio into a timestamped transcript.
+pub trait TranscriptionProvider {
+    /// Shown in logs, e.g. `OpenAI Whisper API`.
+    fn name(&self) -> &str;
+
+    fn audio_format(&self) -> AudioFormat {
+        AudioFormat::Mp3
+    }
+
+    /// Largest audio file accepted in one request, if there is a limit.
+    /// Larger files are split, see [`super::chunking`].
+    fn max_upload_bytes(&self) -> Option<u64> {
+        None
+    }
+
+    fn transcribe(
+        &self,
+        request: &TranscriptionRequest<'_>,
+    ) -> impl Future<Output = Result<TranscriptionResponse, NolanForgeError>> + Send;
+}
+
+/// The provider chosen in [`Config`].
+pub enum Provider {
+    /// The OpenAI Whisper API or a server with the same API.
+    OpenAi(OpenAiProvider),
+    /// whisper.cpp running on this machine.
+    Local(WhisperCppProvider),
+}
+
+impl Provider {
+    pub fn from_config(config: &Config) -> Result<Self, NolanForgeError> {
+        let settings = &config.transcription;
+        let provider = match settings.backend {
+            TranscriptionBackend::OpenAi => {
+                let api_key = config.openai_api_key.clone().ok_or(NolanForgeError::ApiKeyMissing)?;
+                let model = settings.model.clone().unwrap_or_else(|| config.openai_model.clone());
+                Provider::OpenAi(OpenAiProvider::whisper_api(api_key, model))
+            }
+            TranscriptionBackend::OpenAiCompatible => {
+                let base_url = settings.base_url.clone().filter(|url| !url.trim().is_empty()).ok_or_else(|| {
+                    NolanForgeError::Config("Set the URL of your transcription server in Settings".to_string())
+                })?;
+                let model = settings.model.clone().unwrap_or_else(|| config.openai_model.clone());
+                Provider::OpenAi(OpenAiProvider::compatible(base_url, settings.api_key.clone(), model))
+            }
+            TranscriptionBackend::Local => {
+                let binary = settings.whisper_binary.clone().ok_or_else(|| {
+                    NolanForgeError::Config("Choose the whisper.cpp executable in Settings".to_string())
+                })?;
+                let model = settings.whisper_model.clone().ok_or_else(|| {
+                    NolanForgeError::Config("Choose a Whisper model file in Settings".to_string())
+                })?;
+                Provider::Local(WhisperCppProvider::new(binary.into(), model.into())?)
+            }
+        };
+        println!("🔍 Debug: Transcribing with {}", provider.name());
+        Ok(provider)
+    }
+}
+
+impl TranscriptionProvider for Provider {
+    fn name(&self) -> &str {
+        match self {
+            Provider::OpenAi(provider) => provider.name(),
+            Provider::Local(provider) => provider.name(),
+        }
+    }
+
+    fn audio_format(&self) -> AudioFormat {
+        match self {
+            Provider::OpenAi(provider) => provider.audio_format(),
+            Provider::Local(provider) => provider.audio_format(),
+        }
+    }
+
+    fn max_upload_bytes(&self) -> Option<u64> {
+        match self {
+            Provider::OpenAi(provider) => provider.max_upload_bytes(),
+            Provider::Local(provider) => provider.max_upload_bytes(),
+        }
+    }
+
+    async fn transcribe(&self, request: &TranscriptionRequest<'_>) -> Result<TranscriptionResponse, NolanForgeError> {
+        match self {
+            Provider::OpenAi(provider) => provider.transcribe(request).await,
+            Provider::Local(provider) => provider.transcribe(request).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: f64, end: Option<f64>, timeline_start: f64) -> SourceRange {
+        SourceRange {
+            input_path: PathBuf::from("clip.mp4"),
+            start,
+            end,
+            timeline_start,
+        }
+    }
+
+    /// One word and one segment at each of `times` in the extracted audio.
+    fn transcript(times: &[f64]) -> TranscriptionResponse {
+        TranscriptionResponse {
+            text: String::new(),
+            language: None,
+            duration: None,
+            words: Some(
+                times
+                    .iter()
+                    .map(|&start| Word {
+                        word: "word".to_string(),
+                        start,
+                        end: start + 0.5,
+                    })
+                    .collect(),
+            ),
+            segments: Some(
+                times
+                    .iter()
+                    .enumerate()
+                    .map(|(id, &start)| Segment {
+                        id: id as i32,
+                        seek: start * 100.0,
+                        start,
+                        end: start + 0.5,
+                        text: "word".to_string(),
+                        tokens: Vec::new(),
+                        temperature: 0.0,
+                        avg_logprob: 0.0,
+                        compression_ratio: 0.0,
+                        no_speech_prob: 0.0,
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    fn starts(response: &TranscriptionResponse) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+        let words = response.words.as_ref().unwrap().iter().map(|w| w.start).collect();
+        let segments = response.segments.as_ref().unwrap();
+        (
+            words,
+            segments.iter().map(|s| s.start).collect(),
+            segments.iter().map(|s| s.seek).collect(),
+        )
+    }
+
+    #[test]
+    fn each_range_moves_back_to_its_source() {
+        // 10-15s of the source, then 40-50s: 5s and 10s of extracted audio
+        let ranges = [range(10.0, Some(15.0), 0.0), range(40.0, Some(50.0), 20.0)];
+
+        let mut response = transcript(&[2.0, 7.0]);
+        rebase(&mut response, &ranges, TranscriptTimebase::Source);
+        assert_eq!(starts(&response), (vec![12.0, 42.0], vec![12.0, 42.0], vec![1200.0, 4200.0]));
+        assert_eq!(response.words.as_ref().unwrap()[1].end, 42.5);
+
+        let mut response = transcript(&[2.0, 7.0]);
+        rebase(&mut response, &ranges, TranscriptTimebase::Timeline);
+        assert_eq!(starts(&response), (vec![2.0, 22.0], vec![2.0, 22.0], vec![200.0, 2200.0]));
+    }
+
+    #[test]
+    fn open_ended_range_runs_to_the_end() {
+        let ranges = [range(30.0, None, 100.0)];
+
+        let mut response = transcript(&[0.0, 500.0]);
+        rebase(&mut response, &ranges, TranscriptTimebase::Source);
+        assert_eq!(starts(&response), (vec![30.0, 530.0], vec![30.0, 530.0], vec![3000.0, 53000.0]));
+
+        let mut response = transcript(&[500.0]);
+        rebase(&mut response, &ranges, TranscriptTimebase::Timeline);
+        assert_eq!(starts(&response), (vec![600.0], vec![600.0], vec![60000.0]));
+    }
+}
@@ -0,0 +1,437 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use super::encoding::EncodingProfile;
+use super::ffmpeg::{ffmpeg_command, run_ffmpeg, ProgressReporter};
+use super::probe;
+use super::toolchain::Ffmpeg;
+use super::workspace::Workspace;
+use crate::error::NolanForgeError;
+
+/// Cut points closer than this to a keyframe count as on the keyframe.
+const FRAME_TOLERANCE: f64 = 0.001;
+
+/// How `export_trimmed_video` cuts the source.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrimMode {
+    /// Stream copy. Fast and lossless, but the start snaps back to the
+    /// previous keyframe.
+    #[default]
+    Copy,
+    /// Full re-encode. Frame accurate but slow.
+    Exact,
+    /// Re-encodes from the cut point up to the next keyframe and stream
+    /// copies the rest.
+    Smart,
+}
+
+impl TrimMode {
+    /// Relative amount of media FFmpeg processes in this mode, used to weight
+    /// progress reporting.
+    pub fn work_factor(self) -> f64 {
+        match self {
+            TrimMode::Copy | TrimMode::Exact => 1.0,
+            // Head and tail, joining, then muxing the audio back in
+            TrimMode::Smart => 3.0,
+        }
+    }
+}
+
+/// In and out points a trim actually achieved, next to the requested ones.
+#[derive(Debug, Clone, Serialize)]
+pub struct TrimResult {
+    pub message: String,
+    pub output_path: String,
+    /// The mode that was used. Smart trims fall back to exact when the source
+    /// cannot be stream copied around the cut.
+    pub mode: TrimMode,
+    pub requested_start: f64,
+    pub requested_end: f64,
+    pub actual_start: f64,
+    pub actual_end: f64,
+}
+
+/// Trims `input` to `start..end`.
+///
+/// Copy and smart trims keep the source video stream, so only the profile's
+/// container settings (and, for smart trims, its audio settings) apply.
+#[allow(clippy::too_many_arguments)]
+pub async fn trim_video(
+    ffmpeg: &Ffmpeg,
+    reporter: &mut ProgressReporter<'_>,
+    workspace: &Workspace,
+    input: &Path,
+    output: &Path,
+    start: f64,
+    end: f64,
+    mode: TrimMode,
+    profile: &EncodingProfile,
+) -> Result<TrimResult, NolanForgeError> {
+    let (used_mode, actual_start) = match mode {
+        TrimMode::Copy => (mode, copy_trim(ffmpeg, reporter, input, output, start, end, profile).await?),
+        TrimMode::Exact => (mode, exact_trim(ffmpeg, reporter, input, output, start, end, profile).await?),
+        TrimMode::Smart => smart_trim(ffmpeg, reporter, workspace, input, output, start, end, profile).await?,
+    };
+
+    // Measure the result instead of trusting the requested range
+    let output_duration = probe::media_duration(ffmpeg, output).await?;
+
+    Ok(TrimResult {
+        message: format!("Video exported successfully to: {}", output.display()),
+        output_path: output.to_string_lossy().to_string(),
+        mode: used_mode,
+        requested_start: start,
+        requested_end: end,
+        actual_start,
+        actual_end: actual_start + output_duration,
+    })
+}
+
+/// Stream copies the range and returns the keyframe the output starts at.
+async fn copy_trim(
+    ffmpeg: &Ffmpeg,
+    reporter: &mut ProgressReporter<'_>,
+    input: &Path,
+    output: &Path,
+    start: f64,
+    end: f64,
+    profile: &EncodingProfile,
+) -> Result<f64, NolanForgeError> {
+    let duration = end - start;
+
+    let mut cmd = ffmpeg_command(ffmpeg);
+    cmd.arg("-ss")
+        .arg(start.to_string())
+        .arg("-i")
+        .arg(input)
+        .arg("-t")
+        .arg(duration.to_string())
+        .arg("-c")
+        .arg("copy") // Use stream copy for fast encoding
+        .arg("-avoid_negative_ts")
+        .arg("make_zero")
+        .args(profile.container_args())
+        .arg("-y") // Overwrite output file
+        .arg(output);
+
+    run_ffmpeg(cmd, reporter, "Trimming video", duration).await?;
+
+    // Audio-only sources have no keyframes to snap to
+    Ok(probe::keyframe_at_or_before(ffmpeg, input, start).await?.unwrap_or(start))
+}
+
+/// Re-encodes the range so it starts exactly at `start`.
+async fn exact_trim(
+    ffmpeg: &Ffmpeg,
+    reporter: &mut ProgressReporter<'_>,
+    input: &Path,
+    output: &Path,
+    start: f64,
+    end: f64,
+    profile: &EncodingProfile,
+) -> Result<f64, NolanForgeError> {
+    let duration = end - start;
+
+    // With -ss before -i FFmpeg decodes from the previous keyframe and drops
+    // frames up to the cut, so the output starts exactly at `start`
+    let mut cmd = ffmpeg_command(ffmpeg);
+    cmd.arg("-ss")
+        .arg(start.to_string())
+        .arg("-i")
+        .arg(input)
+        .arg("-t")
+        .arg(duration.to_string())
+        .args(profile.output_args())
+        .arg("-y")
+        .arg(output);
+
+    run_ffmpeg(cmd, reporter, "Re-encoding trimmed video", duration).await?;
+
+    Ok(start)
+}
+
+/// Re-encodes only the part before the first keyframe inside the range and
+/// stream copies the rest.
+#[allow(clippy::too_many_arguments)]
+async fn smart_trim(
+    ffmpeg: &Ffmpeg,
+    reporter: &mut ProgressReporter<'_>,
+    workspace: &Workspace,
+    input: &Path,
+    output: &Path,
+    start: f64,
+    end: f64,
+    profile: &EncodingProfile,
+) -> Result<(TrimMode, f64), NolanForgeError> {
+    let info = probe::basic_info(ffmpeg, input).await?;
+    let keyframe = probe::keyframes_between(ffmpeg, input, start, end)
+        .await?
+        .into_iter()
+        .find(|k| *k < end - FRAME_TOLERANCE);
+
+    let Some(keyframe) = keyframe else {
+        // No keyframe inside the range, so everything needs re-encoding
+        println!("🔍 Debug: No keyframe between {}s and {}s, using exact trim", start, end);
+        return Ok((TrimMode::Exact, exact_trim(ffmpeg, reporter, input, output, start, end, profile).await?));
+    };
+
+    if keyframe - start <= FRAME_TOLERANCE {
+        // The cut already lands on a keyframe, a plain copy is exact
+        return Ok((TrimMode::Smart, copy_trim(ffmpeg, reporter, input, output, start, end, profile).await?));
+    }
+
+    // The re-encoded head has to be joinable with copied H.264 packets
+    if info.video_codec.as_deref() != Some("h264") {
+        println!(
+            "🔍 Debug: Smart trim needs H.264 source, got {:?}; using exact trim",
+            info.video_codec
+        );
+        return Ok((TrimMode::Exact, exact_trim(ffmpeg, reporter, input, output, start, end, profile).await?));
+    }
+
+    // The head brings its own parameter sets, so the output has to let
+    // decoders pick them up in-band
+    let Some(video_tag) = inband_parameter_set_tag(&profile.container) else {
+        println!(
+            "🔍 Debug: {} cannot carry a second set of H.264 parameters; using exact trim",
+            profile.container
+        );
+        return Ok((TrimMode::Exact, exact_trim(ffmpeg, reporter, input, output, start, end, profile).await?));
+    };
+
+    // libx264 has to produce a stream the source's decoder setup accepts
+    let Some(head_settings) = HeadSettings::matching(&info) else {
+        println!(
+            "🔍 Debug: Cannot match H.264 profile {:?} at level {:?} ({:?}); using exact trim",
+            info.video_profile, info.video_level, info.pixel_format
+        );
+        return Ok((TrimMode::Exact, exact_trim(ffmpeg, reporter, input, output, start, end, profile).await?));
+    };
+
+    // MPEG-TS carries parameter sets in-band, so the re-encoded head and the
+    // copied tail keep their own SPS/PPS when joined
+    let head_path = workspace.join("smart_head.ts");
+    let tail_path = workspace.join("smart_tail.ts");
+    let joined_path = workspace.join("smart_joined.ts");
+    let segments = SmartSegments::new(start, end, keyframe, info.frame_rate);
+
+    // Step 1: Re-encode from the cut point up to the keyframe
+    let mut cmd = ffmpeg_command(ffmpeg);
+    cmd.arg("-ss")
+        .arg(start.to_string())
+        .arg("-i")
+        .arg(input)
+        .arg("-t")
+        .arg(segments.head_duration.to_string())
+        .arg("-an")
+        .arg("-c:v")
+        .arg("libx264")
+        .arg("-preset")
+        .arg("fast")
+        .arg("-crf")
+        .arg("18")
+        .args(head_settings.args())
+        .arg("-bsf:v")
+        .arg("h264_mp4toannexb")
+        .arg("-f")
+        .arg("mpegts")
+        .arg("-y")
+        .arg(&head_path);
+
+    run_ffmpeg(cmd, reporter, "Re-encoding up to first keyframe", segments.head_duration).await?;
+
+    // Step 2: Copy everything from the keyframe on. Seeking inside the
+    // keyframe's own frame makes the demuxer snap back to exactly that
+    // keyframe, never to the one before it
+    let mut cmd = ffmpeg_command(ffmpeg);
+    cmd.arg("-noaccurate_seek")
+        .arg("-ss")
+        .arg(segments.tail_seek.to_string())
+        .arg("-i")
+        .arg(input)
+        .arg("-t")
+        .arg(segments.tail_duration.to_string())
+        .arg("-an")
+        .arg("-c:v")
+        .arg("copy")
+        .arg("-bsf:v")
+        .arg("h264_mp4toannexb")
+        .arg("-f")
+        .arg("mpegts")
+        .arg("-y")
+        .arg(&tail_path);
+
+    run_ffmpeg(cmd, reporter, "Copying from first keyframe", segments.tail_duration).await?;
+
+    // Step 3: Join head and tail
+    let concat_file = workspace.join("smart_concat_list.txt");
+    let concat_content = format!(
+        "file '{}'\nfile '{}'\n",
+        head_path.display(),
+        tail_path.display()
+    );
+    fs::write(&concat_file, concat_content)
+        .map_err(|e| NolanForgeError::io("Failed to write concat file", e))?;
+
+    let mut cmd = ffmpeg_command(ffmpeg);
+    cmd.arg("-f")
+        .arg("concat")
+        .arg("-safe")
+        .arg("0")
+        .arg("-i")
+        .arg(&concat_file)
+        .arg("-c")
+        .arg("copy")
+        .arg("-f")
+        .arg("mpegts")
+        .arg("-y")
+        .arg(&joined_path);
+
+    run_ffmpeg(cmd, reporter, "Joining trimmed video", end - start).await?;
+
+    // Step 4: Add the audio of the exact range back in
+    let mut cmd = ffmpeg_command(ffmpeg);
+    cmd.arg("-i")
+        .arg(&joined_path)
+        .arg("-ss")
+        .arg(start.to_string())
+        .arg("-i")
+        .arg(input)
+        .arg("-t")
+        .arg((end - start).to_string())
+        .arg("-map")
+        .arg("0:v")
+        .arg("-map")
+        .arg("1:a?")
+        .arg("-c:v")
+        .arg("copy")
+        .arg("-tag:v")
+        .arg(video_tag)
+        .args(profile.audio_args())
+        .args(profile.container_args())
+        .arg("-y")
+        .arg(output);
+
+    run_ffmpeg(cmd, reporter, "Adding audio", end - start).await?;
+
+    Ok((TrimMode::Smart, start))
+}
+
+/// Codec tag that tells decoders to read H.264 parameter sets from the
+/// stream, or `None` if the container only stores one set up front.
+fn inband_parameter_set_tag(container: &str) -> Option<&'static str> {
+    match container {
+        "mp4" | "mov" => Some("avc3"),
+        _ => None,
+    }
+}
+
+/// libx264 settings for a head that matches the source's profile, level
+/// and pixel format.
+#[derive(Debug, PartialEq)]
+struct HeadSettings {
+    profile: &'static str,
+    level: String,
+    pixel_format: String,
+}
+
+impl HeadSettings {
+    fn matching(info: &probe::BasicInfo) -> Option<HeadSettings> {
+        let profile = match info.video_profile.as_deref()? {
+            "Baseline" | "Constrained Baseline" => "baseline",
+            "Main" => "main",
+            "High" => "high",
+            "High 10" => "high10",
+            "High 4:2:2" => "high422",
+            "High 4:4:4 Predictive" => "high444",
+            _ => return None,
+        };
+        let level = info.video_level?;
+        Some(HeadSettings {
+            profile,
+            // ffprobe reports level 1b as 9
+            level: if level == 9 { "1b".to_string() } else { format!("{}.{}", level / 10, level % 10) },
+            pixel_format: info.pixel_format.clone()?,
+        })
+    }
+
+    fn args(&self) -> Vec<String> {
+        vec![
+            "-profile:v".to_string(),
+            self.profile.to_string(),
+            "-level:v".to_string(),
+            self.level.clone(),
+            "-pix_fmt".to_string(),
+            self.pixel_format.clone(),
+            // Parameter sets before every keyframe, as the copied tail has
+            "-x264-params".to_string(),
+            "repeat-headers=1".to_string(),
+        ]
+    }
+}
+
+/// Where a smart trim splits `start..end` around the first keyframe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SmartSegments {
+    head_duration: f64,
+    /// Input seek for the copied tail: inside the keyframe's frame, so the
+    /// demuxer snaps back to the keyframe itself.
+    tail_seek: f64,
+    tail_duration: f64,
+}
+
+impl SmartSegments {
+    fn new(start: f64, end: f64, keyframe: f64, frame_rate: Option<f64>) -> SmartSegments {
+        let frame = frame_rate.filter(|rate| *rate > 0.0).map_or(1.0 / 60.0, |rate| 1.0 / rate);
+        let tail_seek = keyframe + frame / 2.0;
+        SmartSegments {
+            head_duration: keyframe - start,
+            tail_seek,
+            tail_duration: end - tail_seek,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn smart_segments_split_at_the_keyframe() {
+        let segments = SmartSegments::new(3.2, 12.0, 4.0, Some(25.0));
+
+        assert!((segments.head_duration - 0.8).abs() < 1e-9);
+        // The tail seek stays inside the keyframe's frame
+        assert!(segments.tail_seek > 4.0);
+        assert!(segments.tail_seek < 4.0 + 1.0 / 25.0);
+        // The copied tail starts at the keyframe and still ends at `end`
+        assert!((segments.tail_seek + segments.tail_duration - 12.0).abs() < 1e-9);
+        assert!((segments.head_duration + (segments.tail_seek - 4.0) + segments.tail_duration - 8.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn head_settings_follow_the_source() {
+        let mut info = probe::BasicInfo {
+            duration: Some(10.0),
+            start_time: 0.0,
+            video_codec: Some("h264".to_string()),
+            video_profile: Some("High".to_string()),
+            video_level: Some(41),
+            pixel_format: Some("yuv420p".to_string()),
+            width: Some(1920),
+            height: Some(1080),
+            rotation: 0,
+            frame_rate: Some(30.0),
+            has_audio: true,
+        };
+        let settings = HeadSettings::matching(&info).expect("High profile is supported");
+        assert_eq!(settings.profile, "high");
+        assert_eq!(settings.level, "4.1");
+
+        info.video_profile = Some("High 10 Intra".to_string());
+        assert_eq!(HeadSettings::matching(&info), None);
+        assert_eq!(inband_parameter_set_tag("mkv"), None);
+    }
+}
@@ -0,0 +1,414 @@
+use base64::Engine;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::io::AsyncReadExt;
+
+use super::cache;
+use super::files;
+use super::probe;
+use super::toolchain::Ffmpeg;
+use crate::error::NolanForgeError;
+
+/// Audio is decoded to mono at this rate before peaks are taken. Plenty for
+/// drawing and keeps decoding long files quick.
+const PEAK_SAMPLE_RATE: u32 = 16000;
+
+/// Samples per peak at the finest level, i.e. 500 peaks per second.
+const BASE_SAMPLES_PER_PEAK: u32 = 32;
+
+/// Coarser levels are added until one has no more than this many peaks.
+const MIN_LEVEL_PEAKS: usize = 64;
+
+/// Identifies the cache file layout; bump `CACHE_VERSION` when it changes
+/// so old entries are recomputed instead of misread.
+const CACHE_MAGIC: &[u8; 4] = b"NFWF";
+const CACHE_VERSION: u8 = 1;
+
+/// Min/max peaks of a file's audio at several resolutions.
+#[derive(Debug, Clone, Serialize)]
+pub struct WaveformPeaks {
+    /// Length of the decoded audio in seconds.
+    pub duration: f64,
+    pub sample_rate: u32,
+    /// Finest level first. Empty if the file has no audio.
+    pub levels: Vec<PeakLevel>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PeakLevel {
+    pub samples_per_peak: u32,
+    pub peaks_per_second: f64,
+    pub count: usize,
+    /// Base64 of interleaved `min, max` pairs as signed bytes, full scale
+    /// being -128..=127.
+    pub data: String,
+}
+
+/// One resolution of peaks, `peaks` holding interleaved min/max pairs.
+#[derive(Debug, Clone)]
+struct Level {
+    samples_per_peak: u32,
+    peaks: Vec<i8>,
+}
+
+impl Level {
+    fn count(&self) -> usize {
+        self.peaks.len() / 2
+    }
+
+    /// The next level down, each peak covering two of this level's.
+    fn halve(&self) -> Level {
+        let peaks = self
+            .peaks
+            .chunks(4)
+            .flat_map(|pair| match *pair {
+                [min_a, max_a, min_b, max_b] => [min_a.min(min_b), max_a.max(max_b)],
+                [min, max] => [min, max],
+                _ => unreachable!("peaks come in min/max pairs"),
+            })
+            .collect();
+        Level {
+            samples_per_peak: self.samples_per_peak * 2,
+            peaks,
+        }
+    }
+}
+
+/// Everything stored in the cache for one source.
+struct Peaks {
+    total_samples: u64,
+    levels: Vec<Level>,
+}
+
+/// Returns the waveform peaks of `path`, decoding the audio on first use.
+///
+/// With `peaks_per_second` only the coarsest level at least that detailed is
+/// returned, which is all a timeline needs at a given zoom.
+pub async fn waveform_peaks(ffmpeg: &Ffmpeg, path: &Path, peaks_per_second: Option<f64>) -> Result<WaveformPeaks, NolanForgeError> {
+    if let Some(pps) = peaks_per_second {
+        if !pps.is_finite() || pps <= 0.0 {
+            return Err(NolanForgeError::InvalidInput(format!("Invalid peak resolution: {}", pps)));
+        }
+    }
+
+    let fingerprint = cache::source_fingerprint(path)?;
+    let cache_path = cache::cache_dir("waveforms")?.join(format!("{}.peaks", fingerprint));
+
+    let peaks = match fs::read(&cache_path).ok().and_then(|bytes| decode_cache(&bytes)) {
+        Some(peaks) => peaks,
+        None => {
+            let peaks = compute_peaks(ffmpeg, path).await?;
+            files::write_atomic(&cache_path, &encode_cache(&peaks))?;
+            peaks
+        }
+    };
+
+    let levels: Vec<&Level> = match peaks_per_second {
+        None => peaks.levels.iter().collect(),
+        Some(pps) => {
+            // Levels run fine to coarse, so the last one that is detailed
+            // enough is the smallest to send; fall back to the finest
+            let level = peaks
+                .levels
+                .iter()
+                .rfind(|level| level_rate(level) >= pps)
+                .or(peaks.levels.first());
+            level.into_iter().collect()
+        }
+    };
+
+    Ok(WaveformPeaks {
+        duration: peaks.total_samples as f64 / PEAK_SAMPLE_RATE as f64,
+        sample_rate: PEAK_SAMPLE_RATE,
+        levels: levels
+            .into_iter()
+            .map(|level| PeakLevel {
+                samples_per_peak: level.samples_per_peak,
+                peaks_per_second: level_rate(level),
+                count: level.count(),
+                data: base64::engine::general_purpose::STANDARD
+                    .encode(level.peaks.iter().map(|p| *p as u8).collect::<Vec<u8>>()),
+            })
+            .collect(),
+    })
+}
+
+fn level_rate(level: &Level) -> f64 {
+    PEAK_SAMPLE_RATE as f64 / level.samples_per_peak as f64
+}
+
+/// Decodes the first audio stream to mono PCM and builds every level.
+async fn compute_peaks(ffmpeg: &Ffmpeg, path: &Path) -> Result<Peaks, NolanForgeError> {
+    if !probe::basic_info(ffmpeg, path).await?.has_audio {
+        return Ok(Peaks {
+            total_samples: 0,
+            levels: Vec::new(),
+        });
+    }
+
+    let mut child = ffmpeg
+        .command()
+        .arg("-hide_banner")
+        .arg("-v")
+        .arg("error")
+        .arg("-i")
+        .arg(path)
+        .arg("-vn")
+        .arg("-ac")
+        .arg("1")
+        .arg("-ar")
+        .arg(PEAK_SAMPLE_RATE.to_string())
+        .arg("-c:a")
+        .arg("pcm_s16le")
+        .arg("-f")
+        .arg("s16le")
+        .arg("pipe:1")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| NolanForgeError::launch("FFmpeg", e))?;
+
+    // Drain stderr concurrently so FFmpeg never blocks on a full pipe
+    let mut stderr = child.stderr.take().expect("stderr is piped");
+    let stderr_task = tokio::spawn(async move {
+        let mut buf = String::new();
+        let _ = stderr.read_to_string(&mut buf).await;
+        buf
+    });
+
+    // The PCM is folded into peaks as it arrives, so long files never have
+    // to fit in memory
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut leftover: Option<u8> = None;
+    let mut base = Level {
+        samples_per_peak: BASE_SAMPLES_PER_PEAK,
+        peaks: Vec::new(),
+    };
+    let mut bucket = Bucket::default();
+    let mut total_samples: u64 = 0;
+
+    loop {
+        let read = stdout
+            .read(&mut buf)
+            .await
+            .map_err(|e| NolanForgeError::io("Failed to read decoded audio", e))?;
+        if read == 0 {
+            break;
+        }
+
+        let mut bytes = &buf[..read];
+        // A sample may straddle two reads
+        if let Some(low) = leftover.take() {
+            bucket.push(i16::from_le_bytes([low, bytes[0]]), &mut base);
+            total_samples += 1;
+            bytes = &bytes[1..];
+        }
+        let mut samples = bytes.chunks_exact(2);
+        for sample in &mut samples {
+            bucket.push(i16::from_le_bytes([sample[0], sample[1]]), &mut base);
+            total_samples += 1;
+        }
+        leftover = samples.remainder().first().copied();
+    }
+    bucket.flush(&mut base);
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| NolanForgeError::io("Failed to wait for FFmpeg", e))?;
+    let stderr_output = stderr_task.await.unwrap_or_default();
+    if !status.success() {
+        return Err(NolanForgeError::ffmpeg(
+            format!("Failed to decode audio of {}", path.display()),
+            stderr_output,
+        ));
+    }
+
+    let levels = build_levels(base);
+    println!(
+        "🔍 Debug: Waveform of {}: {} samples, {} levels",
+        path.display(),
+        total_samples,
+        levels.len()
+    );
+    Ok(Peaks { total_samples, levels })
+}
+
+/// `base` followed by ever coarser levels, down to one of at most
+/// `MIN_LEVEL_PEAKS` peaks.
+fn build_levels(base: Level) -> Vec<Level> {
+    let mut levels = vec![base];
+    while levels.last().is_some_and(|level| level.count() > MIN_LEVEL_PEAKS) {
+        let next = levels.last().unwrap().halve();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Min and max of the samples seen for the peak being built.
+#[derive(Default)]
+struct Bucket {
+    min: i16,
+    max: i16,
+    len: u32,
+}
+
+impl Bucket {
+    fn push(&mut self, sample: i16, level: &mut Level) {
+        if self.len == 0 {
+            self.min = sample;
+            self.max = sample;
+        } else {
+            self.min = self.min.min(sample);
+            self.max = self.max.max(sample);
+        }
+        self.len += 1;
+        if self.len == level.samples_per_peak {
+            self.flush(level);
+        }
+    }
+
+    fn flush(&mut self, level: &mut Level) {
+        if self.len > 0 {
+            // Keep the high byte, which is plenty for a lane a few dozen
+            // pixels tall
+            level.peaks.push((self.min >> 8) as i8);
+            level.peaks.push((self.max >> 8) as i8);
+            self.len = 0;
+        }
+    }
+}
+
+/// Cache layout, little endian: magic, version, total sample count, level
+/// count, then per level its samples per peak, peak count and peak bytes.
+fn encode_cache(peaks: &Peaks) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(CACHE_MAGIC);
+    bytes.push(CACHE_VERSION);
+    bytes.extend_from_slice(&peaks.total_samples.to_le_bytes());
+    bytes.extend_from_slice(&(peaks.levels.len() as u32).to_le_bytes());
+    for level in &peaks.levels {
+        bytes.extend_from_slice(&level.samples_per_peak.to_le_bytes());
+        bytes.extend_from_slice(&(level.count() as u32).to_le_bytes());
+        bytes.extend(level.peaks.iter().map(|p| *p as u8));
+    }
+    bytes
+}
+
+/// Reads a cache file written by [`encode_cache`], `None` if it is from
+/// another version or damaged.
+fn decode_cache(bytes: &[u8]) -> Option<Peaks> {
+    let mut reader = bytes;
+    let mut take = |len: usize| -> Option<&[u8]> {
+        if reader.len() < len {
+            return None;
+        }
+        let (head, rest) = reader.split_at(len);
+        reader = rest;
+        Some(head)
+    };
+
+    if take(4)? != CACHE_MAGIC || take(1)?[0] != CACHE_VERSION {
+        return None;
+    }
+    let total_samples = u64::from_le_bytes(take(8)?.try_into().ok()?);
+    let level_count = u32::from_le_bytes(take(4)?.try_into().ok()?);
+
+    let mut levels = Vec::new();
+    for _ in 0..level_count {
+        let samples_per_peak = u32::from_le_bytes(take(4)?.try_into().ok()?);
+        let count = u32::from_le_bytes(take(4)?.try_into().ok()?) as usize;
+        let peaks = take(count * 2)?.iter().map(|b| *b as i8).collect();
+        levels.push(Level {
+            samples_per_peak,
+            peaks,
+        });
+    }
+
+    Some(Peaks { total_samples, levels })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base_level(samples: &[i16]) -> Level {
+        let mut level = Level {
+            samples_per_peak: BASE_SAMPLES_PER_PEAK,
+            peaks: Vec::new(),
+        };
+        let mut bucket = Bucket::default();
+        for &sample in samples {
+            bucket.push(sample, &mut level);
+        }
+        bucket.flush(&mut level);
+        level
+    }
+
+    #[test]
+    fn base_level_keeps_min_and_max_of_each_bucket() {
+        let per_peak = BASE_SAMPLES_PER_PEAK as usize;
+        let mut samples = vec![0i16; per_peak * 2 + 3];
+        samples[1] = 12800;
+        samples[5] = -25600;
+        samples[per_peak] = i16::MAX;
+        samples[per_peak + 1] = i16::MIN;
+        // The last, partial bucket is flushed too
+        samples[per_peak * 2] = 512;
+
+        let level = base_level(&samples);
+        assert_eq!(level.count(), 3);
+        assert_eq!(level.peaks, vec![-100, 50, -128, 127, 0, 2]);
+    }
+
+    #[test]
+    fn each_level_halves_the_one_before() {
+        // Peak i of the base level swings between -(i % 100) and i % 100
+        let peaks: Vec<i8> = (0..200).map(|i| (i % 100) as i8).flat_map(|i| [-i, i]).collect();
+        let levels = build_levels(Level {
+            samples_per_peak: BASE_SAMPLES_PER_PEAK,
+            peaks,
+        });
+
+        let counts: Vec<usize> = levels.iter().map(Level::count).collect();
+        assert_eq!(counts, vec![200, 100, 50]);
+        let spans: Vec<u32> = levels.iter().map(|level| level.samples_per_peak).collect();
+        assert_eq!(spans, vec![32, 64, 128]);
+
+        assert_eq!(&levels[1].peaks[..4], &[-1, 1, -3, 3]);
+        assert_eq!(&levels[2].peaks[..4], &[-3, 3, -7, 7]);
+        assert_eq!(&levels[2].peaks[96..], &[-99, 99]);
+    }
+
+    #[test]
+    fn odd_peak_carries_over_when_halving() {
+        let level = Level {
+            samples_per_peak: 64,
+            peaks: vec![-1, 1, -5, 2, -3, 4],
+        };
+        let half = level.halve();
+        assert_eq!(half.samples_per_peak, 128);
+        assert_eq!(half.peaks, vec![-5, 2, -3, 4]);
+    }
+
+    #[test]
+    fn cache_round_trip() {
+        let peaks = Peaks {
+            total_samples: 6400,
+            levels: build_levels(base_level(&[300, -300].repeat(3200))),
+        };
+        let decoded = decode_cache(&encode_cache(&peaks)).unwrap();
+        assert_eq!(decoded.total_samples, 6400);
+        assert_eq!(decoded.levels.len(), peaks.levels.len());
+        for (a, b) in decoded.levels.iter().zip(&peaks.levels) {
+            assert_eq!(a.samples_per_peak, b.samples_per_peak);
+            assert_eq!(a.peaks, b.peaks);
+        }
+        assert!(decode_cache(&encode_cache(&peaks)[..20]).is_none());
+    }
+}
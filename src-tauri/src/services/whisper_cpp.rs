@@ -0,0 +1,320 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+
+use super::transcription::{
+    AudioFormat, Segment, TranscriptionProvider, TranscriptionRequest, TranscriptionResponse, Word,
+};
+use crate::error::NolanForgeError;
+
+/// Transcribes on this machine by running a whisper.cpp executable
+/// (`whisper-cli`, or `main` in older releases) with a ggml model file.
+/// Nothing leaves the computer.
+pub struct WhisperCppProvider {
+    name: String,
+    binary: PathBuf,
+    model: PathBuf,
+}
+
+impl WhisperCppProvider {
+    pub fn new(binary: PathBuf, model: PathBuf) -> Result<Self, NolanForgeError> {
+        if !binary.is_file() {
+            return Err(NolanForgeError::Config(format!(
+                "whisper.cpp executable not found at {}. Choose it in Settings",
+                binary.display()
+            )));
+        }
+        if !model.is_file() {
+            return Err(NolanForgeError::WhisperModelMissing {
+                path: model.display().to_string(),
+            });
+        }
+
+        let model_name = model.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        Ok(WhisperCppProvider {
+            name: format!("whisper.cpp ({})", model_name),
+            binary,
+            model,
+        })
+    }
+}
+
+impl TranscriptionProvider for WhisperCppProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn audio_format(&self) -> AudioFormat {
+        AudioFormat::Wav
+    }
+
+    async fn transcribe(&self, request: &TranscriptionRequest<'_>) -> Result<TranscriptionResponse, NolanForgeError> {
+        if request.job.is_cancelled() {
+            return Err(NolanForgeError::Cancelled);
+        }
+
+        // whisper.cpp appends .json to the output prefix
+        let output_prefix = request.audio_path.with_extension("");
+        let output_file = output_prefix.with_extension("json");
+        let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+
+        let mut cmd = Command::new(&self.binary);
+        cmd.arg("-m")
+            .arg(&self.model)
+            .arg("-f")
+            .arg(request.audio_path)
+            .arg("-l")
+            .arg(request.language.unwrap_or("auto"))
+            .arg("-t")
+            .arg(threads.to_string())
+            .arg("--output-json-full")
+            .arg("--output-file")
+            .arg(&output_prefix)
+            .arg("--print-progress")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        println!("🔍 Debug: Running {} on {}", self.binary.display(), request.audio_path.display());
+
+        let mut child = cmd.spawn().map_err(|e| NolanForgeError::launch("whisper.cpp", e))?;
+
+        // Progress lines look like `whisper_print_progress_callback: progress =  45%`
+        let stderr = child.stderr.take().expect("stderr is piped");
+        let mut lines = BufReader::new(stderr).lines();
+        let mut log = Vec::new();
+        loop {
+            let line = tokio::select! {
+                line = lines.next_line() => line,
+                _ = request.job.cancelled() => {
+                    let _ = child.kill().await;
+                    return Err(NolanForgeError::Cancelled);
+                }
+            };
+            let Ok(Some(line)) = line else {
+                break;
+            };
+            if let Some(percent) = parse_progress(&line) {
+                (request.progress)(percent);
+            } else {
+                log.push(line);
+            }
+        }
+
+        let status = child
+            .wait()
+            .await
+            .map_err(|e| NolanForgeError::io("Failed to wait for whisper.cpp", e))?;
+        if !status.success() {
+            // The model file can vanish or be unreadable after it was checked
+            if log.iter().any(|line| line.contains("failed to load model")) {
+                return Err(NolanForgeError::WhisperModelMissing {
+                    path: self.model.display().to_string(),
+                });
+            }
+            // Keep the end of the log, where whisper.cpp explains what went wrong
+            let tail = log[log.len().saturating_sub(20)..].join("\n");
+            return Err(NolanForgeError::WhisperFailed {
+                diagnostic: format!("whisper.cpp exited with {}:\n{}", status, tail),
+            });
+        }
+
+        // Token text can split multi-byte characters, so decode leniently
+        let bytes = tokio::fs::read(&output_file)
+            .await
+            .map_err(|e| NolanForgeError::io("Failed to read whisper.cpp output", e))?;
+        let output: WhisperOutput = serde_json::from_str(&String::from_utf8_lossy(&bytes))
+            .map_err(|e| NolanForgeError::WhisperFailed {
+                diagnostic: format!("Failed to parse whisper.cpp output: {}", e),
+            })?;
+
+        let transcription = output.into_response();
+        println!("🔍 Debug: Transcription completed successfully");
+        println!("🔍 Debug: Text length: {} characters", transcription.text.len());
+
+        Ok(transcription)
+    }
+}
+
+fn parse_progress(line: &str) -> Option<f64> {
+    let (_, rest) = line.split_once("progress =")?;
+    rest.trim().trim_end_matches('%').trim().parse().ok()
+}
+
+/// The parts of whisper.cpp's `--output-json-full` file we use.
+#[derive(Debug, Deserialize)]
+struct WhisperOutput {
+    #[serde(default)]
+    result: Option<WhisperResult>,
+    transcription: Vec<WhisperSegment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperResult {
+    language: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperSegment {
+    offsets: WhisperOffsets,
+    text: String,
+    #[serde(default)]
+    tokens: Vec<WhisperToken>,
+}
+
+/// Milliseconds from the start of the audio.
+#[derive(Debug, Deserialize)]
+struct WhisperOffsets {
+    from: i64,
+    to: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhisperToken {
+    text: String,
+    offsets: WhisperOffsets,
+    id: Option<i32>,
+    p: Option<f64>,
+}
+
+impl WhisperToken {
+    /// Special tokens such as `[_BEG_]` and `[_TT_150]`.
+    fn is_special(&self) -> bool {
+        self.text.starts_with("[_") || self.text.starts_with("<|")
+    }
+}
+
+impl WhisperOutput {
+    fn into_response(self) -> TranscriptionResponse {
+        let mut words: Vec<Word> = Vec::new();
+        let mut segments = Vec::with_capacity(self.transcription.len());
+
+        for (index, segment) in self.transcription.into_iter().enumerate() {
+            let tokens: Vec<&WhisperToken> = segment.tokens.iter().filter(|t| !t.is_special()).collect();
+
+            // Tokens are word pieces; a new word starts at a leading space
+            let mut starts_word = true;
+            for token in &tokens {
+                let start = ms_to_seconds(token.offsets.from);
+                let end = ms_to_seconds(token.offsets.to);
+                match words.last_mut() {
+                    Some(word) if !starts_word && !token.text.starts_with(' ') => {
+                        word.word.push_str(&token.text);
+                        word.end = end;
+                    }
+                    _ => words.push(Word {
+                        word: token.text.trim_start().to_string(),
+                        start,
+                        end,
+                    }),
+                }
+                starts_word = false;
+            }
+
+            let probabilities: Vec<f64> = tokens.iter().filter_map(|t| t.p).filter(|p| *p > 0.0).collect();
+            let avg_logprob = if probabilities.is_empty() {
+                0.0
+            } else {
+                probabilities.iter().map(|p| p.ln()).sum::<f64>() / probabilities.len() as f64
+            };
+
+            segments.push(Segment {
+                id: index as i32,
+                seek: 0.0,
+                start: ms_to_seconds(segment.offsets.from),
+                end: ms_to_seconds(segment.offsets.to),
+                text: segment.text,
+                tokens: tokens.iter().filter_map(|t| t.id).collect(),
+                temperature: 0.0,
+                avg_logprob,
+                compression_ratio: 0.0,
+                no_speech_prob: 0.0,
+            });
+        }
+        words.retain(|word| !word.word.trim().is_empty());
+
+        let text = segments.iter().map(|s| s.text.as_str()).collect::<String>().trim().to_string();
+        let duration = segments.last().map(|s| s.end);
+
+        TranscriptionResponse {
+            text,
+            language: self.result.and_then(|r| r.language),
+            duration,
+            words: if words.is_empty() { None } else { Some(words) },
+            segments: Some(segments),
+        }
+    }
+}
+
+fn ms_to_seconds(ms: i64) -> f64 {
+    ms.max(0) as f64 / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A trimmed down `--output-json-full` file with two segments.
+    const OUTPUT: &str = r#"{
+        "result": { "language": "en" },
+        "transcription": [
+            {
+                "offsets": { "from": 0, "to": 2000 },
+                "text": " Hello world.",
+                "tokens": [
+                    { "text": "[_BEG_]", "offsets": { "from": 0, "to": 0 }, "id": 50364, "p": 0.9 },
+                    { "text": " Hel", "offsets": { "from": 0, "to": 400 }, "id": 1, "p": 0.8 },
+                    { "text": "lo", "offsets": { "from": 400, "to": 800 }, "id": 2, "p": 0.5 },
+                    { "text": " world", "offsets": { "from": 900, "to": 1500 }, "id": 3, "p": 0.9 },
+                    { "text": ".", "offsets": { "from": 1500, "to": 1600 }, "id": 4, "p": 0.6 },
+                    { "text": "[_TT_100]", "offsets": { "from": 2000, "to": 2000 }, "id": 50464, "p": 0.1 }
+                ]
+            },
+            {
+                "offsets": { "from": 2000, "to": 3000 },
+                "text": " Again",
+                "tokens": [
+                    { "text": " Again", "offsets": { "from": 2000, "to": 3000 }, "id": 5, "p": 1.0 }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn output_maps_onto_a_transcript() {
+        let output: WhisperOutput = serde_json::from_str(OUTPUT).unwrap();
+        let transcript = output.into_response();
+
+        assert_eq!(transcript.text, "Hello world. Again");
+        assert_eq!(transcript.language.as_deref(), Some("en"));
+        assert_eq!(transcript.duration, Some(3.0));
+
+        // Word pieces join up to the next leading space; special tokens are dropped
+        let words: Vec<(&str, f64, f64)> = transcript
+            .words
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|w| (w.word.as_str(), w.start, w.end))
+            .collect();
+        assert_eq!(words, vec![("Hello", 0.0, 0.8), ("world.", 0.9, 1.6), ("Again", 2.0, 3.0)]);
+
+        let segments = transcript.segments.as_ref().unwrap();
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[1].id, segments[1].start, segments[1].end), (1, 2.0, 3.0));
+        assert_eq!(segments[0].tokens, vec![1, 2, 3, 4]);
+        let expected = (0.8f64.ln() + 0.5f64.ln() + 0.9f64.ln() + 0.6f64.ln()) / 4.0;
+        assert!((segments[0].avg_logprob - expected).abs() < 1e-9);
+        assert_eq!(segments[1].avg_logprob, 0.0);
+    }
+
+    #[test]
+    fn progress_lines_are_parsed() {
+        assert_eq!(parse_progress("whisper_print_progress_callback: progress =  45%"), Some(45.0));
+        assert_eq!(parse_progress("whisper_print_progress_callback: progress = 100%"), Some(100.0));
+        assert_eq!(parse_progress("whisper_init_from_file: loading model"), None);
+    }
+}
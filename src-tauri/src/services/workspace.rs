@@ -0,0 +1,108 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::error::NolanForgeError;
+
+/// File inside every workspace recording the PID of the process that owns it.
+pub const OWNER_FILE: &str = ".owner";
+
+/// All job workspaces live below `<temp>/nolanforge`.
+fn workspaces_root() -> PathBuf {
+    std::env::temp_dir().join("nolanforge")
+}
+
+/// An isolated scratch directory for the intermediate files of one job.
+///
+/// The directory is removed when the workspace is dropped, unless
+/// intermediates are being kept for debugging.
+pub struct Workspace {
+    path: PathBuf,
+    keep: bool,
+}
+
+impl Workspace {
+    pub fn create(kind: &str, job_id: &str) -> Result<Self, NolanForgeError> {
+        let path = workspaces_root().join(format!("{}-{}", kind, job_id));
+        fs::create_dir_all(&path)
+            .map_err(|e| NolanForgeError::io("Failed to create temp directory", e))?;
+        fs::write(path.join(OWNER_FILE), std::process::id().to_string())
+            .map_err(|e| NolanForgeError::io("Failed to write workspace owner file", e))?;
+
+        Ok(Workspace {
+            path,
+            keep: Config::keep_intermediates(),
+        })
+    }
+
+    pub fn join(&self, name: impl AsRef<Path>) -> PathBuf {
+        self.path.join(name)
+    }
+}
+
+impl Drop for Workspace {
+    fn drop(&mut self) {
+        if self.keep {
+            println!("🔍 Debug: Keeping intermediates in {}", self.path.display());
+            return;
+        }
+
+        if let Err(e) = fs::remove_dir_all(&self.path) {
+            if self.path.exists() {
+                eprintln!("Warning: Failed to clean up temp directory: {}", e);
+            }
+        }
+    }
+}
+
+/// Removes workspaces left behind by runs that crashed or were killed.
+///
+/// A workspace is stale when the process recorded in its owner file is no
+/// longer running, so workspaces of another live NolanForge instance survive.
+pub fn sweep_stale_workspaces() {
+    if Config::keep_intermediates() {
+        return;
+    }
+
+    let Ok(entries) = fs::read_dir(workspaces_root()) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+
+        let owner = fs::read_to_string(path.join(OWNER_FILE))
+            .ok()
+            .and_then(|pid| pid.trim().parse::<u32>().ok());
+        let stale = match owner {
+            Some(pid) => pid != std::process::id() && !process_alive(pid),
+            None => true,
+        };
+
+        if stale {
+            println!("🔍 Debug: Removing stale workspace {}", path.display());
+            if let Err(e) = fs::remove_dir_all(&path) {
+                eprintln!("Warning: Failed to remove stale workspace {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+pub fn process_alive(pid: u32) -> bool {
+    // Signal 0 only checks whether the process exists
+    let result = unsafe { libc::kill(pid as i32, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+pub fn process_alive(pid: u32) -> bool {
+    std::process::Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+        .unwrap_or(true)
+}
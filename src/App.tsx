@@ -4,8 +4,13 @@ import Uploader from "./routes/Uploader";
 import Editor from "./routes/Editor";
 import Recorder from "./routes/Recorder";
 import Navbar from "./components/Navbar";
+import RecoveryDialog from "./components/RecoveryDialog";
+import { useProxyEvents } from "./hooks/useProxies";
+import { useAutosave } from "./hooks/useAutosave";
 
 function App() {
+  useProxyEvents();
+  useAutosave();
 
   return (
     <Router>
@@ -19,6 +24,7 @@ function App() {
             <Route path="/recorder" element={<Recorder />} />
           </Routes>
         </main>
+        <RecoveryDialog />
       </div>
     </Router>
   );
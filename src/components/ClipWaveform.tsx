@@ -0,0 +1,77 @@
+import React, { useEffect, useRef, useState } from 'react'
+import { DecodedLevel, getWaveformLevel, peaksForRange } from '../utils/waveform'
+
+interface ClipWaveformProps {
+  path: string // File on disk
+  trimStart: number
+  trimEnd: number
+  width: number // Pixels
+  height: number // Pixels
+  className?: string
+}
+
+/**
+ * Draws the waveform of the trimmed part of a clip's source
+ */
+const ClipWaveform: React.FC<ClipWaveformProps> = ({
+  path,
+  trimStart,
+  trimEnd,
+  width,
+  height,
+  className = ''
+}) => {
+  const canvasRef = useRef<HTMLCanvasElement>(null)
+  const [level, setLevel] = useState<DecodedLevel | null>(null)
+
+  const pixelsPerSecond = trimEnd > trimStart ? width / (trimEnd - trimStart) : 0
+
+  useEffect(() => {
+    if (pixelsPerSecond <= 0) return
+    let cancelled = false
+
+    getWaveformLevel(path, pixelsPerSecond)
+      .then(result => {
+        if (!cancelled) setLevel(result)
+      })
+      .catch(error => {
+        // Lanes just show no waveform
+        console.warn('⚠️ Failed to load waveform for:', path, error)
+      })
+
+    return () => {
+      cancelled = true
+    }
+  }, [path, pixelsPerSecond])
+
+  useEffect(() => {
+    const canvas = canvasRef.current
+    const ctx = canvas?.getContext('2d')
+    if (!canvas || !ctx) return
+
+    const columns = Math.max(Math.floor(width), 0)
+    canvas.width = columns
+    canvas.height = height
+    ctx.clearRect(0, 0, columns, height)
+    if (!level) return
+
+    const peaks = peaksForRange(level, trimStart, trimEnd, columns)
+    const middle = height / 2
+    ctx.fillStyle = 'rgba(255, 255, 255, 0.35)'
+    for (let x = 0; x < columns; x++) {
+      const top = middle - peaks[2 * x + 1] * middle
+      const bottom = middle - peaks[2 * x] * middle
+      ctx.fillRect(x, top, 1, Math.max(bottom - top, 1))
+    }
+  }, [level, trimStart, trimEnd, width, height])
+
+  return (
+    <canvas
+      ref={canvasRef}
+      className={`pointer-events-none ${className}`}
+      style={{ width: `${width}px`, height: `${height}px` }}
+    />
+  )
+}
+
+export default ClipWaveform
@@ -1,29 +1,91 @@
 import React from 'react'
+import { CommandError, CommandErrorCode, toCommandError } from '../utils/commandError'
+import type { PreflightReport } from '../hooks/useExport'
 
 interface ErrorDisplayProps {
-  error: string | null
+  error: string | CommandError | null
   onDismiss?: () => void
+  onRetry?: () => void
+  onOpenSettings?: () => void
   className?: string
 }
 
+// What the user can do about each kind of failure
+const RECOVERY_HINTS: Partial<Record<CommandErrorCode, string>> = {
+  ffmpeg_not_found: 'Install FFmpeg and make sure it is on your PATH, then restart NolanForge.',
+  ffmpeg_failed: 'Try a different encoding profile, or check that the source files play correctly.',
+  feature_unavailable: 'Your FFmpeg build lacks what this needs. Install a full build, or choose another FFmpeg in Settings.',
+  invalid_input: 'Check the trim range and clip settings, then export again.',
+  file_not_found: 'A source file was moved or deleted. Re-import it and try again.',
+  media_missing: 'Some source files were moved or renamed. Relink them to their new location and export again.',
+  disk_full: 'Free up disk space or choose an output folder on another drive.',
+  permission_denied: 'Choose an output folder you can write to.',
+  api_key_missing: 'Add your OpenAI API key in Settings.',
+  openai_unauthorized: 'Your OpenAI API key was rejected. Update it in Settings.',
+  openai_rate_limited: 'OpenAI is limiting requests. Wait a moment before trying again.',
+  whisper_failed: 'whisper.cpp stopped with an error. Check the details, or try another model in Settings.',
+  whisper_model_missing: 'Choose an existing ggml model file in Settings.',
+  network_error: 'Check your internet connection and try again.',
+}
+
+const SETTINGS_CODES: CommandErrorCode[] = ['api_key_missing', 'openai_unauthorized', 'whisper_model_missing']
+
 const ErrorDisplay: React.FC<ErrorDisplayProps> = ({ 
   error, 
   onDismiss, 
+  onRetry,
+  onOpenSettings,
   className = '' 
 }) => {
   if (!error) return null
 
+  const details = toCommandError(error)
+  const hint = RECOVERY_HINTS[details.code]
+  const showSettings = onOpenSettings && SETTINGS_CODES.includes(details.code)
+  const showRetry = onRetry && details.retryable
+
   return (
     <div className={`glass rounded-3xl border-2 border-red-500/50 backdrop-blur-xl p-6 bg-gradient-to-br from-red-500/10 to-red-600/5 shadow-glow animate-slide-in ${className}`}>
       <div className="flex items-start">
         <div className="flex-shrink-0 w-14 h-14 rounded-2xl bg-red-500/20 flex items-center justify-center text-3xl shadow-lg mr-4">
           ⚠️
         </div>
-        <div className="flex-1">
+        <div className="flex-1 min-w-0">
           <h4 className="text-red-300 font-bold text-lg mb-1">Error</h4>
           <p className="text-red-200 font-medium">
-            {error}
+            {details.message}
           </p>
+          {hint && (
+            <p className="text-red-300/80 text-sm mt-2">{hint}</p>
+          )}
+          {(showRetry || showSettings) && (
+            <div className="flex gap-2 mt-3">
+              {showRetry && (
+                <button
+                  onClick={onRetry}
+                  className="px-3 py-1.5 rounded-lg text-sm font-semibold bg-red-500/30 text-red-100 hover:bg-red-500/40 transition-colors"
+                >
+                  Try again
+                </button>
+              )}
+              {showSettings && (
+                <button
+                  onClick={onOpenSettings}
+                  className="px-3 py-1.5 rounded-lg text-sm font-semibold bg-red-500/30 text-red-100 hover:bg-red-500/40 transition-colors"
+                >
+                  Open Settings
+                </button>
+              )}
+            </div>
+          )}
+          {details.diagnostic && (
+            <details className="mt-3">
+              <summary className="text-red-300/80 text-xs cursor-pointer">Technical details</summary>
+              <pre className="mt-2 max-h-48 overflow-auto whitespace-pre-wrap text-xs text-red-200/80 bg-black/30 rounded-lg p-3">
+                {details.diagnostic}
+              </pre>
+            </details>
+          )}
         </div>
         {onDismiss && (
           <div className="ml-4 flex-shrink-0">
@@ -78,4 +140,62 @@ export const WarningDisplay: React.FC<WarningDisplayProps> = ({
   )
 }
 
+interface PreflightDisplayProps {
+  report: PreflightReport | null
+  onDismiss?: () => void
+  className?: string
+}
+
+// Everything pre-flight found wrong with an export, so it can all be fixed at once
+export const PreflightDisplay: React.FC<PreflightDisplayProps> = ({
+  report,
+  onDismiss,
+  className = ''
+}) => {
+  if (!report || report.errors.length === 0) return null
+
+  const issues = [...report.errors, ...report.warnings]
+
+  return (
+    <div className={`glass rounded-3xl border-2 border-red-500/50 backdrop-blur-xl p-6 bg-gradient-to-br from-red-500/10 to-red-600/5 shadow-glow animate-slide-in ${className}`}>
+      <div className="flex items-start">
+        <div className="flex-shrink-0 w-14 h-14 rounded-2xl bg-red-500/20 flex items-center justify-center text-3xl shadow-lg mr-4">
+          🚫
+        </div>
+        <div className="flex-1 min-w-0">
+          <h4 className="text-red-300 font-bold text-lg mb-3">
+            {report.errors.length === 1 ? 'Export cannot start' : `Export cannot start: ${report.errors.length} problems`}
+          </h4>
+          <ul className="space-y-2">
+            {issues.map((issue, index) => (
+              <li key={index} className="flex items-start">
+                <span className={`mr-3 mt-1 ${index < report.errors.length ? 'text-red-400' : 'text-yellow-400'}`}>•</span>
+                <span
+                  className={`font-medium flex-1 break-words ${index < report.errors.length ? 'text-red-200' : 'text-yellow-200'}`}
+                  title={issue.path ?? undefined}
+                >
+                  {issue.message}
+                </span>
+              </li>
+            ))}
+          </ul>
+        </div>
+        {onDismiss && (
+          <div className="ml-4 flex-shrink-0">
+            <button
+              onClick={onDismiss}
+              className="p-2 text-red-400 hover:text-red-300 hover:bg-red-500/20 rounded-xl transition-all duration-200 hover:scale-110"
+              title="Dismiss"
+            >
+              <svg className="h-6 w-6" viewBox="0 0 20 20" fill="currentColor">
+                <path fillRule="evenodd" d="M4.293 4.293a1 1 0 011.414 0L10 8.586l4.293-4.293a1 1 0 111.414 1.414L11.414 10l4.293 4.293a1 1 0 01-1.414 1.414L10 11.414l-4.293 4.293a1 1 0 01-1.414-1.414L8.586 10 4.293 5.707a1 1 0 010-1.414z" clipRule="evenodd" />
+              </svg>
+            </button>
+          </div>
+        )}
+      </div>
+    </div>
+  )
+}
+
 export default ErrorDisplay
@@ -1,11 +1,13 @@
 import React, { useState, useEffect, useCallback } from 'react'
 import { useNavigate } from 'react-router-dom'
-import { useMediaStore } from '../state/mediaStore'
+import { MediaFile, useMediaStore } from '../state/mediaStore'
 import { useEditState } from '../state/editState'
 import MediaListItem from './MediaListItem'
 import ConfirmationDialog from './ConfirmationDialog'
 import { TranscriptionPanel } from './TranscriptionPanel'
 import { createMediaFile, validateMultipleFiles, getErrorMessage } from '../utils/fileUtils'
+import { cancelProxyJob, requestProxy } from '../hooks/useProxies'
+import { errorMessage } from '../utils/commandError'
 
 type ViewMode = 'grid' | 'list'
 
@@ -160,6 +162,14 @@ const MediaLibrary: React.FC<MediaLibraryProps> = ({ multiTrackMode = false }) =
     setShowTranscriptionPanel(true)
   }
 
+  const handleCreateProxy = async (file: MediaFile) => {
+    try {
+      await requestProxy(file)
+    } catch (error) {
+      setError(`Failed to generate proxy: ${errorMessage(error)}`)
+    }
+  }
+
   const handleDelete = (id: string) => {
     setFileToDelete(id)
     setShowDeleteConfirm(true)
@@ -445,6 +455,8 @@ const MediaLibrary: React.FC<MediaLibraryProps> = ({ multiTrackMode = false }) =
               onPreview={handlePreview}
               onDelete={handleDelete}
               onTranscribe={handleTranscribe}
+              onCreateProxy={handleCreateProxy}
+              onCancelProxy={cancelProxyJob}
               multiTrackMode={multiTrackMode}
               onAddToTrack={handleAddToTrack}
             />
@@ -462,6 +474,8 @@ const MediaLibrary: React.FC<MediaLibraryProps> = ({ multiTrackMode = false }) =
               onPreview={handlePreview}
               onDelete={handleDelete}
               onTranscribe={handleTranscribe}
+              onCreateProxy={handleCreateProxy}
+              onCancelProxy={cancelProxyJob}
               multiTrackMode={multiTrackMode}
               onAddToTrack={handleAddToTrack}
             />
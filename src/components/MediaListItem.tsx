@@ -10,6 +10,8 @@ interface MediaListItemProps {
   onPreview?: (file: MediaFile) => void
   onDelete?: (id: string) => void
   onTranscribe?: (file: MediaFile) => void
+  onCreateProxy?: (file: MediaFile) => void
+  onCancelProxy?: (file: MediaFile) => void
   // Multi-track support
   multiTrackMode?: boolean
   onAddToTrack?: (file: MediaFile, trackId: number) => void
@@ -23,6 +25,8 @@ const MediaListItem: React.FC<MediaListItemProps> = ({
   onPreview,
   onDelete,
   onTranscribe,
+  onCreateProxy,
+  onCancelProxy,
   multiTrackMode = false,
   onAddToTrack
 }) => {
@@ -54,6 +58,15 @@ const MediaListItem: React.FC<MediaListItemProps> = ({
     onTranscribe?.(file)
   }
 
+  const handleProxy = (e: React.MouseEvent) => {
+    e.stopPropagation()
+    if (file.proxyJob) {
+      onCancelProxy?.(file)
+    } else {
+      onCreateProxy?.(file)
+    }
+  }
+
   const handleDragStart = (e: React.DragEvent) => {
     e.dataTransfer.effectAllowed = 'copy'
     e.dataTransfer.setData('mediaFileId', file.id)
@@ -121,7 +134,24 @@ const MediaListItem: React.FC<MediaListItemProps> = ({
         <div className="flex-1 min-w-0">
           <h3 className="font-bold text-white text-lg truncate mb-3">
             {file.name}
+            {file.proxyPath && (
+              <span className="ml-2 px-2 py-0.5 text-xs font-semibold text-green-300 bg-green-500/20 rounded-md align-middle" title="Playing a low-res proxy; exports use the original">
+                PROXY
+              </span>
+            )}
           </h3>
+
+          {file.proxyJob && (
+            <div className="mb-3">
+              <div className="flex justify-between text-xs text-gray-400 mb-1">
+                <span>Generating proxy…</span>
+                <span>{Math.round(file.proxyJob.progress)}%</span>
+              </div>
+              <div className="h-1.5 bg-gray-800/60 rounded-full overflow-hidden">
+                <div className="h-full bg-green-400 transition-all duration-300" style={{ width: `${file.proxyJob.progress}%` }} />
+              </div>
+            </div>
+          )}
           
           <div className="grid grid-cols-2 gap-3">
             <div className="px-3 py-2 bg-gray-800/40 rounded-xl backdrop-blur-sm">
@@ -169,6 +199,23 @@ const MediaListItem: React.FC<MediaListItemProps> = ({
                 </svg>
               </button>
             )}
+            {file.type === 'video' && file.originalPath && !file.proxyPath && (
+              <button
+                onClick={handleProxy}
+                className="p-3 text-gray-400 hover:text-yellow-400 hover:bg-yellow-500/20 rounded-xl transition-all duration-200 hover:scale-110 group/btn"
+                title={file.proxyJob ? 'Cancel proxy generation' : 'Generate proxy for smoother editing'}
+              >
+                {file.proxyJob ? (
+                  <svg className="w-5 h-5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                    <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M6 18L18 6M6 6l12 12" />
+                  </svg>
+                ) : (
+                  <svg className="w-5 h-5" fill="none" stroke="currentColor" viewBox="0 0 24 24">
+                    <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M13 10V3L4 14h7v7l9-11h-7z" />
+                  </svg>
+                )}
+              </button>
+            )}
             <button
               onClick={handleEdit}
               className="p-3 text-gray-400 hover:text-green-400 hover:bg-green-500/20 rounded-xl transition-all duration-200 hover:scale-110 group/btn"
@@ -2,6 +2,7 @@ import React, { useRef, useEffect, useState, useCallback } from 'react'
 import { MediaFile } from '../state/mediaStore'
 import { TimelineClip } from '../state/editState'
 import { VideoComposer, createTrackComposition } from '../utils/videoComposition'
+import { playbackUrl } from '../utils/proxyUtils'
 import PlaybackControls from './PlaybackControls'
 
 interface MultiTrackVideoPlayerProps {
@@ -127,8 +128,8 @@ const MultiTrackVideoPlayer: React.FC<MultiTrackVideoPlayerProps> = ({
     
     if (mainTrackClip) {
       const mediaFile = mediaFiles.find(f => f.id === mainTrackClip.mediaFileId)
-      if (mediaFile && audio.src !== mediaFile.path) {
-        audio.src = mediaFile.path
+      if (mediaFile && audio.src !== playbackUrl(mediaFile)) {
+        audio.src = playbackUrl(mediaFile)
         console.log('🔊 Loading main track audio:', mediaFile.name)
       }
       
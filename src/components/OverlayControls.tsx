@@ -1,5 +1,5 @@
 import React, { useState, useEffect } from 'react'
-import { TimelineClip } from '../state/editState'
+import { OverlayBlendMode, TimelineClip } from '../state/editState'
 
 interface OverlayControlsProps {
   clip: TimelineClip | null
@@ -11,7 +11,7 @@ interface OverlaySettings {
   position: { x: number; y: number }
   size: { width: number; height: number }
   opacity: number
-  blendMode: 'normal' | 'multiply' | 'screen' | 'overlay'
+  blendMode: OverlayBlendMode
   visible: boolean
 }
 
@@ -92,7 +92,7 @@ const OverlayControls: React.FC<OverlayControlsProps> = ({
     }
   }
 
-  const handleBlendModeChange = (blendMode: 'normal' | 'multiply' | 'screen' | 'overlay') => {
+  const handleBlendModeChange = (blendMode: OverlayBlendMode) => {
     const newSettings = { ...overlaySettings, blendMode }
     setOverlaySettings(newSettings)
     
@@ -305,8 +305,8 @@ const OverlayControls: React.FC<OverlayControlsProps> = ({
           </div>
           <div>
             <label className="text-xs text-gray-400 font-medium mb-1 block">Blend Mode</label>
-            <div className="grid grid-cols-2 gap-1">
-              {(['normal', 'multiply', 'screen', 'overlay'] as const).map((mode) => (
+            <div className="grid grid-cols-4 gap-1">
+              {(['normal', 'multiply', 'screen', 'overlay', 'add', 'darken', 'lighten', 'difference'] as const).map((mode) => (
                 <button
                   key={mode}
                   onClick={() => handleBlendModeChange(mode)}
@@ -0,0 +1,113 @@
+import React, { useEffect, useState } from 'react'
+import { useNavigate } from 'react-router-dom'
+import {
+  RecoverableSession,
+  autosaveNow,
+  discardSession,
+  listRecoverableSessions,
+  recoverSession
+} from '../utils/autosave'
+import { applyProject } from '../utils/projectFile'
+import { errorMessage } from '../utils/commandError'
+
+/**
+ * Offer to restore work from sessions that ended without saving, newest
+ * first, once at startup.
+ */
+const RecoveryDialog: React.FC = () => {
+  const navigate = useNavigate()
+  const [sessions, setSessions] = useState<RecoverableSession[]>([])
+  const [busy, setBusy] = useState(false)
+
+  useEffect(() => {
+    listRecoverableSessions()
+      .then(setSessions)
+      .catch(error => console.error('❌ Failed to list recoverable sessions:', errorMessage(error)))
+  }, [])
+
+  const session = sessions[0]
+  if (!session) return null
+
+  const next = () => setSessions(current => current.slice(1))
+
+  const handleRestore = async () => {
+    setBusy(true)
+    try {
+      const recovered = await recoverSession(session.session_id)
+      const missing = await applyProject(recovered.project, recovered.project_path, recovered.missing_media)
+      // Journal the restored state before the old session goes
+      await autosaveNow()
+      await discardSession(session.session_id)
+      setSessions([])
+      navigate('/editor')
+
+      if (missing.length > 0) {
+        alert(`⚠️ Some media files could not be found:\n${missing.map(media => media.name).join('\n')}`)
+      }
+    } catch (error) {
+      console.error('❌ Failed to restore session:', error)
+      alert(`❌ Failed to restore session: ${errorMessage(error)}`)
+    } finally {
+      setBusy(false)
+    }
+  }
+
+  const handleDiscard = async () => {
+    setBusy(true)
+    try {
+      await discardSession(session.session_id)
+    } catch (error) {
+      console.error('❌ Failed to discard session:', errorMessage(error))
+    } finally {
+      setBusy(false)
+      next()
+    }
+  }
+
+  return (
+    <div className="fixed inset-0 bg-black/70 backdrop-blur-sm flex items-center justify-center z-50 p-4 animate-fade-in">
+      <div className="glass rounded-3xl border border-yellow-500/30 backdrop-blur-xl shadow-2xl max-w-md w-full animate-scale-in">
+        <div className="p-8">
+          <div className="flex items-start mb-6">
+            <div className="w-16 h-16 rounded-2xl bg-gradient-to-br from-yellow-500/30 to-yellow-600/30 flex items-center justify-center text-4xl shadow-lg mr-4 flex-shrink-0">
+              🛟
+            </div>
+            <div className="flex-1">
+              <h3 className="text-2xl font-bold text-white mb-3">Restore unsaved work?</h3>
+              <p className="text-gray-300 text-base leading-relaxed">
+                NolanForge closed without saving <span className="font-semibold text-white">{session.project_name || 'Untitled Project'}</span>.
+              </p>
+              <p className="text-gray-400 text-sm mt-2">
+                {session.clip_count} clip{session.clip_count !== 1 ? 's' : ''} • last edited {new Date(session.updated_at).toLocaleString()}
+              </p>
+              {session.project_path && (
+                <p className="text-gray-500 text-xs mt-1 truncate" title={session.project_path}>
+                  {session.project_path}
+                </p>
+              )}
+            </div>
+          </div>
+
+          <div className="flex gap-4">
+            <button
+              onClick={handleDiscard}
+              disabled={busy}
+              className="flex-1 bg-gradient-to-r from-gray-600/20 to-gray-700/20 hover:from-gray-600/30 hover:to-gray-700/30 border border-gray-500/30 hover:border-gray-400/60 text-white font-bold py-4 px-6 rounded-2xl transition-all duration-300 disabled:opacity-50"
+            >
+              Discard
+            </button>
+            <button
+              onClick={handleRestore}
+              disabled={busy}
+              className="flex-1 bg-gradient-to-r from-yellow-500 to-yellow-600 hover:from-yellow-600 hover:to-yellow-700 text-white shadow-glow font-bold py-4 px-6 rounded-2xl transition-all duration-300 disabled:opacity-50"
+            >
+              {busy ? 'Restoring...' : 'Restore'}
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  )
+}
+
+export default RecoveryDialog
@@ -0,0 +1,174 @@
+import React, { useEffect, useState } from 'react'
+import { open } from '@tauri-apps/plugin-dialog'
+import { ProjectMediaReference } from '../utils/projectFile'
+import {
+  MATCH_KIND_LABELS,
+  RelinkSuggestion,
+  relinkMedia,
+  searchRelinkCandidates
+} from '../utils/relink'
+import { errorMessage } from '../utils/commandError'
+
+interface RelinkDialogProps {
+  missing: ProjectMediaReference[] // Empty closes the dialog
+  onClose: () => void
+  onRelinked?: () => void
+}
+
+/**
+ * Find media that moved or was renamed by searching folders the user picks,
+ * then point every reference at its new file in one go.
+ */
+const RelinkDialog: React.FC<RelinkDialogProps> = ({ missing, onClose, onRelinked }) => {
+  const [suggestions, setSuggestions] = useState<Record<string, RelinkSuggestion>>({})
+  const [chosen, setChosen] = useState<Record<string, string>>({})
+  const [searching, setSearching] = useState(false)
+  const [relinking, setRelinking] = useState(false)
+  const [error, setError] = useState<string | null>(null)
+
+  useEffect(() => {
+    setSuggestions({})
+    setChosen({})
+    setError(null)
+  }, [missing])
+
+  if (missing.length === 0) return null
+
+  const handleSearch = async () => {
+    const selected = await open({ directory: true, multiple: true, title: 'Folders to search for missing media' })
+    if (!selected) return
+    const directories = Array.isArray(selected) ? selected : [selected]
+
+    setSearching(true)
+    setError(null)
+    try {
+      // Only look for what has not been found yet
+      const unresolved = missing.filter(media => !chosen[media.id])
+      const results = await searchRelinkCandidates(unresolved, directories)
+      setSuggestions(current => {
+        const next = { ...current }
+        results.forEach(result => {
+          if (result.candidates.length > 0) next[result.media_id] = result
+        })
+        return next
+      })
+      setChosen(current => {
+        const next = { ...current }
+        results.forEach(result => {
+          if (result.candidates.length > 0) next[result.media_id] = result.candidates[0].path
+        })
+        return next
+      })
+    } catch (err) {
+      setError(errorMessage(err))
+    } finally {
+      setSearching(false)
+    }
+  }
+
+  const handleLocate = async (media: ProjectMediaReference) => {
+    const selected = await open({ multiple: false, title: `Locate ${media.name}` })
+    if (selected && !Array.isArray(selected)) {
+      setChosen(current => ({ ...current, [media.id]: selected }))
+    }
+  }
+
+  const handleRelink = async () => {
+    setRelinking(true)
+    setError(null)
+    try {
+      const relinks = Object.entries(chosen).map(([media_id, path]) => ({ media_id, path }))
+      await relinkMedia(missing, relinks)
+      onRelinked?.()
+      onClose()
+    } catch (err) {
+      setError(errorMessage(err))
+    } finally {
+      setRelinking(false)
+    }
+  }
+
+  const chosenCount = Object.keys(chosen).length
+
+  return (
+    <div className="fixed inset-0 bg-black/70 backdrop-blur-sm flex items-center justify-center z-50 p-4 animate-fade-in">
+      <div className="glass rounded-3xl border border-yellow-500/30 backdrop-blur-xl shadow-2xl max-w-2xl w-full animate-scale-in">
+        <div className="p-8">
+          <h3 className="text-2xl font-bold text-white mb-2">🔗 Relink Missing Media</h3>
+          <p className="text-gray-300 text-sm mb-6">
+            {missing.length} source file{missing.length !== 1 ? 's' : ''} could not be found. Search the folders they were
+            moved to, or locate each file yourself.
+          </p>
+
+          <div className="space-y-3 max-h-80 overflow-y-auto mb-6">
+            {missing.map(media => {
+              const suggestion = suggestions[media.id]
+              const path = chosen[media.id]
+              return (
+                <div key={media.id} className="p-4 bg-gray-800/50 border border-gray-700/30 rounded-xl">
+                  <div className="flex items-center justify-between gap-3">
+                    <div className="min-w-0">
+                      <div className="text-white font-medium truncate">{media.name}</div>
+                      <div className="text-gray-500 text-xs truncate" title={media.path}>{media.path}</div>
+                    </div>
+                    <button
+                      onClick={() => handleLocate(media)}
+                      className="flex-shrink-0 px-3 py-1.5 rounded-lg text-xs font-medium bg-gray-700/50 text-gray-300 hover:bg-gray-600/50"
+                    >
+                      Locate…
+                    </button>
+                  </div>
+                  {suggestion && suggestion.candidates.length > 1 ? (
+                    <select
+                      value={path}
+                      onChange={e => setChosen(current => ({ ...current, [media.id]: e.target.value }))}
+                      className="mt-2 w-full bg-gray-900/70 border border-gray-700/50 rounded-lg text-xs text-gray-200 p-2"
+                    >
+                      {suggestion.candidates.map(candidate => (
+                        <option key={candidate.path} value={candidate.path}>
+                          {candidate.path} ({MATCH_KIND_LABELS[candidate.matched_by]})
+                        </option>
+                      ))}
+                    </select>
+                  ) : path ? (
+                    <div className="mt-2 text-xs text-green-300 truncate" title={path}>
+                      → {path}
+                      {suggestion && ` (${MATCH_KIND_LABELS[suggestion.candidates[0].matched_by]})`}
+                    </div>
+                  ) : null}
+                </div>
+              )
+            })}
+          </div>
+
+          {error && <p className="text-red-300 text-sm mb-4">{error}</p>}
+
+          <div className="flex gap-4">
+            <button
+              onClick={onClose}
+              className="flex-1 bg-gradient-to-r from-gray-600/20 to-gray-700/20 hover:from-gray-600/30 hover:to-gray-700/30 border border-gray-500/30 text-white font-bold py-3 px-6 rounded-2xl transition-all duration-300"
+            >
+              Cancel
+            </button>
+            <button
+              onClick={handleSearch}
+              disabled={searching || relinking}
+              className="flex-1 bg-gray-700/50 border border-gray-600/30 hover:bg-gray-600/50 text-white font-bold py-3 px-6 rounded-2xl transition-all duration-300 disabled:opacity-50"
+            >
+              {searching ? 'Searching...' : '🔍 Search Folders'}
+            </button>
+            <button
+              onClick={handleRelink}
+              disabled={chosenCount === 0 || searching || relinking}
+              className="flex-1 bg-gradient-to-r from-yellow-500 to-yellow-600 hover:from-yellow-600 hover:to-yellow-700 text-white shadow-glow font-bold py-3 px-6 rounded-2xl transition-all duration-300 disabled:opacity-50"
+            >
+              {relinking ? 'Relinking...' : `Relink ${chosenCount}`}
+            </button>
+          </div>
+        </div>
+      </div>
+    </div>
+  )
+}
+
+export default RelinkDialog
@@ -1,5 +1,52 @@
 import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { open } from '@tauri-apps/plugin-dialog';
+import { errorMessage } from '../utils/commandError';
+
+// Mirrors Ffmpeg in src-tauri/src/services/toolchain.rs
+interface FfmpegInfo {
+  ffmpeg_path: string;
+  ffprobe_path: string;
+  source: 'configured' | 'sidecar' | 'app_data' | 'path';
+  version: string;
+}
+
+// Mirrors FeatureSupport and CapabilityReport in src-tauri/src/services/toolchain.rs
+interface FeatureSupport {
+  id: string;
+  name: string;
+  available: boolean;
+  missing: string[];
+}
+
+interface FfmpegCapabilities {
+  version: string;
+  minimum_version: string;
+  meets_minimum_version: boolean;
+  features: FeatureSupport[];
+  encoders: string[];
+  decoders: string[];
+  filters: string[];
+  formats: string[];
+  hwaccels: string[];
+}
+
+const FFMPEG_SOURCE_LABELS: Record<FfmpegInfo['source'], string> = {
+  configured: 'Chosen in Settings',
+  sidecar: 'Bundled with NolanForge',
+  app_data: 'NolanForge data folder',
+  path: 'System PATH',
+};
+
+// Mirrors TranscriptionSettings in src-tauri/src/config.rs
+interface TranscriptionSettings {
+  backend: 'open_ai' | 'open_ai_compatible' | 'local';
+  base_url: string | null;
+  api_key: string | null;
+  model: string | null;
+  whisper_binary: string | null;
+  whisper_model: string | null;
+}
 
 interface SettingsPanelProps {
   onClose: () => void;
@@ -9,12 +56,76 @@ export const SettingsPanel: React.FC<SettingsPanelProps> = ({ onClose }) => {
   const [apiKey, setApiKey] = useState('');
   const [isSaving, setIsSaving] = useState(false);
   const [message, setMessage] = useState<{ type: 'success' | 'error'; text: string } | null>(null);
+  const [ffmpeg, setFfmpeg] = useState<FfmpegInfo | null>(null);
+  const [ffmpegError, setFfmpegError] = useState<string | null>(null);
+  const [capabilities, setCapabilities] = useState<FfmpegCapabilities | null>(null);
+  const [transcription, setTranscription] = useState<TranscriptionSettings | null>(null);
+  const [transcriptionError, setTranscriptionError] = useState<string | null>(null);
 
   useEffect(() => {
     // Load existing API key from storage
     loadApiKey();
+    invoke<FfmpegInfo>('get_ffmpeg_info')
+      .then(setFfmpeg)
+      .catch((error) => setFfmpegError(errorMessage(error)));
+    invoke<TranscriptionSettings>('get_transcription_settings')
+      .then(setTranscription)
+      .catch((error) => setTranscriptionError(errorMessage(error)));
   }, []);
 
+  useEffect(() => {
+    if (!ffmpeg) return;
+    setCapabilities(null);
+    invoke<FfmpegCapabilities>('ffmpeg_capabilities')
+      .then(setCapabilities)
+      .catch((error) => setFfmpegError(errorMessage(error)));
+  }, [ffmpeg]);
+
+  // null goes back to finding FFmpeg automatically
+  const updateFfmpegPath = async (path: string | null) => {
+    setFfmpegError(null);
+    try {
+      setFfmpeg(await invoke<FfmpegInfo>('set_ffmpeg_path', { path }));
+    } catch (error) {
+      setFfmpegError(errorMessage(error));
+    }
+  };
+
+  const handleBrowseFfmpeg = async () => {
+    const selected = await open({ multiple: false, title: 'Choose the FFmpeg program' });
+    if (selected && !Array.isArray(selected)) {
+      await updateFfmpegPath(selected);
+    }
+  };
+
+  // Text fields save on blur, the backend picker as soon as it changes
+  const saveTranscription = async (settings: TranscriptionSettings) => {
+    setTranscription(settings);
+    setTranscriptionError(null);
+    try {
+      await invoke('save_transcription_settings', { settings });
+    } catch (error) {
+      setTranscriptionError(errorMessage(error));
+    }
+  };
+
+  const editTranscription = (field: 'base_url' | 'api_key' | 'model', value: string) => {
+    if (transcription) {
+      setTranscription({ ...transcription, [field]: value.trim() ? value : null });
+    }
+  };
+
+  const handleBrowseWhisper = async (field: 'whisper_binary' | 'whisper_model') => {
+    if (!transcription) return;
+    const selected = await open({
+      multiple: false,
+      title: field === 'whisper_binary' ? 'Choose the whisper.cpp program' : 'Choose a Whisper model (ggml .bin)',
+    });
+    if (selected && !Array.isArray(selected)) {
+      await saveTranscription({ ...transcription, [field]: selected });
+    }
+  };
+
   const loadApiKey = async () => {
     try {
       const stored = await invoke<string>('get_stored_api_key');
@@ -44,7 +155,7 @@ export const SettingsPanel: React.FC<SettingsPanelProps> = ({ onClose }) => {
         onClose();
       }, 2000);
     } catch (error) {
-      setMessage({ type: 'error', text: `Failed to save API key: ${error}` });
+      setMessage({ type: 'error', text: `Failed to save API key: ${errorMessage(error)}` });
     } finally {
       setIsSaving(false);
     }
@@ -63,7 +174,7 @@ export const SettingsPanel: React.FC<SettingsPanelProps> = ({ onClose }) => {
       await invoke('test_api_key', { apiKey: apiKey.trim() });
       setMessage({ type: 'success', text: 'API key is valid and working!' });
     } catch (error) {
-      setMessage({ type: 'error', text: `API key test failed: ${error}` });
+      setMessage({ type: 'error', text: `API key test failed: ${errorMessage(error)}` });
     } finally {
       setIsSaving(false);
     }
@@ -107,6 +218,124 @@ export const SettingsPanel: React.FC<SettingsPanelProps> = ({ onClose }) => {
               </p>
             </div>
 
+            {/* Transcription backend */}
+            {transcription && (
+              <div>
+                <label className="block text-sm font-semibold text-gray-200 mb-3">
+                  Transcription
+                </label>
+                <select
+                  value={transcription.backend}
+                  onChange={(e) => saveTranscription({ ...transcription, backend: e.target.value as TranscriptionSettings['backend'] })}
+                  className="w-full px-4 py-3 bg-gray-800/50 border border-gray-600/30 rounded-xl text-white focus:outline-none focus:ring-2 focus:ring-blue-500/50"
+                >
+                  <option value="open_ai">OpenAI Whisper API</option>
+                  <option value="open_ai_compatible">OpenAI-compatible server</option>
+                  <option value="local">whisper.cpp on this computer (offline)</option>
+                </select>
+                {transcription.backend === 'local' && (
+                  <div className="space-y-2 mt-2 text-xs">
+                    {(['whisper_binary', 'whisper_model'] as const).map((field) => (
+                      <div key={field} className="flex items-center gap-2">
+                        <div
+                          className="flex-1 p-2 bg-gray-800/50 border border-gray-600/30 rounded-xl text-gray-300 truncate"
+                          title={transcription[field] ?? undefined}
+                        >
+                          {transcription[field] ?? (field === 'whisper_binary' ? 'No whisper.cpp program chosen' : 'No model chosen')}
+                        </div>
+                        <button
+                          onClick={() => handleBrowseWhisper(field)}
+                          className="px-3 py-2 rounded-xl font-medium bg-gray-700/50 text-gray-200 hover:bg-gray-600/50 transition-colors"
+                        >
+                          Choose…
+                        </button>
+                      </div>
+                    ))}
+                    <p className="text-gray-400">Audio never leaves this computer.</p>
+                  </div>
+                )}
+                {transcription.backend === 'open_ai_compatible' && (
+                  <div className="space-y-2 mt-2">
+                    <input
+                      type="text"
+                      value={transcription.base_url ?? ''}
+                      onChange={(e) => editTranscription('base_url', e.target.value)}
+                      onBlur={() => saveTranscription(transcription)}
+                      placeholder="http://localhost:8000/v1"
+                      className="w-full px-4 py-2 bg-gray-800/50 border border-gray-600/30 rounded-xl text-sm text-white placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-500/50"
+                    />
+                    <input
+                      type="password"
+                      value={transcription.api_key ?? ''}
+                      onChange={(e) => editTranscription('api_key', e.target.value)}
+                      onBlur={() => saveTranscription(transcription)}
+                      placeholder="Server API key (optional)"
+                      className="w-full px-4 py-2 bg-gray-800/50 border border-gray-600/30 rounded-xl text-sm text-white placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-500/50"
+                    />
+                  </div>
+                )}
+                {transcription.backend !== 'local' && (
+                  <input
+                    type="text"
+                    value={transcription.model ?? ''}
+                    onChange={(e) => editTranscription('model', e.target.value)}
+                    onBlur={() => saveTranscription(transcription)}
+                    placeholder="Model (default: whisper-1)"
+                    className="w-full mt-2 px-4 py-2 bg-gray-800/50 border border-gray-600/30 rounded-xl text-sm text-white placeholder-gray-400 focus:outline-none focus:ring-2 focus:ring-blue-500/50"
+                  />
+                )}
+              </div>
+            )}
+            {transcriptionError && <p className="text-xs text-red-300">{transcriptionError}</p>}
+
+            {/* FFmpeg */}
+            <div>
+              <label className="block text-sm font-semibold text-gray-200 mb-3">
+                FFmpeg
+              </label>
+              {ffmpeg && (
+                <div className="p-3 bg-gray-800/50 border border-gray-600/30 rounded-xl text-xs space-y-1">
+                  <div className="text-gray-200 truncate" title={ffmpeg.version}>{ffmpeg.version}</div>
+                  <div className="text-gray-400 truncate" title={ffmpeg.ffmpeg_path}>{ffmpeg.ffmpeg_path}</div>
+                  <div className="text-gray-500">{FFMPEG_SOURCE_LABELS[ffmpeg.source]}</div>
+                </div>
+              )}
+              {capabilities && !capabilities.meets_minimum_version && (
+                <p className="text-xs text-yellow-300 mt-2">
+                  Exports need FFmpeg {capabilities.minimum_version} or newer.
+                </p>
+              )}
+              {capabilities && (
+                <ul className="mt-2 space-y-1 text-xs">
+                  {capabilities.features.map((feature) => (
+                    <li
+                      key={feature.id}
+                      className={feature.available ? 'text-gray-300' : 'text-gray-500'}
+                      title={feature.missing.length > 0 ? `Missing: ${feature.missing.join(', ')}` : undefined}
+                    >
+                      {feature.available ? '✅' : '⛔'} {feature.name}
+                    </li>
+                  ))}
+                </ul>
+              )}
+              {ffmpegError && <p className="text-xs text-red-300 mt-2">{ffmpegError}</p>}
+              <div className="flex gap-2 mt-3">
+                <button
+                  onClick={handleBrowseFfmpeg}
+                  className="flex-1 px-3 py-2 rounded-xl text-sm font-medium bg-gray-700/50 text-gray-200 hover:bg-gray-600/50 transition-colors"
+                >
+                  Choose…
+                </button>
+                <button
+                  onClick={() => updateFfmpegPath(null)}
+                  disabled={ffmpeg?.source !== 'configured'}
+                  className="flex-1 px-3 py-2 rounded-xl text-sm font-medium bg-gray-700/50 text-gray-200 hover:bg-gray-600/50 transition-colors disabled:opacity-50"
+                >
+                  Detect automatically
+                </button>
+              </div>
+            </div>
+
             {/* Message Display */}
             {message && (
               <div className={`p-4 rounded-xl border backdrop-blur-sm ${
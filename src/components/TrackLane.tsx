@@ -1,5 +1,7 @@
 import React, { useCallback, useRef, useState } from 'react'
 import { TimelineClip } from '../state/editState'
+import { useMediaStore } from '../state/mediaStore'
+import ClipWaveform from './ClipWaveform'
 
 interface TrackLaneProps {
   trackId: number
@@ -23,6 +25,7 @@ const TrackLane: React.FC<TrackLaneProps> = ({
   className = ''
 }) => {
   const laneRef = useRef<HTMLDivElement>(null)
+  const { getFileById } = useMediaStore()
   const [isDragging, setIsDragging] = useState(false)
   const [draggedClipId, setDraggedClipId] = useState<string | null>(null)
   const [dragStartX, setDragStartX] = useState(0)
@@ -86,6 +89,7 @@ const TrackLane: React.FC<TrackLaneProps> = ({
         const clipX = timeToPixels(clip.startTime)
         const clipWidth = timeToPixels(clip.duration)
         const isSelected = clip.id === selectedClipId
+        const sourcePath = getFileById(clip.mediaFileId)?.originalPath
         
         return (
           <div
@@ -107,8 +111,20 @@ const TrackLane: React.FC<TrackLaneProps> = ({
             onDoubleClick={() => handleDoubleClick(clip)}
             title={`${clip.id} - ${clip.duration.toFixed(1)}s`}
           >
+            {/* Waveform */}
+            {sourcePath && (
+              <ClipWaveform
+                path={sourcePath}
+                trimStart={clip.trimStart}
+                trimEnd={clip.trimEnd}
+                width={Math.max(clipWidth, 20)}
+                height={50}
+                className="absolute inset-0"
+              />
+            )}
+
             {/* Clip Content */}
-            <div className="h-full flex items-center justify-center px-2">
+            <div className="relative h-full flex items-center justify-center px-2">
               <span className="text-xs font-medium text-white truncate">
                 {clip.duration.toFixed(1)}s
               </span>
@@ -1,18 +1,25 @@
 import React, { useState } from 'react';
 import { useTranscription } from '../hooks/useTranscription';
+import { errorMessage } from '../utils/commandError';
+import { TranscriptionSelection } from '../types/transcription';
 
 interface TranscriptionPanelProps {
   onClose?: () => void;
   initialVideoPath?: string;
+  // Transcribe only part of the video instead of all of it
+  selection?: TranscriptionSelection;
 }
 
-export const TranscriptionPanel: React.FC<TranscriptionPanelProps> = ({ onClose, initialVideoPath }) => {
+export const TranscriptionPanel: React.FC<TranscriptionPanelProps> = ({ onClose, initialVideoPath, selection }) => {
   const {
     isProcessing,
     progress,
     transcript,
     error,
     transcribeVideo,
+    retryTranscription,
+    cancelTranscription,
+    jobId,
     exportTranscript,
     clearTranscript,
   } = useTranscription();
@@ -21,7 +28,15 @@ export const TranscriptionPanel: React.FC<TranscriptionPanelProps> = ({ onClose,
 
   const handleTranscribe = async () => {
     try {
-      await transcribeVideo(initialVideoPath);
+      await transcribeVideo(initialVideoPath, selection);
+    } catch (error) {
+      console.error('Transcription failed:', error);
+    }
+  };
+
+  const handleRetry = async () => {
+    try {
+      await retryTranscription();
     } catch (error) {
       console.error('Transcription failed:', error);
     }
@@ -33,7 +48,7 @@ export const TranscriptionPanel: React.FC<TranscriptionPanelProps> = ({ onClose,
       alert(`Transcript exported as ${selectedFormat.toUpperCase()} successfully!`);
     } catch (error) {
       console.error('Export failed:', error);
-      alert(`Export failed: ${error}`);
+      alert(`Export failed: ${errorMessage(error)}`);
     }
   };
 
@@ -83,6 +98,12 @@ export const TranscriptionPanel: React.FC<TranscriptionPanelProps> = ({ onClose,
               </button>
             )}
           </div>
+          {selection?.start !== undefined && selection.end !== undefined && (
+            <p className="text-sm text-gray-600 mt-2">
+              Only {selection.start.toFixed(1)}s – {selection.end.toFixed(1)}s of the source will be transcribed
+              {selection.timebase === 'timeline' ? ', timed to the timeline' : ''}.
+            </p>
+          )}
 
           {/* Progress Bar */}
           {isProcessing && (
@@ -93,14 +114,34 @@ export const TranscriptionPanel: React.FC<TranscriptionPanelProps> = ({ onClose,
                   style={{ width: `${progress}%` }}
                 />
               </div>
-              <p className="text-sm text-gray-600 mt-1">Progress: {progress}%</p>
+              <div className="flex items-center justify-between mt-1">
+                <p className="text-sm text-gray-600">Progress: {progress}%</p>
+                <button
+                  onClick={cancelTranscription}
+                  disabled={!jobId}
+                  className="px-3 py-1 bg-gray-500 text-white rounded hover:bg-gray-600 text-sm disabled:opacity-50"
+                >
+                  Cancel
+                </button>
+              </div>
             </div>
           )}
 
           {/* Error Display */}
           {error && (
             <div className="mt-4 p-4 bg-red-100 border border-red-400 text-red-700 rounded">
-              <strong>Error:</strong> {error}
+              <strong>Error:</strong> {error.message}
+              {error.retryable && (
+                <div className="mt-2">
+                  <button
+                    onClick={handleRetry}
+                    className="px-3 py-1 bg-red-600 text-white rounded hover:bg-red-700 text-sm"
+                  >
+                    Try again
+                  </button>
+                  <span className="ml-2 text-sm">Parts already transcribed will not be sent again.</span>
+                </div>
+              )}
             </div>
           )}
         </div>
@@ -197,7 +238,7 @@ export const TranscriptionPanel: React.FC<TranscriptionPanelProps> = ({ onClose,
                   <li>• Click "Start Transcription" to select a video file</li>
                 </>
               )}
-              <li>• The AI will extract audio and transcribe it using OpenAI Whisper</li>
+              <li>• The AI will extract audio and transcribe it using the backend chosen in Settings</li>
               <li>• Transcription may take a few minutes depending on video length</li>
               <li>• Export in SRT, VTT, TXT, or JSON format</li>
             </ul>
@@ -1,5 +1,6 @@
 import React, { useRef, useEffect, useState, useCallback } from 'react'
 import { MediaFile } from '../state/mediaStore'
+import { playbackUrl } from '../utils/proxyUtils'
 import PlaybackControls from './PlaybackControls'
 
 interface VideoPlayerProps {
@@ -110,8 +111,9 @@ const VideoPlayer: React.FC<VideoPlayerProps> = ({
     video.addEventListener('pause', handlePause)
     
     // Only load if src changed
-    if (video.src !== file.path) {
-      video.src = file.path
+    const src = playbackUrl(file)
+    if (video.src !== src) {
+      video.src = src
       video.load()
     } else {
       // If already loaded, just set loading to false
@@ -128,7 +130,7 @@ const VideoPlayer: React.FC<VideoPlayerProps> = ({
       video.removeEventListener('play', handlePlay)
       video.removeEventListener('pause', handlePause)
     }
-  }, [file?.id]) // Only re-run when file ID changes
+  }, [file?.id, file?.proxyPath]) // Only re-run when the file or its proxy changes
 
   // Handle time updates
   useEffect(() => {
@@ -313,8 +315,8 @@ const VideoPlayer: React.FC<VideoPlayerProps> = ({
       <div className="relative flex-1 flex items-center justify-center bg-black min-h-0">
         <video
           ref={videoRef}
-          key={file.path}
-          src={file.path}
+          key={playbackUrl(file)}
+          src={playbackUrl(file)}
           className="w-full h-full object-contain"
           preload="metadata"
         >
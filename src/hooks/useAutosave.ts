@@ -0,0 +1,56 @@
+import { useEffect } from 'react'
+import { useEditState } from '../state/editState'
+import { useTrackState } from '../state/trackState'
+import { useMediaStore } from '../state/mediaStore'
+import { useProjectState } from '../state/projectState'
+import { autosaveNow } from '../utils/autosave'
+import { errorMessage } from '../utils/commandError'
+
+// How long after the first edit the journal is written
+const AUTOSAVE_DELAY_MS = 2000
+
+/**
+ * Keep the backend autosave journal up to date with the editor. Mounted once
+ * at the app root so edits on every page are covered.
+ */
+export function useAutosave() {
+  useEffect(() => {
+    let timer: ReturnType<typeof setTimeout> | null = null
+
+    const schedule = () => {
+      // Not reset by further edits, so constant editing still gets saved
+      if (timer) return
+      timer = setTimeout(() => {
+        timer = null
+        autosaveNow().catch(error => {
+          console.error('❌ Autosave failed:', errorMessage(error))
+        })
+      }, AUTOSAVE_DELAY_MS)
+    }
+
+    const unsubscribers = [
+      // Playback updates currentTime constantly, only edits matter here
+      useEditState.subscribe((state, prev) => {
+        if (
+          state.timelineClips !== prev.timelineClips ||
+          state.globalTrimStart !== prev.globalTrimStart ||
+          state.globalTrimEnd !== prev.globalTrimEnd
+        ) {
+          schedule()
+        }
+      }),
+      useTrackState.subscribe((state, prev) => {
+        if (state.tracks !== prev.tracks) schedule()
+      }),
+      useMediaStore.subscribe((state, prev) => {
+        if (state.files !== prev.files) schedule()
+      }),
+      useProjectState.subscribe(schedule)
+    ]
+
+    return () => {
+      unsubscribers.forEach(unsubscribe => unsubscribe())
+      if (timer) clearTimeout(timer)
+    }
+  }, [])
+}
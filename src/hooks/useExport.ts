@@ -1,13 +1,47 @@
 import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
 import { save, open } from '@tauri-apps/plugin-dialog'
-import { useState, useCallback } from 'react'
+import { useState, useCallback, useEffect } from 'react'
 import { MediaFile } from '../state/mediaStore'
+import { OverlayBlendMode } from '../state/editState'
+import { CommandError, toCommandError } from '../utils/commandError'
+
+export type TrimMode = 'copy' | 'exact' | 'smart'
+
+// Output encoding settings, mirrors EncodingProfile in src-tauri/src/services/encoding.rs
+export interface EncodingProfile {
+  id: string
+  name: string
+  video_codec: string
+  preset: string | null
+  crf: number | null
+  video_bitrate: string | null
+  pixel_format: string | null
+  audio_codec: string
+  audio_bitrate: string | null
+  container: 'mp4' | 'mov' | 'mkv' | 'webm'
+  faststart: boolean
+  built_in: boolean
+}
 
 export interface ExportOptions {
   inputPath: string
   outputPath: string
   trimStart: number
   trimEnd: number
+  trimMode?: TrimMode
+  profile?: EncodingProfile
+}
+
+// In/out points a trimmed export actually achieved
+export interface TrimExportResult {
+  message: string
+  output_path: string
+  mode: TrimMode
+  requested_start: number
+  requested_end: number
+  actual_start: number
+  actual_end: number
 }
 
 export interface ClipForExport {
@@ -27,24 +61,143 @@ export interface MultiTrackClipForExport {
   overlayPosition?: { x: number; y: number }
   overlaySize?: { width: number; height: number }
   overlayOpacity?: number
-  overlayBlendMode?: string
+  overlayBlendMode?: OverlayBlendMode
+  fitMode?: FitMode
+  // Linear volume multiplier, 1 when unset
+  gain?: number
+}
+
+// Timeline model accepted by render_timeline, in the backend's field names
+export interface TimelineDocument {
+  canvas?: { width: number; height: number; frame_rate: number }
+  tracks: TimelineTrack[]
+  range?: { start: number; end: number } | null
+}
+
+export interface TimelineTrack {
+  id: number
+  muted?: boolean
+  solo?: boolean
+  clips: TimelineRenderClip[]
+}
+
+export interface TimelineRenderClip {
+  input_path: string
+  start_time: number
+  trim_start: number
+  trim_end: number
+  transform?: {
+    position?: [number, number] | null
+    size?: [number, number] | null
+    fit_mode?: FitMode
+    opacity?: number
+    blend_mode?: OverlayBlendMode
+  }
+  audio?: { gain?: number; muted?: boolean }
+}
+
+export interface TrackAudioSettings {
+  trackId: number
+  muted: boolean
+  solo: boolean
+}
+
+// How a main-track clip is placed on the project canvas
+export type FitMode = 'fit' | 'fill' | 'stretch'
+
+export interface ProjectCanvas {
+  width: number
+  height: number
+  frameRate: number
+}
+
+// Payload of the `export-progress` event emitted by the Rust export commands
+export interface ExportProgressEvent {
+  job_id: string
+  stage: string
+  percent: number
+  stage_percent: number
+  frame: number | null
+  out_time: number
+  speed: number | null
+  eta_seconds: number | null
+}
+
+// Mirrors PreflightReport in src-tauri/src/services/preflight.rs
+export interface PreflightIssue {
+  code: string
+  message: string
+  path: string | null
+}
+
+export interface PreflightReport {
+  errors: PreflightIssue[] // Any error blocks the export
+  warnings: PreflightIssue[]
+  estimated_size: number | null
+  available_space: number | null
 }
 
+// An export as validate_export accepts it, with the arguments of its command
+export type ExportRequest =
+  | { kind: 'trim'; options: object }
+  | { kind: 'multi_clip'; clips: object[]; output_path: string; profile: EncodingProfile | null }
+  | { kind: 'multi_track'; options: object }
+  | { kind: 'timeline'; options: object }
+
 export interface ExportState {
   isExporting: boolean
+  jobId: string | null
   progress: number
-  error: string | null
+  stage: string | null
+  etaSeconds: number | null
+  error: CommandError | null
   success: boolean
+  preflight: PreflightReport | null // Set when pre-flight checks stopped the export
 }
 
 export function useExport() {
   const [state, setState] = useState<ExportState>({
     isExporting: false,
+    jobId: null,
     progress: 0,
+    stage: null,
+    etaSeconds: null,
     error: null,
     success: false,
+    preflight: null,
   })
 
+  // Follow real FFmpeg progress while an export command is running
+  useEffect(() => {
+    const unlistenStarted = listen<{ job_id: string }>('export-started', event => {
+      setState(prev => prev.isExporting ? { ...prev, jobId: event.payload.job_id } : prev)
+    })
+    const unlistenProgress = listen<ExportProgressEvent>('export-progress', event => {
+      setState(prev => prev.isExporting ? {
+        ...prev,
+        jobId: event.payload.job_id,
+        progress: event.payload.percent,
+        stage: event.payload.stage,
+        etaSeconds: event.payload.eta_seconds,
+      } : prev)
+    })
+
+    return () => {
+      unlistenStarted.then(fn => fn())
+      unlistenProgress.then(fn => fn())
+    }
+  }, [])
+
+  const cancelExport = useCallback(async (): Promise<void> => {
+    if (!state.jobId) return
+
+    try {
+      await invoke<string>('cancel_export', { jobId: state.jobId })
+    } catch (error) {
+      console.error('Failed to cancel export:', error)
+    }
+  }, [state.jobId])
+
   const checkFFmpeg = useCallback(async (): Promise<boolean> => {
     try {
       const version = await invoke<string>('check_ffmpeg')
@@ -54,19 +207,42 @@ export function useExport() {
       console.error('FFmpeg check failed:', error)
       setState(prev => ({
         ...prev,
-        error: 'FFmpeg is not installed. Please install FFmpeg to export videos.',
+        error: toCommandError(error),
       }))
       return false
     }
   }, [])
 
+  // Check the whole export before FFmpeg starts; false if it should not run
+  const passesPreflight = useCallback(async (request: ExportRequest): Promise<boolean> => {
+    const report = await invoke<PreflightReport>('validate_export', { request })
+    if (report.errors.length > 0) {
+      console.warn('Export pre-flight failed:', report.errors)
+      setState(prev => ({ ...prev, isExporting: false, preflight: report }))
+      return false
+    }
+
+    if (report.warnings.length > 0) {
+      const warnings = report.warnings.map(warning => `• ${warning.message}`).join('\n')
+      if (!window.confirm(`⚠️ Before exporting:\n${warnings}\n\nExport anyway?`)) {
+        setState(prev => ({ ...prev, isExporting: false }))
+        return false
+      }
+    }
+    return true
+  }, [])
+
   const exportVideo = useCallback(async (options: ExportOptions): Promise<boolean> => {
     // Reset state
     setState({
       isExporting: true,
+      jobId: null,
       progress: 0,
+      stage: null,
+      etaSeconds: null,
       error: null,
       success: false,
+      preflight: null,
     })
 
     try {
@@ -77,42 +253,54 @@ export function useExport() {
         return false
       }
 
-      setState(prev => ({ ...prev, progress: 10 }))
+      // Convert camelCase to snake_case for Rust
+      const exportOptions = {
+        input_path: options.inputPath,
+        output_path: options.outputPath,
+        trim_start: options.trimStart,
+        trim_end: options.trimEnd,
+        trim_mode: options.trimMode ?? 'copy',
+        profile: options.profile ?? null,
+      }
+      if (!await passesPreflight({ kind: 'trim', options: exportOptions })) {
+        return false
+      }
 
       // Call Tauri command to export video
-      // Convert camelCase to snake_case for Rust
-      const result = await invoke<string>('export_trimmed_video', { 
-        options: {
-          input_path: options.inputPath,
-          output_path: options.outputPath,
-          trim_start: options.trimStart,
-          trim_end: options.trimEnd,
-        }
-      })
+      const result = await invoke<TrimExportResult>('export_trimmed_video', { options: exportOptions })
       
       setState({
         isExporting: false,
+        jobId: null,
         progress: 100,
+        stage: null,
+        etaSeconds: null,
         error: null,
         success: true,
+        preflight: null,
       })
 
       console.log('Export successful:', result)
       return true
     } catch (error) {
-      const errorMessage = error instanceof Error ? error.message : String(error)
-      console.error('Export failed:', errorMessage)
+      const commandError = toCommandError(error)
+      console.error('Export failed:', commandError.message, commandError.diagnostic)
       
       setState({
         isExporting: false,
+        jobId: null,
         progress: 0,
-        error: errorMessage,
+        stage: null,
+        etaSeconds: null,
+        // A cancelled export is not an error
+        error: commandError.code === 'cancelled' ? null : commandError,
         success: false,
+        preflight: null,
       })
       
       return false
     }
-  }, [checkFFmpeg])
+  }, [checkFFmpeg, passesPreflight])
 
   const selectInputPath = useCallback(async (): Promise<string | null> => {
     try {
@@ -137,7 +325,7 @@ export function useExport() {
         defaultPath: defaultFilename,
         filters: [{
           name: 'Video',
-          extensions: ['mp4']
+          extensions: [defaultFilename.split('.').pop() || 'mp4']
         }]
       })
       
@@ -151,19 +339,39 @@ export function useExport() {
   const reset = useCallback(() => {
     setState({
       isExporting: false,
+      jobId: null,
       progress: 0,
+      stage: null,
+      etaSeconds: null,
       error: null,
       success: false,
+      preflight: null,
     })
   }, [])
 
-  const exportMultiClipVideo = useCallback(async (clips: ClipForExport[]): Promise<boolean> => {
+  const listEncodingProfiles = useCallback(async (): Promise<EncodingProfile[]> => {
+    try {
+      return await invoke<EncodingProfile[]>('list_encoding_profiles')
+    } catch (error) {
+      console.error('Failed to load encoding profiles:', error)
+      return []
+    }
+  }, [])
+
+  const exportMultiClipVideo = useCallback(async (
+    clips: ClipForExport[],
+    profile?: EncodingProfile
+  ): Promise<boolean> => {
     // Reset state
     setState({
       isExporting: true,
+      jobId: null,
       progress: 0,
+      stage: null,
+      etaSeconds: null,
       error: null,
       success: false,
+      preflight: null,
     })
 
     try {
@@ -174,18 +382,14 @@ export function useExport() {
         return false
       }
 
-      setState(prev => ({ ...prev, progress: 10 }))
-
       // Ask where to save the output
-      const outputPath = await selectOutputPath('combined_output.mp4')
+      const outputPath = await selectOutputPath(`combined_output.${profile?.container ?? 'mp4'}`)
       
       if (!outputPath) {
         setState(prev => ({ ...prev, isExporting: false }))
         return false
       }
 
-      setState(prev => ({ ...prev, progress: 20 }))
-
       // Prepare clips data for Rust (convert to snake_case)
       const clipsData = clips.map(clip => ({
         input_path: clip.inputPath,
@@ -193,47 +397,68 @@ export function useExport() {
         trim_end: clip.trimEnd,
       }))
 
+      if (!await passesPreflight({ kind: 'multi_clip', clips: clipsData, output_path: outputPath, profile: profile ?? null })) {
+        return false
+      }
+
       // Call Tauri command to export multiple clips
       const result = await invoke<string>('export_multi_clip_video', { 
         clips: clipsData,
         outputPath: outputPath,
+        profile: profile ?? null,
       })
       
       setState({
         isExporting: false,
+        jobId: null,
         progress: 100,
+        stage: null,
+        etaSeconds: null,
         error: null,
         success: true,
+        preflight: null,
       })
 
       console.log('Multi-clip export successful:', result)
       return true
     } catch (error) {
-      const errorMessage = error instanceof Error ? error.message : String(error)
-      console.error('Multi-clip export failed:', errorMessage)
+      const commandError = toCommandError(error)
+      console.error('Multi-clip export failed:', commandError.message, commandError.diagnostic)
       
       setState({
         isExporting: false,
+        jobId: null,
         progress: 0,
-        error: errorMessage,
+        stage: null,
+        etaSeconds: null,
+        // A cancelled export is not an error
+        error: commandError.code === 'cancelled' ? null : commandError,
         success: false,
+        preflight: null,
       })
       
       return false
     }
-  }, [checkFFmpeg, selectOutputPath])
+  }, [checkFFmpeg, selectOutputPath, passesPreflight])
 
   const exportMultiTrackVideo = useCallback(async (
     clips: MultiTrackClipForExport[], 
     globalTrimStart: number, 
-    globalTrimEnd: number
+    globalTrimEnd: number,
+    profile?: EncodingProfile,
+    canvas?: ProjectCanvas,
+    trackSettings: TrackAudioSettings[] = []
   ): Promise<boolean> => {
     // Reset state
     setState({
       isExporting: true,
+      jobId: null,
       progress: 0,
+      stage: null,
+      etaSeconds: null,
       error: null,
       success: false,
+      preflight: null,
     })
 
     try {
@@ -244,18 +469,14 @@ export function useExport() {
         return false
       }
 
-      setState(prev => ({ ...prev, progress: 10 }))
-
       // Ask where to save the output
-      const outputPath = await selectOutputPath('multitrack_output.mp4')
+      const outputPath = await selectOutputPath(`multitrack_output.${profile?.container ?? 'mp4'}`)
       
       if (!outputPath) {
         setState(prev => ({ ...prev, isExporting: false }))
         return false
       }
 
-      setState(prev => ({ ...prev, progress: 20 }))
-
       // Prepare clips data for Rust (convert to snake_case)
       const clipsData = clips.map(clip => ({
         input_path: clip.inputPath,
@@ -268,50 +489,156 @@ export function useExport() {
         overlay_size: clip.overlaySize ? [clip.overlaySize.width, clip.overlaySize.height] : null,
         overlay_opacity: clip.overlayOpacity,
         overlay_blend_mode: clip.overlayBlendMode,
+        fit_mode: clip.fitMode ?? 'fit',
+        gain: clip.gain ?? null,
       }))
 
+      const exportOptions = {
+        clips: clipsData,
+        output_path: outputPath,
+        global_trim_start: globalTrimStart,
+        global_trim_end: globalTrimEnd,
+        profile: profile ?? null,
+        ...(canvas && {
+          canvas: {
+            width: canvas.width,
+            height: canvas.height,
+            frame_rate: canvas.frameRate,
+          },
+        }),
+        track_settings: trackSettings.map(track => ({
+          track_id: track.trackId,
+          muted: track.muted,
+          solo: track.solo,
+        })),
+      }
+      if (!await passesPreflight({ kind: 'multi_track', options: exportOptions })) {
+        return false
+      }
+
       // Call Tauri command to export multi-track video
-      const result = await invoke<string>('export_multi_track_video', { 
-        options: {
-          clips: clipsData,
-          output_path: outputPath,
-          global_trim_start: globalTrimStart,
-          global_trim_end: globalTrimEnd,
-        }
-      })
+      const result = await invoke<string>('export_multi_track_video', { options: exportOptions })
       
       setState({
         isExporting: false,
+        jobId: null,
         progress: 100,
+        stage: null,
+        etaSeconds: null,
         error: null,
         success: true,
+        preflight: null,
       })
 
       console.log('Multi-track export successful:', result)
       return true
     } catch (error) {
-      const errorMessage = error instanceof Error ? error.message : String(error)
-      console.error('Multi-track export failed:', errorMessage)
+      const commandError = toCommandError(error)
+      console.error('Multi-track export failed:', commandError.message, commandError.diagnostic)
       
       setState({
         isExporting: false,
+        jobId: null,
         progress: 0,
-        error: errorMessage,
+        stage: null,
+        etaSeconds: null,
+        // A cancelled export is not an error
+        error: commandError.code === 'cancelled' ? null : commandError,
         success: false,
+        preflight: null,
       })
       
       return false
     }
-  }, [checkFFmpeg, selectOutputPath])
+  }, [checkFFmpeg, selectOutputPath, passesPreflight])
+
+  const renderTimeline = useCallback(async (
+    timeline: TimelineDocument,
+    profile?: EncodingProfile
+  ): Promise<boolean> => {
+    // Reset state
+    setState({
+      isExporting: true,
+      jobId: null,
+      progress: 0,
+      stage: null,
+      etaSeconds: null,
+      error: null,
+      success: false,
+      preflight: null,
+    })
+
+    try {
+      // Check if FFmpeg is available
+      const ffmpegAvailable = await checkFFmpeg()
+      if (!ffmpegAvailable) {
+        setState(prev => ({ ...prev, isExporting: false }))
+        return false
+      }
+
+      // Ask where to save the output
+      const outputPath = await selectOutputPath(`timeline_output.${profile?.container ?? 'mp4'}`)
+
+      if (!outputPath) {
+        setState(prev => ({ ...prev, isExporting: false }))
+        return false
+      }
+
+      const renderOptions = {
+        timeline,
+        output_path: outputPath,
+        profile: profile ?? null,
+      }
+      if (!await passesPreflight({ kind: 'timeline', options: renderOptions })) {
+        return false
+      }
+
+      const result = await invoke<string>('render_timeline', { options: renderOptions })
+
+      setState({
+        isExporting: false,
+        jobId: null,
+        progress: 100,
+        stage: null,
+        etaSeconds: null,
+        error: null,
+        success: true,
+        preflight: null,
+      })
+
+      console.log('Timeline export successful:', result)
+      return true
+    } catch (error) {
+      const commandError = toCommandError(error)
+      console.error('Timeline export failed:', commandError.message, commandError.diagnostic)
+
+      setState({
+        isExporting: false,
+        jobId: null,
+        progress: 0,
+        stage: null,
+        etaSeconds: null,
+        // A cancelled export is not an error
+        error: commandError.code === 'cancelled' ? null : commandError,
+        success: false,
+        preflight: null,
+      })
+
+      return false
+    }
+  }, [checkFFmpeg, selectOutputPath, passesPreflight])
 
   return {
     ...state,
     exportVideo,
     exportMultiClipVideo,
     exportMultiTrackVideo,
+    renderTimeline,
     selectInputPath,
     selectOutputPath,
     checkFFmpeg,
+    cancelExport,
+    listEncodingProfiles,
     reset,
   }
 }
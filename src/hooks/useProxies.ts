@@ -0,0 +1,65 @@
+import { useEffect } from 'react'
+import { listen } from '@tauri-apps/api/event'
+import { MediaFile, useMediaStore } from '../state/mediaStore'
+import { ExportProgressEvent } from './useExport'
+import { cancelProxy, generateProxy, ProxyFinishedEvent } from '../utils/proxyUtils'
+import { errorMessage } from '../utils/commandError'
+
+/**
+ * Keep media files in sync with background proxy jobs. Mounted once at the
+ * app root so jobs finishing on another page are not missed.
+ */
+export function useProxyEvents() {
+  useEffect(() => {
+    const unlistenProgress = listen<ExportProgressEvent>('proxy-progress', event => {
+      const { files, updateFile } = useMediaStore.getState()
+      const file = files.find(f => f.proxyJob?.jobId === event.payload.job_id)
+      if (file) {
+        updateFile(file.id, { proxyJob: { jobId: event.payload.job_id, progress: event.payload.percent } })
+      }
+    })
+    const unlistenFinished = listen<ProxyFinishedEvent>('proxy-finished', event => {
+      const { files, updateFile } = useMediaStore.getState()
+      const { job_id, proxy, error } = event.payload
+      const file = files.find(f => f.proxyJob?.jobId === job_id)
+      if (!file) return
+
+      if (error && error.code !== 'cancelled') {
+        console.error('❌ Proxy generation failed for:', file.name, errorMessage(error))
+      }
+      updateFile(file.id, { proxyJob: undefined, proxyPath: proxy?.proxy_path ?? file.proxyPath })
+    })
+
+    return () => {
+      unlistenProgress.then(fn => fn())
+      unlistenFinished.then(fn => fn())
+    }
+  }, [])
+}
+
+/**
+ * Start generating a proxy for a file on disk
+ */
+export const requestProxy = async (file: MediaFile): Promise<void> => {
+  if (!file.originalPath || file.proxyJob) return
+  const { updateFile } = useMediaStore.getState()
+
+  const result = await generateProxy(file.originalPath)
+  if (result.status === 'ready') {
+    updateFile(file.id, { proxyPath: result.proxy.proxy_path })
+  } else {
+    updateFile(file.id, { proxyJob: { jobId: result.job_id, progress: 0 } })
+  }
+}
+
+/**
+ * Cancel a running proxy job; the file keeps playing its original
+ */
+export const cancelProxyJob = async (file: MediaFile): Promise<void> => {
+  if (!file.proxyJob) return
+  try {
+    await cancelProxy(file.proxyJob.jobId)
+  } catch (error) {
+    console.error('Failed to cancel proxy job:', error)
+  }
+}
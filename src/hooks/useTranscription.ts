@@ -1,19 +1,55 @@
-import { useState } from 'react';
+import { useEffect, useRef, useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { open, save } from '@tauri-apps/plugin-dialog';
-import { TranscriptionResponse, TranscriptionOptions, TranscriptionState } from '../types/transcription';
+import {
+  TranscriptionResponse,
+  TranscriptionOptions,
+  TranscriptionState,
+  TranscriptionProgressEvent,
+  TranscriptionSelection,
+} from '../types/transcription';
+import { toCommandError } from '../utils/commandError';
 
 export const useTranscription = () => {
   const [state, setState] = useState<TranscriptionState>({
     isProcessing: false,
+    jobId: null,
     progress: 0,
     transcript: null,
     error: null,
   });
+  // Retrying the same video resumes from the chunks the backend kept
+  const lastVideoPath = useRef<string | undefined>(undefined);
+  const lastSelection = useRef<TranscriptionSelection | undefined>(undefined);
 
-  const transcribeVideo = async (videoPath?: string) => {
+  useEffect(() => {
+    const unlistenStarted = listen<{ job_id: string }>('transcription-started', event => {
+      setState(prev => (prev.isProcessing ? { ...prev, jobId: event.payload.job_id } : prev));
+    });
+    const unlisten = listen<TranscriptionProgressEvent>('transcription-progress', event => {
+      const progress = Math.round(event.payload.percent);
+      setState(prev => (prev.isProcessing ? { ...prev, progress } : prev));
+    });
+    return () => {
+      unlistenStarted.then(fn => fn());
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  const cancelTranscription = async () => {
+    if (!state.jobId) return;
+
+    try {
+      await invoke<string>('cancel_transcription', { jobId: state.jobId });
+    } catch (error) {
+      console.error('Failed to cancel transcription:', error);
+    }
+  };
+
+  const transcribeVideo = async (videoPath?: string, selection?: TranscriptionSelection) => {
     try {
-      setState(prev => ({ ...prev, isProcessing: true, error: null, progress: 0 }));
+      setState(prev => ({ ...prev, isProcessing: true, jobId: null, error: null, progress: 0 }));
 
       // If no video path provided, open file dialog
       let selectedPath = videoPath;
@@ -37,20 +73,17 @@ export const useTranscription = () => {
       }
 
       console.log('🎬 Starting transcription for:', selectedPath);
-
-      // Update progress
-      setState(prev => ({ ...prev, progress: 25 }));
+      lastVideoPath.current = selectedPath;
+      lastSelection.current = selection;
 
       const options: TranscriptionOptions = {
         video_path: selectedPath,
         language: 'en', // Default to English, can be made configurable
         output_format: 'json',
+        ...selection,
       };
 
-      // Update progress
-      setState(prev => ({ ...prev, progress: 50 }));
-
-      // Call Tauri command
+      // Call Tauri command; progress arrives as transcription-progress events
       const transcript = await invoke<TranscriptionResponse>('transcribe_video', { options });
 
       console.log('✅ Transcription completed:', transcript);
@@ -58,6 +91,7 @@ export const useTranscription = () => {
       setState(prev => ({
         ...prev,
         isProcessing: false,
+        jobId: null,
         progress: 100,
         transcript,
         error: null,
@@ -65,16 +99,25 @@ export const useTranscription = () => {
 
       return transcript;
     } catch (error) {
+      const commandError = toCommandError(error);
+      // A cancelled transcription is not an error
+      if (commandError.code === 'cancelled') {
+        setState(prev => ({ ...prev, isProcessing: false, jobId: null, error: null }));
+        return;
+      }
       console.error('❌ Transcription failed:', error);
       setState(prev => ({
         ...prev,
         isProcessing: false,
-        error: error as string,
+        jobId: null,
+        error: commandError,
       }));
       throw error;
     }
   };
 
+  const retryTranscription = () => transcribeVideo(lastVideoPath.current, lastSelection.current);
+
   const exportTranscript = async (format: 'srt' | 'vtt' | 'txt' | 'json') => {
     if (!state.transcript) {
       throw new Error('No transcript available to export');
@@ -116,6 +159,7 @@ export const useTranscription = () => {
   const clearTranscript = () => {
     setState({
       isProcessing: false,
+      jobId: null,
       progress: 0,
       transcript: null,
       error: null,
@@ -125,6 +169,8 @@ export const useTranscription = () => {
   return {
     ...state,
     transcribeVideo,
+    retryTranscription,
+    cancelTranscription,
     exportTranscript,
     clearTranscript,
   };
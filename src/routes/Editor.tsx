@@ -10,6 +10,13 @@ import { TranscriptionPanel } from '../components/TranscriptionPanel'
 import { useMediaStore } from '../state/mediaStore'
 import { useEditState } from '../state/editState'
 import { useExport } from '../hooks/useExport'
+import ErrorDisplay, { PreflightDisplay } from '../components/ErrorDisplay'
+import { errorMessage } from '../utils/commandError'
+import { open, save } from '@tauri-apps/plugin-dialog'
+import { useProjectState } from '../state/projectState'
+import { PROJECT_EXTENSION, ProjectMediaReference, currentProjectDocument, openProject, saveCurrentProject } from '../utils/projectFile'
+import { findMissingMedia } from '../utils/relink'
+import RelinkDialog from '../components/RelinkDialog'
 
 const Editor: React.FC = () => {
   const { getFileById, files } = useMediaStore()
@@ -34,12 +41,15 @@ const Editor: React.FC = () => {
     setZoomLevel
   } = useEditState()
 
-  const { exportMultiClipVideo, exportMultiTrackVideo, isExporting, error: exportError, success } = useExport()
+  const { exportMultiClipVideo, exportMultiTrackVideo, isExporting, progress: exportProgress, stage: exportStage, cancelExport, error: exportError, preflight, success, reset: resetExport } = useExport()
+
+  const { projectPath, projectName, exportSettings } = useProjectState()
 
   const [currentDisplayClip, setCurrentDisplayClip] = useState<any>(null)
   const [localTime, setLocalTime] = useState(0)
   const [multiTrackMode, setMultiTrackMode] = useState(true)
   const [showTranscriptionPanel, setShowTranscriptionPanel] = useState(false)
+  const [missingMedia, setMissingMedia] = useState<ProjectMediaReference[]>([])
 
   // Keyboard shortcuts for zoom
   useEffect(() => {
@@ -119,10 +129,6 @@ const Editor: React.FC = () => {
     if (success) alert('✅ Video exported successfully!')
   }, [success])
 
-  useEffect(() => {
-    if (exportError) alert(`❌ Export failed:\n\n${exportError}`)
-  }, [exportError])
-
   // Update video display based on currentTime on the global timeline
   // FORCE REFRESH - Updated debugging
   useEffect(() => {
@@ -274,6 +280,14 @@ const Editor: React.FC = () => {
     }
 
     try {
+      // Find moved or renamed sources before FFmpeg does
+      const usedMediaIds = new Set(timelineClips.map(clip => clip.mediaFileId))
+      const missing = await findMissingMedia(currentProjectDocument().media.filter(media => usedMediaIds.has(media.id)))
+      if (missing.length > 0) {
+        setMissingMedia(missing)
+        return
+      }
+
       // Use global trim range (default to full timeline if not set)
       const exportStart = globalTrimStart
       const exportEnd = globalTrimEnd || totalDuration
@@ -333,7 +347,7 @@ const Editor: React.FC = () => {
         }
 
         console.log(`📤 Exporting ${clipsForExport.length} multi-track clip(s)`)
-        await exportMultiTrackVideo(clipsForExport, exportStart, exportEnd)
+        await exportMultiTrackVideo(clipsForExport, exportStart, exportEnd, exportSettings.profile ?? undefined, exportSettings.canvas)
       } else {
         // Single-track export: use existing logic
         const mainTrackClips = timelineClips
@@ -386,12 +400,46 @@ const Editor: React.FC = () => {
         }
 
         console.log(`📤 Exporting ${clipsForExport.length} clip(s)`)
-        await exportMultiClipVideo(clipsForExport)
+        await exportMultiClipVideo(clipsForExport, exportSettings.profile ?? undefined)
       }
       
     } catch (error) {
       console.error('Export error:', error)
-      alert(`❌ Export failed: ${error}`)
+      alert(`❌ Export failed: ${errorMessage(error)}`)
+    }
+  }
+  
+  const handleSaveProject = async (saveAs = false) => {
+    try {
+      let path = saveAs ? null : projectPath
+      if (!path) {
+        path = await save({
+          defaultPath: `${projectName}.${PROJECT_EXTENSION}`,
+          filters: [{ name: 'NolanForge Project', extensions: [PROJECT_EXTENSION] }]
+        })
+      }
+      if (!path) return
+
+      const savedPath = await saveCurrentProject(path)
+      console.log('💾 Saved project:', savedPath)
+    } catch (error) {
+      console.error('Save project error:', error)
+      alert(`❌ Failed to save project: ${errorMessage(error)}`)
+    }
+  }
+
+  const handleOpenProject = async () => {
+    try {
+      const path = await open({
+        multiple: false,
+        filters: [{ name: 'NolanForge Project', extensions: [PROJECT_EXTENSION] }]
+      })
+      if (!path || Array.isArray(path)) return
+
+      setMissingMedia(await openProject(path))
+    } catch (error) {
+      console.error('Open project error:', error)
+      alert(`❌ Failed to open project: ${errorMessage(error)}`)
     }
   }
   
@@ -575,8 +623,35 @@ const Editor: React.FC = () => {
               )}
             </div>
 
-            {/* Right Side: Timeline Mode Toggle and Export Button */}
+            {/* Right Side: Project, Timeline Mode Toggle and Export Button */}
             <div className="flex items-center gap-4">
+              {/* Project Buttons */}
+              <div className="flex items-center gap-2">
+                <button
+                  onClick={handleOpenProject}
+                  disabled={isExporting}
+                  className="px-4 py-2.5 rounded-xl font-medium transition-all text-sm bg-gray-700/50 text-gray-300 border border-gray-600/30 hover:bg-gray-600/50 disabled:opacity-50"
+                  title="Open a .nolanforge project"
+                >
+                  📂 Open
+                </button>
+                <button
+                  onClick={(e) => handleSaveProject(e.shiftKey)}
+                  className="px-4 py-2.5 rounded-xl font-medium transition-all text-sm bg-gray-700/50 text-gray-300 border border-gray-600/30 hover:bg-gray-600/50"
+                  title={projectPath ? `Save to ${projectPath} (Shift+click to save as)` : 'Save project'}
+                >
+                  💾 Save
+                </button>
+                <button
+                  onClick={() => setShowTranscriptionPanel(true)}
+                  disabled={!selectedClip || !getFileById(selectedClip.mediaFileId)?.originalPath}
+                  className="px-4 py-2.5 rounded-xl font-medium transition-all text-sm bg-gray-700/50 text-gray-300 border border-gray-600/30 hover:bg-gray-600/50 disabled:opacity-50"
+                  title="Transcribe the selected clip only"
+                >
+                  📝 Transcribe
+                </button>
+              </div>
+
               {/* Timeline Mode Toggle */}
               <div className="flex items-center gap-3">
                 <div className="flex items-center gap-2 text-sm text-gray-400">
@@ -620,12 +695,20 @@ const Editor: React.FC = () => {
                 {isExporting ? (
                   <>
                     <div className="w-5 h-5 border-2 border-white/30 border-t-white rounded-full animate-spin"></div>
-                    <span>Exporting...</span>
+                    <span title={exportStage ?? undefined}>Exporting... {Math.round(exportProgress)}%</span>
                   </>
                 ) : (
                   <>⬇️ Export</>
                 )}
               </button>
+              {isExporting && (
+                <button
+                  onClick={cancelExport}
+                  className="px-4 py-2.5 rounded-xl font-bold transition-all text-lg bg-red-500/20 text-red-300 border border-red-400/30 hover:bg-red-500/30"
+                >
+                  ✕ Cancel
+                </button>
+              )}
             </div>
           </div>
         </div>
@@ -634,6 +717,9 @@ const Editor: React.FC = () => {
       {/* Scrollable Content Area */}
       <div className="flex-1 overflow-y-auto overflow-x-hidden p-8">
         <div className="w-full space-y-8 max-w-full">
+          <ErrorDisplay error={exportError} onDismiss={resetExport} onRetry={handleExport} />
+          <PreflightDisplay report={preflight} onDismiss={resetExport} />
+          <RelinkDialog missing={missingMedia} onClose={() => setMissingMedia([])} />
           
           {/* Video Player */}
           <div className="glass rounded-3xl border border-gray-700/30 backdrop-blur-xl p-6 shadow-2xl">
@@ -932,8 +1018,17 @@ const Editor: React.FC = () => {
       </div>
 
       {/* Transcription Panel */}
-      {showTranscriptionPanel && (
-        <TranscriptionPanel onClose={() => setShowTranscriptionPanel(false)} />
+      {showTranscriptionPanel && selectedClip && (
+        <TranscriptionPanel
+          onClose={() => setShowTranscriptionPanel(false)}
+          initialVideoPath={getFileById(selectedClip.mediaFileId)?.originalPath}
+          selection={{
+            start: selectedClip.trimStart,
+            end: selectedClip.trimEnd,
+            timeline_start: selectedClip.startTime,
+            timebase: 'timeline',
+          }}
+        />
       )}
 
     </div>
@@ -6,6 +6,7 @@ import PreviewWebcam from '../components/PreviewWebcam'
 import ScreenCapture from '../components/ScreenCapture'
 import CombinedCapture from '../components/CombinedCapture'
 import RecorderControls from '../components/RecorderControls'
+import { errorMessage } from '../utils/commandError'
 import { downloadRecording, generateRecordingFileName, formatFileSize, saveRecordingToDisk, saveRecordingToDownloads } from '../utils/recordingUtils'
 
 const Recorder: React.FC = () => {
@@ -78,7 +79,7 @@ const Recorder: React.FC = () => {
       }
     } catch (error) {
       console.error('❌ Failed to save recording:', error)
-      alert(`❌ Failed to save recording: ${error}`)
+      alert(`❌ Failed to save recording: ${errorMessage(error)}`)
     }
   }
   
@@ -135,7 +136,7 @@ const Recorder: React.FC = () => {
       
     } catch (error) {
       console.error('❌ Failed to add recording to timeline:', error)
-      alert(`❌ Failed to add recording to timeline: ${error}`)
+      alert(`❌ Failed to add recording to timeline: ${errorMessage(error)}`)
     }
   }
   
@@ -1,6 +1,17 @@
 import { create } from 'zustand'
 import { MediaFile } from './mediaStore'
 
+// Matches the blend modes the multi-track export renders
+export type OverlayBlendMode =
+  | 'normal'
+  | 'multiply'
+  | 'screen'
+  | 'overlay'
+  | 'add'
+  | 'darken'
+  | 'lighten'
+  | 'difference'
+
 export interface TimelineClip {
   id: string // Unique clip instance ID
   mediaFileId: string // Reference to MediaFile
@@ -15,7 +26,7 @@ export interface TimelineClip {
   overlayPosition?: { x: number; y: number }
   overlaySize?: { width: number; height: number }
   overlayOpacity?: number
-  overlayBlendMode?: 'normal' | 'multiply' | 'screen' | 'overlay'
+  overlayBlendMode?: OverlayBlendMode
   overlayVisible?: boolean
 }
 
@@ -1,5 +1,6 @@
 import { create } from 'zustand'
 import { removeThumbnailFromCache } from '../utils/thumbnailUtils'
+import { MediaInfo } from '../types/media'
 
 export interface MediaFile {
   id: string
@@ -9,6 +10,10 @@ export interface MediaFile {
   size: number
   duration?: number
   thumbnail?: string
+  mediaInfo?: MediaInfo // Probed by FFmpeg when the file has a path on disk
+  proxyPath?: string // Low-res copy played back instead of the original
+  proxyJob?: { jobId: string; progress: number } // Proxy being generated
+  fingerprint?: string // Content fingerprint from the project file, used to relink the file if it moves
   type: 'video' | 'audio'
   format: string
   createdAt: Date
@@ -25,6 +30,7 @@ interface MediaStore {
   // Actions
   addFile: (file: MediaFile) => void
   removeFile: (id: string) => void
+  updateFile: (id: string, changes: Partial<MediaFile>) => void
   removeFiles: (ids: string[]) => void
   selectFile: (id: string) => void
   clearSelection: () => void
@@ -66,6 +72,12 @@ export const useMediaStore = create<MediaStore>((set, get) => ({
     })
   },
 
+  updateFile: (id: string, changes: Partial<MediaFile>) => {
+    set((state) => ({
+      files: state.files.map(file => file.id === id ? { ...file, ...changes } : file)
+    }))
+  },
+
   removeFiles: (ids: string[]) => {
     set((state) => {
       const filesToRemove = state.files.filter(file => ids.includes(file.id))
@@ -0,0 +1,48 @@
+import { create } from 'zustand'
+import { EncodingProfile, ProjectCanvas, TrimMode } from '../hooks/useExport'
+
+// How the project is exported, saved with the project file
+export interface ExportSettings {
+  profile: EncodingProfile | null // null = default profile
+  canvas: ProjectCanvas
+  trimMode: TrimMode
+}
+
+export const DEFAULT_EXPORT_SETTINGS: ExportSettings = {
+  profile: null,
+  canvas: { width: 1920, height: 1080, frameRate: 30 },
+  trimMode: 'copy'
+}
+
+interface ProjectState {
+  projectPath: string | null // .nolanforge file the project was last saved to
+  projectName: string
+  exportSettings: ExportSettings
+
+  setProjectPath: (path: string | null) => void
+  setProjectName: (name: string) => void
+  setExportSettings: (settings: Partial<ExportSettings>) => void
+  resetProject: () => void
+}
+
+export const useProjectState = create<ProjectState>((set) => ({
+  projectPath: null,
+  projectName: 'Untitled Project',
+  exportSettings: DEFAULT_EXPORT_SETTINGS,
+
+  setProjectPath: (path: string | null) => {
+    set({ projectPath: path })
+  },
+
+  setProjectName: (name: string) => {
+    set({ projectName: name })
+  },
+
+  setExportSettings: (settings: Partial<ExportSettings>) => {
+    set((state) => ({ exportSettings: { ...state.exportSettings, ...settings } }))
+  },
+
+  resetProject: () => {
+    set({ projectPath: null, projectName: 'Untitled Project', exportSettings: DEFAULT_EXPORT_SETTINGS })
+  }
+}))
@@ -36,8 +36,8 @@ export interface TrackState {
 }
 
 // Default track configuration
-const DEFAULT_TRACK_HEIGHT = 60
-const TRACK_COLORS = [
+export const DEFAULT_TRACK_HEIGHT = 60
+export const TRACK_COLORS = [
   '#3b82f6', // Blue for main track
   '#ef4444', // Red for overlay track 1
   '#10b981', // Green for overlay track 2
@@ -0,0 +1,48 @@
+// Media information returned by the probe_media command
+
+export interface StreamInfo {
+  index: number;
+  kind: 'video' | 'audio' | 'subtitle' | 'data' | 'attachment' | string;
+  codec: string | null;
+  codec_long_name: string | null;
+  duration: number | null;
+  bitrate: number | null;
+  language: string | null;
+  attached_picture: boolean;
+}
+
+export interface VideoStreamInfo {
+  index: number;
+  codec: string;
+  profile: string | null;
+  width: number;
+  height: number;
+  display_width: number;
+  display_height: number;
+  frame_rate: number | null;
+  pixel_format: string | null;
+  rotation: 0 | 90 | 180 | 270;
+  bitrate: number | null;
+}
+
+export interface AudioStreamInfo {
+  index: number;
+  codec: string;
+  channels: number;
+  channel_layout: string | null;
+  sample_rate: number | null;
+  bitrate: number | null;
+}
+
+export interface MediaInfo {
+  path: string;
+  container: string;
+  container_long_name: string | null;
+  duration: number | null;
+  bitrate: number | null;
+  size_bytes: number | null;
+  streams: StreamInfo[];
+  video: VideoStreamInfo | null;
+  audio: AudioStreamInfo | null;
+  keyframe_interval: number | null;
+}
@@ -1,3 +1,5 @@
+import { CommandError } from '../utils/commandError';
+
 export interface TranscriptionResponse {
   text: string;
   language?: string;
@@ -25,15 +27,46 @@ export interface Segment {
   no_speech_prob: number;
 }
 
+// Mirrors ClipData in src-tauri/src/commands.rs
+export interface TranscriptionClip {
+  input_path: string;
+  trim_start: number;
+  trim_end: number;
+}
+
+export type TranscriptTimebase = 'source' | 'timeline';
+
 export interface TranscriptionOptions {
   video_path: string;
   language?: string;
   output_format: 'srt' | 'vtt' | 'txt' | 'json';
+  // Only this part of video_path, in source seconds
+  start?: number;
+  end?: number;
+  // These clips back to back instead of video_path
+  clips?: TranscriptionClip[];
+  // Where the range or first clip sits on the timeline
+  timeline_start?: number;
+  timebase?: TranscriptTimebase;
+}
+
+// Which audio to transcribe and how to time the result
+export type TranscriptionSelection = Pick<
+  TranscriptionOptions,
+  'start' | 'end' | 'clips' | 'timeline_start' | 'timebase'
+>;
+
+// Mirrors TranscriptionProgress in src-tauri/src/services/transcription.rs
+export interface TranscriptionProgressEvent {
+  stage: string;
+  percent: number;
 }
 
 export interface TranscriptionState {
   isProcessing: boolean;
+  // Set once the backend has registered the job, so it can be cancelled
+  jobId: string | null;
   progress: number;
   transcript: TranscriptionResponse | null;
-  error: string | null;
+  error: CommandError | null;
 }
@@ -0,0 +1,68 @@
+import { describe, it, expect, vi } from 'vitest'
+
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn(),
+  convertFileSrc: (path: string) => `asset://${path}`
+}))
+
+import { diffProjects } from '../autosave'
+import { ProjectClip, ProjectDocument } from '../projectFile'
+
+const clip = (id: string, startTime = 0): ProjectClip => ({
+  id,
+  media_id: 'a',
+  track_id: 0,
+  start_time: startTime,
+  duration: 4,
+  trim_start: 0,
+  trim_end: 4,
+  source_duration: 10,
+  overlay_position: null,
+  overlay_size: null,
+  overlay_opacity: null,
+  overlay_blend_mode: null,
+  overlay_visible: null
+})
+
+const project = (clips: ProjectClip[]): ProjectDocument => ({
+  schema_version: 1,
+  name: 'Test',
+  media: [{ id: 'a', name: 'a.mp4', path: '/media/a.mp4', size: 1000, duration: 10, media_type: 'video' }],
+  tracks: [],
+  clips,
+  global_trim: { start: 0, end: null },
+  export_settings: {
+    profile: null,
+    canvas: { width: 1920, height: 1080, frame_rate: 30 },
+    trim_mode: 'copy'
+  }
+})
+
+describe('diffProjects', () => {
+  it('returns nothing for identical projects', () => {
+    expect(diffProjects(project([clip('c1')]), project([clip('c1')]))).toEqual([])
+  })
+
+  it('sends only the clips that changed', () => {
+    const changes = diffProjects(
+      project([clip('c1'), clip('c2'), clip('c3')]),
+      project([clip('c1'), clip('c2', 5), clip('c4')])
+    )
+
+    expect(changes).toEqual([
+      { kind: 'upsert_clips', clips: [clip('c2', 5), clip('c4')] },
+      { kind: 'remove_clips', ids: ['c3'] }
+    ])
+  })
+
+  it('sends other parts of the document whole', () => {
+    const next = { ...project([]), name: 'Renamed', global_trim: { start: 1, end: 5 } }
+    const changes = diffProjects(project([]), next, null, '/projects/test.nolanforge')
+
+    expect(changes).toEqual([
+      { kind: 'name', name: 'Renamed' },
+      { kind: 'project_path', project_path: '/projects/test.nolanforge' },
+      { kind: 'global_trim', global_trim: { start: 1, end: 5 } }
+    ])
+  })
+})
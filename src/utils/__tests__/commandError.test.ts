@@ -0,0 +1,35 @@
+import { describe, it, expect } from 'vitest'
+import { errorMessage, isCommandError, toCommandError } from '../commandError'
+
+describe('command errors', () => {
+  it('keeps structured backend errors', () => {
+    const error = {
+      code: 'ffmpeg_failed',
+      message: 'Rendering timeline failed',
+      diagnostic: 'Invalid data found when processing input',
+      retryable: false,
+    }
+
+    expect(isCommandError(error)).toBe(true)
+    expect(toCommandError(error)).toEqual(error)
+  })
+
+  it('wraps plain strings and Error objects', () => {
+    expect(toCommandError('Something broke')).toEqual({
+      code: 'unknown',
+      message: 'Something broke',
+      diagnostic: null,
+      retryable: false,
+    })
+    expect(errorMessage(new Error('Boom'))).toBe('Boom')
+  })
+
+  it('fills in missing optional fields', () => {
+    expect(toCommandError({ code: 'network_error', message: 'Network error: timeout' })).toEqual({
+      code: 'network_error',
+      message: 'Network error: timeout',
+      diagnostic: null,
+      retryable: false,
+    })
+  })
+})
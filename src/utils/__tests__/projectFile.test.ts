@@ -0,0 +1,88 @@
+import { describe, it, expect, vi } from 'vitest'
+
+vi.mock('@tauri-apps/api/core', () => ({
+  invoke: vi.fn(),
+  convertFileSrc: (path: string) => `asset://${path}`
+}))
+
+import { buildProjectDocument, toTimelineClip, toTrack } from '../projectFile'
+import { DEFAULT_EXPORT_SETTINGS } from '../../state/projectState'
+import { MediaFile } from '../../state/mediaStore'
+import { TimelineClip } from '../../state/editState'
+
+const file = (id: string, originalPath?: string): MediaFile => ({
+  id,
+  name: `${id}.mp4`,
+  path: `blob:${id}`,
+  originalPath,
+  size: 1000,
+  duration: 10,
+  type: 'video',
+  format: 'mp4',
+  createdAt: new Date(),
+  lastModified: new Date()
+})
+
+const clip = (id: string, mediaFileId: string): TimelineClip => ({
+  id,
+  mediaFileId,
+  trackId: 0,
+  startTime: 2,
+  duration: 4,
+  trimStart: 1,
+  trimEnd: 5,
+  sourceDuration: 10,
+  overlayOpacity: 0.5
+})
+
+const track = {
+  id: 0,
+  name: 'Main Track',
+  muted: false,
+  solo: false,
+  locked: false,
+  visible: true,
+  height: 60,
+  color: '#3b82f6'
+}
+
+describe('project file', () => {
+  it('leaves out media without a path on disk and its clips', () => {
+    const document = buildProjectDocument({
+      name: 'Test',
+      files: [file('a', '/media/a.mp4'), file('b')],
+      tracks: [track],
+      clips: [clip('c1', 'a'), clip('c2', 'b')],
+      globalTrimStart: 0,
+      globalTrimEnd: null,
+      exportSettings: DEFAULT_EXPORT_SETTINGS
+    })
+
+    expect(document.media.map((media) => media.id)).toEqual(['a'])
+    expect(document.media[0].path).toBe('/media/a.mp4')
+    expect(document.clips.map((c) => c.id)).toEqual(['c1'])
+    expect(document.export_settings.canvas.frame_rate).toBe(30)
+  })
+
+  it('round-trips clips and tracks', () => {
+    const document = buildProjectDocument({
+      name: 'Test',
+      files: [file('a', '/media/a.mp4')],
+      tracks: [track],
+      clips: [clip('c1', 'a')],
+      globalTrimStart: 1,
+      globalTrimEnd: 5,
+      exportSettings: DEFAULT_EXPORT_SETTINGS
+    })
+
+    expect(document.clips.map(toTimelineClip)).toEqual([clip('c1', 'a')])
+    expect(document.tracks.map(toTrack)).toEqual([track])
+    expect(document.global_trim).toEqual({ start: 1, end: 5 })
+  })
+
+  it('fills in track defaults missing from the file', () => {
+    const restored = toTrack({ ...track, id: 2, height: null, color: null })
+    expect(restored.height).toBe(60)
+    expect(restored.color).toBeTruthy()
+  })
+})
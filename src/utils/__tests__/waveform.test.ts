@@ -0,0 +1,50 @@
+import { describe, it, expect } from 'vitest'
+import { decodeLevel, peaksForRange, DecodedLevel } from '../waveform'
+
+const level = (peaksPerSecond: number, values: number[]): DecodedLevel => ({
+  peaksPerSecond,
+  peaks: Int8Array.from(values),
+})
+
+describe('waveform utilities', () => {
+  describe('decodeLevel', () => {
+    it('decodes base64 bytes as signed peaks', () => {
+      const data = btoa(String.fromCharCode(0x80, 0x7f, 0xff, 0x01))
+      const decoded = decodeLevel({ samples_per_peak: 32, peaks_per_second: 500, count: 2, data })
+
+      expect(decoded.peaksPerSecond).toBe(500)
+      expect(Array.from(decoded.peaks)).toEqual([-128, 127, -1, 1])
+    })
+  })
+
+  describe('peaksForRange', () => {
+    it('merges several peaks into one column', () => {
+      const peaks = level(4, [-10, 20, -64, 5, -1, 1, -2, 127])
+      const columns = peaksForRange(peaks, 0, 1, 1)
+
+      expect(columns[0]).toBeCloseTo(-64 / 128)
+      expect(columns[1]).toBeCloseTo(127 / 128)
+    })
+
+    it('only reads peaks inside the range', () => {
+      const peaks = level(2, [-128, 127, -64, 64, -32, 32])
+      const columns = peaksForRange(peaks, 0.5, 1.5, 2)
+
+      expect(Array.from(columns)).toEqual([-0.5, 0.5, -0.25, 0.25])
+    })
+
+    it('repeats peaks when zoomed in past the level', () => {
+      const peaks = level(1, [-64, 64])
+      const columns = peaksForRange(peaks, 0, 1, 3)
+
+      expect(Array.from(columns)).toEqual([-0.5, 0.5, -0.5, 0.5, -0.5, 0.5])
+    })
+
+    it('is silent past the end of the audio', () => {
+      const peaks = level(1, [-64, 64])
+      const columns = peaksForRange(peaks, 5, 6, 2)
+
+      expect(Array.from(columns)).toEqual([0, 0, 0, 0])
+    })
+  })
+})
@@ -0,0 +1,138 @@
+import { invoke } from '@tauri-apps/api/core'
+import { useProjectState } from '../state/projectState'
+import {
+  ProjectClip,
+  ProjectDocument,
+  ProjectMediaReference,
+  ExportSettingsDocument,
+  ProjectTrack,
+  currentProjectDocument
+} from './projectFile'
+
+// One edit in the autosave journal, mirrors ProjectChange in src-tauri/src/services/autosave.rs
+export type ProjectChange =
+  | { kind: 'name'; name: string }
+  | { kind: 'project_path'; project_path: string | null }
+  | { kind: 'media'; media: ProjectMediaReference[] }
+  | { kind: 'tracks'; tracks: ProjectTrack[] }
+  | { kind: 'upsert_clips'; clips: ProjectClip[] }
+  | { kind: 'remove_clips'; ids: string[] }
+  | { kind: 'global_trim'; global_trim: ProjectDocument['global_trim'] }
+  | { kind: 'export_settings'; export_settings: ExportSettingsDocument }
+
+export interface RecoverableSession {
+  session_id: string
+  project_name: string
+  project_path: string | null
+  updated_at: number // Milliseconds since the Unix epoch
+  clip_count: number
+  edit_count: number // Edits since the session's last snapshot
+}
+
+export interface RecoveredSession {
+  session_id: string
+  project_path: string | null
+  project: ProjectDocument
+  missing_media: string[]
+}
+
+// A full snapshot is sent at least this often, the journal covers the rest
+const SNAPSHOT_INTERVAL_MS = 5 * 60 * 1000
+
+const same = (a: unknown, b: unknown) => JSON.stringify(a) === JSON.stringify(b)
+
+/**
+ * The journal entries that turn `previous` into `next`. Clips are compared
+ * one by one, every other part of the document is sent whole when it changes.
+ */
+export const diffProjects = (
+  previous: ProjectDocument,
+  next: ProjectDocument,
+  previousPath: string | null = null,
+  nextPath: string | null = null
+): ProjectChange[] => {
+  const changes: ProjectChange[] = []
+  if (previous.name !== next.name) changes.push({ kind: 'name', name: next.name })
+  if (previousPath !== nextPath) changes.push({ kind: 'project_path', project_path: nextPath })
+  if (!same(previous.media, next.media)) changes.push({ kind: 'media', media: next.media })
+  if (!same(previous.tracks, next.tracks)) changes.push({ kind: 'tracks', tracks: next.tracks })
+
+  const previousClips = new Map(previous.clips.map((clip) => [clip.id, clip]))
+  const nextIds = new Set(next.clips.map((clip) => clip.id))
+  const upserted = next.clips.filter((clip) => !same(previousClips.get(clip.id), clip))
+  const removed = previous.clips.filter((clip) => !nextIds.has(clip.id)).map((clip) => clip.id)
+  if (upserted.length > 0) changes.push({ kind: 'upsert_clips', clips: upserted })
+  if (removed.length > 0) changes.push({ kind: 'remove_clips', ids: removed })
+
+  if (!same(previous.global_trim, next.global_trim)) {
+    changes.push({ kind: 'global_trim', global_trim: next.global_trim })
+  }
+  if (!same(previous.export_settings, next.export_settings)) {
+    changes.push({ kind: 'export_settings', export_settings: next.export_settings })
+  }
+  return changes
+}
+
+// What the backend journal holds for this run
+let baseline: { project: ProjectDocument; path: string | null } | null = null
+let sessionActive = false
+let lastSnapshotAt = 0
+
+const isEmpty = (project: ProjectDocument) => project.media.length === 0 && project.clips.length === 0
+
+// Runs are chained so a slow write never races the next one
+let queue: Promise<void> = Promise.resolve()
+
+/**
+ * Journal whatever changed since the last call, or snapshot the whole
+ * project when there is no session yet or the last snapshot is old.
+ */
+export const autosaveNow = (): Promise<void> => {
+  queue = queue.catch(() => {}).then(runAutosave)
+  return queue
+}
+
+const runAutosave = async (): Promise<void> => {
+  const project = currentProjectDocument()
+  const path = useProjectState.getState().projectPath
+
+  if (!sessionActive || Date.now() - lastSnapshotAt > SNAPSHOT_INTERVAL_MS) {
+    const unchanged = baseline ? same(baseline.project, project) && baseline.path === path : isEmpty(project)
+    // Nothing unsaved to protect
+    if (!sessionActive && unchanged) return
+
+    await invoke('autosave_snapshot', { project, projectPath: path })
+    sessionActive = true
+    lastSnapshotAt = Date.now()
+  } else {
+    const changes = diffProjects(baseline!.project, project, baseline!.path, path)
+    if (changes.length === 0) return
+    await invoke('autosave_record', { changes })
+  }
+  baseline = { project, path }
+}
+
+/**
+ * Drop the journal once the current state is safely in a project file. The
+ * saved state becomes the baseline, so a session only starts again on edit.
+ */
+export const markProjectSaved = (): Promise<void> => {
+  queue = queue.catch(() => {}).then(async () => {
+    baseline = { project: currentProjectDocument(), path: useProjectState.getState().projectPath }
+    sessionActive = false
+    await invoke('clear_autosave')
+  })
+  return queue
+}
+
+export const listRecoverableSessions = (): Promise<RecoverableSession[]> => {
+  return invoke<RecoverableSession[]>('list_recoverable_sessions')
+}
+
+export const recoverSession = (sessionId: string): Promise<RecoveredSession> => {
+  return invoke<RecoveredSession>('recover_session', { sessionId })
+}
+
+export const discardSession = (sessionId: string): Promise<void> => {
+  return invoke('discard_session', { sessionId })
+}
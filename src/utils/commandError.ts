@@ -0,0 +1,64 @@
+/**
+ * Errors returned by Tauri commands
+ */
+
+export type CommandErrorCode =
+  | 'ffmpeg_not_found'
+  | 'ffmpeg_failed'
+  | 'feature_unavailable'
+  | 'cancelled'
+  | 'invalid_input'
+  | 'file_not_found'
+  | 'media_missing'
+  | 'disk_full'
+  | 'permission_denied'
+  | 'io_error'
+  | 'api_key_missing'
+  | 'config_error'
+  | 'openai_unauthorized'
+  | 'openai_rate_limited'
+  | 'openai_failed'
+  | 'whisper_failed'
+  | 'whisper_model_missing'
+  | 'network_error'
+  | 'internal_error'
+  | 'unknown'
+
+// Shape of NolanForgeError once serialized by the backend
+export interface CommandError {
+  code: CommandErrorCode
+  message: string
+  diagnostic: string | null
+  retryable: boolean
+}
+
+export const isCommandError = (value: unknown): value is CommandError => {
+  return (
+    typeof value === 'object' &&
+    value !== null &&
+    typeof (value as CommandError).code === 'string' &&
+    typeof (value as CommandError).message === 'string'
+  )
+}
+
+/**
+ * Normalize anything thrown by `invoke` or frontend code into a CommandError
+ */
+export const toCommandError = (error: unknown): CommandError => {
+  if (isCommandError(error)) {
+    return {
+      code: error.code,
+      message: error.message,
+      diagnostic: error.diagnostic ?? null,
+      retryable: error.retryable ?? false,
+    }
+  }
+
+  const message = error instanceof Error ? error.message : String(error)
+  return { code: 'unknown', message, diagnostic: null, retryable: false }
+}
+
+/**
+ * User-facing message for anything thrown by `invoke`
+ */
+export const errorMessage = (error: unknown): string => toCommandError(error).message
@@ -1,5 +1,8 @@
 import { MediaFile } from '../state/mediaStore'
-import { generateVideoThumbnail } from './thumbnailUtils'
+import { generateNativeThumbnails, generateVideoThumbnail } from './thumbnailUtils'
+import { convertFileSrc, invoke } from '@tauri-apps/api/core'
+import { MediaInfo } from '../types/media'
+import { getProxy } from './proxyUtils'
 
 // Store the actual File objects to maintain reference
 const fileObjectCache = new Map<string, File>()
@@ -33,7 +36,96 @@ export const getFileExtension = (filename: string): string => {
   return filename.split('.').pop()?.toLowerCase() || ''
 }
 
-export const createMediaFile = (file: File): Promise<MediaFile> => {
+/**
+ * Probe a file on disk with FFmpeg. Unlike a video element this works for
+ * MKV, ProRes, HEVC and anything else FFmpeg can read.
+ */
+export const probeMedia = (path: string): Promise<MediaInfo> => {
+  return invoke<MediaInfo>('probe_media', { path })
+}
+
+export const createMediaFile = async (file: File): Promise<MediaFile> => {
+  // @ts-ignore - path property exists in Tauri but not in standard File API
+  const onDisk = Boolean(file.path || (file as any).path)
+  // Files on disk get their thumbnail from FFmpeg instead of the video element
+  const mediaFile = await createBrowserMediaFile(file, !onDisk)
+  if (!mediaFile.originalPath) return mediaFile
+  return enrichMediaFile(mediaFile, mediaFile.originalPath)
+}
+
+/**
+ * Create a media file for a path on disk, e.g. when reopening a project.
+ * Playback goes through the asset protocol instead of a blob URL.
+ */
+export const createMediaFileFromPath = async (
+  path: string,
+  details: { id?: string; name?: string; size?: number; duration?: number; type?: 'video' | 'audio' } = {}
+): Promise<MediaFile> => {
+  const name = details.name ?? path.split(/[\\/]/).pop() ?? path
+  const mediaFile: MediaFile = {
+    id: details.id ?? generateId(),
+    name,
+    path: convertFileSrc(path),
+    originalPath: path,
+    size: details.size ?? 0,
+    duration: details.duration,
+    type: details.type ?? 'video',
+    format: getFileExtension(name),
+    createdAt: new Date(),
+    lastModified: new Date()
+  }
+  return enrichMediaFile(mediaFile, path)
+}
+
+// Fill in what FFmpeg knows about a file on disk: metadata, proxy and thumbnail
+const enrichMediaFile = async (mediaFile: MediaFile, originalPath: string): Promise<MediaFile> => {
+  const fileName = mediaFile.name
+  try {
+    const info = await probeMedia(originalPath)
+    mediaFile.mediaInfo = info
+    if (info.duration) {
+      mediaFile.duration = info.duration
+    }
+    mediaFile.type = info.video ? 'video' : 'audio'
+  } catch (error) {
+    // Keep whatever the browser could tell us
+    console.warn('⚠️ Failed to probe media file:', fileName, error)
+  }
+
+  if (mediaFile.type === 'video') {
+    // Proxies persist across sessions
+    try {
+      mediaFile.proxyPath = (await getProxy(originalPath))?.proxy_path
+    } catch (error) {
+      console.warn('⚠️ Failed to look up proxy for:', fileName, error)
+    }
+
+    try {
+      const [thumbnail] = await generateNativeThumbnails(originalPath, {
+        times: [(mediaFile.duration ?? 0) * 0.1],
+        width: 320,
+        height: 180
+      })
+      mediaFile.thumbnail = thumbnail?.data_url ?? undefined
+    } catch (error) {
+      console.warn('⚠️ Failed to generate native thumbnail for:', fileName, error)
+    }
+    if (!mediaFile.thumbnail) {
+      try {
+        mediaFile.thumbnail = await generateVideoThumbnail(mediaFile.path, {
+          width: 320,
+          height: 180,
+          quality: 0.8
+        })
+      } catch (error) {
+        console.warn('⚠️ Failed to generate thumbnail for:', fileName, error)
+      }
+    }
+  }
+  return mediaFile
+}
+
+const createBrowserMediaFile = (file: File, withThumbnail: boolean): Promise<MediaFile> => {
   return new Promise((resolve) => {
     const extension = getFileExtension(file.name)
     const isVideo = file.type.startsWith('video/')
@@ -91,18 +183,20 @@ export const createMediaFile = (file: File): Promise<MediaFile> => {
           })
           
           // Generate thumbnail after metadata is loaded
-          try {
-            console.log('🎬 Generating thumbnail for:', file.name)
-            const thumbnail = await generateVideoThumbnail(blobUrl, {
-              width: 320,
-              height: 180,
-              quality: 0.8
-            })
-            mediaFile.thumbnail = thumbnail
-            console.log('✅ Thumbnail generated for:', file.name)
-          } catch (error) {
-            console.warn('⚠️ Failed to generate thumbnail for:', file.name, error)
-            // Continue without thumbnail - not a critical error
+          if (withThumbnail) {
+            try {
+              console.log('🎬 Generating thumbnail for:', file.name)
+              const thumbnail = await generateVideoThumbnail(blobUrl, {
+                width: 320,
+                height: 180,
+                quality: 0.8
+              })
+              mediaFile.thumbnail = thumbnail
+              console.log('✅ Thumbnail generated for:', file.name)
+            } catch (error) {
+              console.warn('⚠️ Failed to generate thumbnail for:', file.name, error)
+              // Continue without thumbnail - not a critical error
+            }
           }
           
           video.remove()
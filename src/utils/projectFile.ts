@@ -0,0 +1,267 @@
+import { invoke } from '@tauri-apps/api/core'
+import { MediaFile, useMediaStore } from '../state/mediaStore'
+import { OverlayBlendMode, TimelineClip, useEditState } from '../state/editState'
+import { DEFAULT_TRACK_HEIGHT, TRACK_COLORS, Track, useTrackState } from '../state/trackState'
+import { ExportSettings, useProjectState } from '../state/projectState'
+import { EncodingProfile, TrimMode } from '../hooks/useExport'
+import { createMediaFileFromPath } from './fileUtils'
+import { markProjectSaved } from './autosave'
+
+export const PROJECT_EXTENSION = 'nolanforge'
+
+// On-disk project format, mirrors ProjectDocument in src-tauri/src/services/project.rs
+export interface ProjectMediaReference {
+  id: string
+  name: string
+  path: string
+  size: number | null
+  duration: number | null
+  media_type: 'video' | 'audio'
+  fingerprint?: string | null // Filled in by the backend on save
+}
+
+export interface ProjectTrack {
+  id: number
+  name: string
+  muted: boolean
+  solo: boolean
+  locked: boolean
+  visible: boolean
+  height: number | null
+  color: string | null
+}
+
+export interface ProjectClip {
+  id: string
+  media_id: string
+  track_id: number
+  start_time: number
+  duration: number
+  trim_start: number
+  trim_end: number
+  source_duration: number
+  overlay_position: { x: number; y: number } | null
+  overlay_size: { width: number; height: number } | null
+  overlay_opacity: number | null
+  overlay_blend_mode: OverlayBlendMode | null
+  overlay_visible: boolean | null
+}
+
+export interface ProjectDocument {
+  schema_version: number
+  name: string
+  media: ProjectMediaReference[]
+  tracks: ProjectTrack[]
+  clips: ProjectClip[]
+  global_trim: { start: number; end: number | null }
+  export_settings: ExportSettingsDocument
+}
+
+export interface ExportSettingsDocument {
+  profile: EncodingProfile | null
+  canvas: { width: number; height: number; frame_rate: number }
+  trim_mode: TrimMode
+}
+
+export interface LoadedProject {
+  path: string
+  project: ProjectDocument
+  loaded_schema_version: number
+  missing_media: string[] // IDs of media whose file could not be found
+}
+
+// Written by this build; the backend migrates older files on load
+export const PROJECT_SCHEMA_VERSION = 1
+
+export interface ProjectSnapshot {
+  name: string
+  files: MediaFile[]
+  tracks: Track[]
+  clips: TimelineClip[]
+  globalTrimStart: number
+  globalTrimEnd: number | null
+  exportSettings: ExportSettings
+}
+
+/**
+ * Build the document for a project. Only media with a path on disk can be
+ * reopened, so other media and the clips using it are left out.
+ */
+export const buildProjectDocument = (snapshot: ProjectSnapshot): ProjectDocument => {
+  const media = snapshot.files.filter((file) => file.originalPath)
+  const mediaIds = new Set(media.map((file) => file.id))
+  const { canvas } = snapshot.exportSettings
+
+  return {
+    schema_version: PROJECT_SCHEMA_VERSION,
+    name: snapshot.name,
+    media: media.map((file) => ({
+      id: file.id,
+      name: file.name,
+      path: file.originalPath!,
+      size: file.size,
+      duration: file.duration ?? null,
+      media_type: file.type,
+      fingerprint: file.fingerprint ?? null
+    })),
+    tracks: snapshot.tracks.map((track) => ({
+      id: track.id,
+      name: track.name,
+      muted: track.muted,
+      solo: track.solo,
+      locked: track.locked,
+      visible: track.visible,
+      height: track.height,
+      color: track.color
+    })),
+    clips: snapshot.clips
+      .filter((clip) => mediaIds.has(clip.mediaFileId))
+      .map((clip) => ({
+        id: clip.id,
+        media_id: clip.mediaFileId,
+        track_id: clip.trackId,
+        start_time: clip.startTime,
+        duration: clip.duration,
+        trim_start: clip.trimStart,
+        trim_end: clip.trimEnd,
+        source_duration: clip.sourceDuration,
+        overlay_position: clip.overlayPosition ?? null,
+        overlay_size: clip.overlaySize ?? null,
+        overlay_opacity: clip.overlayOpacity ?? null,
+        overlay_blend_mode: clip.overlayBlendMode ?? null,
+        overlay_visible: clip.overlayVisible ?? null
+      })),
+    global_trim: { start: snapshot.globalTrimStart, end: snapshot.globalTrimEnd },
+    export_settings: {
+      profile: snapshot.exportSettings.profile,
+      canvas: { width: canvas.width, height: canvas.height, frame_rate: canvas.frameRate },
+      trim_mode: snapshot.exportSettings.trimMode
+    }
+  }
+}
+
+export const toTimelineClip = (clip: ProjectClip): TimelineClip => ({
+  id: clip.id,
+  mediaFileId: clip.media_id,
+  trackId: clip.track_id,
+  startTime: clip.start_time,
+  duration: clip.duration,
+  trimStart: clip.trim_start,
+  trimEnd: clip.trim_end,
+  sourceDuration: clip.source_duration,
+  overlayPosition: clip.overlay_position ?? undefined,
+  overlaySize: clip.overlay_size ?? undefined,
+  overlayOpacity: clip.overlay_opacity ?? undefined,
+  overlayBlendMode: clip.overlay_blend_mode ?? undefined,
+  overlayVisible: clip.overlay_visible ?? undefined
+})
+
+export const toTrack = (track: ProjectTrack): Track => ({
+  id: track.id,
+  name: track.name,
+  muted: track.muted,
+  solo: track.solo,
+  locked: track.locked,
+  visible: track.visible,
+  height: track.height ?? DEFAULT_TRACK_HEIGHT,
+  color: track.color ?? TRACK_COLORS[Math.abs(track.id) % TRACK_COLORS.length]
+})
+
+// A media file for a project's media reference
+export const mediaFileFromReference = async (media: ProjectMediaReference): Promise<MediaFile> => {
+  const file = await createMediaFileFromPath(media.path, {
+    id: media.id,
+    name: media.name,
+    size: media.size ?? undefined,
+    duration: media.duration ?? undefined,
+    type: media.media_type
+  })
+  return { ...file, fingerprint: media.fingerprint ?? undefined }
+}
+
+export const saveProject = (path: string, project: ProjectDocument): Promise<string> => {
+  return invoke<string>('save_project', { path, project })
+}
+
+export const loadProject = (path: string): Promise<LoadedProject> => {
+  return invoke<LoadedProject>('load_project', { path })
+}
+
+// The editor's current state as a project document
+export const currentProjectDocument = (): ProjectDocument => {
+  const project = useProjectState.getState()
+  const edit = useEditState.getState()
+  return buildProjectDocument({
+    name: project.projectName,
+    files: useMediaStore.getState().files,
+    tracks: useTrackState.getState().tracks,
+    clips: edit.timelineClips,
+    globalTrimStart: edit.globalTrimStart,
+    globalTrimEnd: edit.globalTrimEnd,
+    exportSettings: project.exportSettings
+  })
+}
+
+// Save the editor's current state, returning the path written
+export const saveCurrentProject = async (path: string): Promise<string> => {
+  const savedPath = await saveProject(path, currentProjectDocument())
+  useProjectState.getState().setProjectPath(savedPath)
+  await markProjectSaved()
+  return savedPath
+}
+
+/**
+ * Replace the editor's state with the project at `path`. Resolves to the
+ * media whose file could not be found.
+ */
+export const openProject = async (path: string): Promise<ProjectMediaReference[]> => {
+  const loaded = await loadProject(path)
+  console.log('📂 Opened project:', loaded.path, `(schema ${loaded.loaded_schema_version})`)
+  const missing = await applyProject(loaded.project, loaded.path, loaded.missing_media)
+  await markProjectSaved()
+  return missing
+}
+
+/**
+ * Replace the editor's state with `project`. Resolves to the references of
+ * the media listed in `missingMedia`.
+ */
+export const applyProject = async (
+  project: ProjectDocument,
+  projectPath: string | null,
+  missingMedia: string[]
+): Promise<ProjectMediaReference[]> => {
+  const missing = new Set(missingMedia)
+
+  const files = await Promise.all(project.media.map(mediaFileFromReference))
+
+  const tracks = project.tracks.map(toTrack)
+
+  useMediaStore.setState({ files, selectedFileId: null, error: null })
+  useTrackState.setState({
+    tracks,
+    selectedTrackId: tracks[0]?.id ?? 0,
+    maxTrackId: Math.max(0, ...tracks.map((track) => track.id))
+  })
+  useEditState.setState({
+    timelineClips: project.clips.map(toTimelineClip),
+    selectedClipId: null,
+    currentTime: 0,
+    isPlaying: false,
+    globalTrimStart: project.global_trim.start,
+    globalTrimEnd: project.global_trim.end
+  })
+
+  const { canvas } = project.export_settings
+  useProjectState.setState({
+    projectPath,
+    projectName: project.name,
+    exportSettings: {
+      profile: project.export_settings.profile,
+      canvas: { width: canvas.width, height: canvas.height, frameRate: canvas.frame_rate },
+      trimMode: project.export_settings.trim_mode
+    }
+  })
+
+  return project.media.filter((media) => missing.has(media.id))
+}
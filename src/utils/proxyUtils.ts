@@ -0,0 +1,53 @@
+/**
+ * Proxy media utilities. Proxies are low-res copies of heavy footage that
+ * the editor plays back instead; exports always read the originals.
+ */
+
+import { convertFileSrc, invoke } from '@tauri-apps/api/core'
+import { MediaFile } from '../state/mediaStore'
+import { CommandError } from './commandError'
+
+export interface ProxyEntry {
+  original_path: string
+  proxy_path: string
+  fingerprint: string
+  height: number
+  duration: number
+}
+
+// Result of generate_proxy: an existing proxy, or the ID of a background job
+export type ProxyRequest =
+  | { status: 'ready'; proxy: ProxyEntry }
+  | { status: 'started'; job_id: string }
+
+// Payload of the `proxy-finished` event
+export interface ProxyFinishedEvent {
+  job_id: string
+  original_path: string
+  proxy: ProxyEntry | null
+  error: CommandError | null
+}
+
+export const generateProxy = (path: string, height?: number): Promise<ProxyRequest> => {
+  return invoke<ProxyRequest>('generate_proxy', { path, height })
+}
+
+export const cancelProxy = (jobId: string): Promise<string> => {
+  return invoke<string>('cancel_proxy', { jobId })
+}
+
+export const getProxy = (path: string): Promise<ProxyEntry | null> => {
+  return invoke<ProxyEntry | null>('get_proxy', { path })
+}
+
+export const deleteProxy = (path: string): Promise<string> => {
+  return invoke<string>('delete_proxy', { path })
+}
+
+/**
+ * URL the editor should play a file from: its proxy when there is one,
+ * otherwise the original
+ */
+export const playbackUrl = (file: MediaFile): string => {
+  return file.proxyPath ? convertFileSrc(file.proxyPath) : file.path
+}
@@ -0,0 +1,65 @@
+import { invoke } from '@tauri-apps/api/core'
+import { useMediaStore } from '../state/mediaStore'
+import { ProjectMediaReference, mediaFileFromReference } from './projectFile'
+
+// Mirrors MatchKind in src-tauri/src/services/relink.rs, best first
+export type RelinkMatchKind = 'fingerprint' | 'name_and_size' | 'size_and_duration' | 'name_and_duration'
+
+export const MATCH_KIND_LABELS: Record<RelinkMatchKind, string> = {
+  fingerprint: 'Same content',
+  name_and_size: 'Same name and size',
+  size_and_duration: 'Same size and duration',
+  name_and_duration: 'Same name and duration'
+}
+
+export interface RelinkCandidate {
+  path: string
+  matched_by: RelinkMatchKind
+}
+
+export interface RelinkSuggestion {
+  media_id: string
+  original_path: string
+  candidates: RelinkCandidate[] // Best match first
+}
+
+export interface Relink {
+  media_id: string
+  path: string
+}
+
+// The references whose file no longer exists
+export const findMissingMedia = (media: ProjectMediaReference[]): Promise<ProjectMediaReference[]> => {
+  return invoke<ProjectMediaReference[]>('find_missing_media', { media })
+}
+
+export const searchRelinkCandidates = (
+  media: ProjectMediaReference[],
+  directories: string[]
+): Promise<RelinkSuggestion[]> => {
+  return invoke<RelinkSuggestion[]>('search_relink_candidates', { media, directories })
+}
+
+/**
+ * Point every relinked media file at its new path in one batch. Nothing
+ * changes if any of the new paths does not exist.
+ */
+export const relinkMedia = async (media: ProjectMediaReference[], relinks: Relink[]): Promise<void> => {
+  const updated = await invoke<ProjectMediaReference[]>('relink_media', { media, relinks })
+  const relinkedIds = new Set(relinks.map(relink => relink.media_id))
+
+  const replacements = await Promise.all(
+    updated
+      .filter(reference => relinkedIds.has(reference.id))
+      .map(reference => mediaFileFromReference({
+        ...reference,
+        name: reference.path.split(/[\\/]/).pop() ?? reference.name
+      }))
+  )
+  const byId = new Map(replacements.map(file => [file.id, file]))
+
+  useMediaStore.setState(state => ({
+    files: state.files.map(file => byId.get(file.id) ?? file)
+  }))
+  console.log(`🔗 Relinked ${replacements.length} media file(s)`)
+}
@@ -2,6 +2,8 @@
  * Thumbnail generation utilities for video files
  */
 
+import { invoke } from '@tauri-apps/api/core'
+
 // Thumbnail cache to prevent regeneration
 const thumbnailCache = new Map<string, string>()
 
@@ -164,6 +166,60 @@ export const generateVideoThumbnail = (
   })
 }
 
+export interface NativeThumbnailOptions {
+  times?: number[] // Source timestamps in seconds
+  interval?: number // Seconds between frames, used when no times are given
+  width?: number
+  height?: number // Omit to keep the source aspect ratio
+}
+
+export interface NativeThumbnail {
+  time: number
+  path: string
+  data_url: string | null
+}
+
+/**
+ * Extract frames from a file on disk with FFmpeg. Works for any codec FFmpeg
+ * can decode, and results are cached on disk across sessions.
+ */
+export const generateNativeThumbnails = (
+  path: string,
+  options: NativeThumbnailOptions
+): Promise<NativeThumbnail[]> => {
+  return invoke<NativeThumbnail[]>('generate_thumbnails', {
+    options: {
+      path,
+      times: options.times,
+      interval: options.interval,
+      width: options.width,
+      height: options.height,
+      format: 'base64'
+    }
+  })
+}
+
+/**
+ * Generate `count` evenly spaced frames across a file for a timeline
+ * filmstrip, as data URLs
+ */
+export const generateFilmstrip = async (
+  path: string,
+  duration: number,
+  count: number,
+  height: number = 60
+): Promise<string[]> => {
+  if (count <= 0 || duration <= 0) return []
+  // Sample the middle of each segment so the first frame isn't black
+  const times = Array.from({ length: count }, (_, i) => ((i + 0.5) * duration) / count)
+  const thumbnails = await generateNativeThumbnails(path, {
+    times,
+    width: Math.round((height * 16) / 9),
+    height
+  })
+  return thumbnails.map(thumbnail => thumbnail.data_url ?? '')
+}
+
 /**
  * Generate multiple thumbnails from a video (start, middle, end)
  */
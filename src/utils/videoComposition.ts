@@ -3,14 +3,15 @@
  * UPDATED: Fixed gap detection logic - force refresh
  */
 import { MediaFile } from '../state/mediaStore'
-import { TimelineClip } from '../state/editState'
+import { OverlayBlendMode, TimelineClip } from '../state/editState'
+import { playbackUrl } from './proxyUtils'
 
 export interface VideoCompositionOptions {
   width: number
   height: number
   backgroundColor?: string
   overlayOpacity?: number
-  overlayBlendMode?: 'normal' | 'multiply' | 'screen' | 'overlay'
+  overlayBlendMode?: OverlayBlendMode
 }
 
 export interface TrackComposition {
@@ -20,7 +21,7 @@ export interface TrackComposition {
   position: { x: number; y: number }
   size: { width: number; height: number }
   opacity: number
-  blendMode: 'normal' | 'multiply' | 'screen' | 'overlay'
+  blendMode: OverlayBlendMode
   visible: boolean
 }
 
@@ -85,7 +86,7 @@ export class VideoComposer {
       video.addEventListener('timeupdate', ensureMuted)
       
       // Set source after setting up event listeners
-      video.src = mediaFile.path
+      video.src = playbackUrl(mediaFile)
       
       // Add to DOM to ensure proper loading
       document.body.appendChild(video)
@@ -95,6 +96,10 @@ export class VideoComposer {
     
     // Ensure video remains muted every time we access it
     const video = this.videoElements.get(key)!
+    // Switch over once a proxy has been generated
+    if (video.src !== playbackUrl(mediaFile)) {
+      video.src = playbackUrl(mediaFile)
+    }
     video.muted = true
     video.volume = 0
     video.setAttribute('muted', 'true')
@@ -304,7 +309,8 @@ export class VideoComposer {
         
         // Set composition properties
         this.ctx.globalAlpha = track.opacity
-        this.ctx.globalCompositeOperation = track.blendMode as GlobalCompositeOperation
+        // Canvas calls additive blending 'lighter'
+        this.ctx.globalCompositeOperation = (track.blendMode === 'add' ? 'lighter' : track.blendMode) as GlobalCompositeOperation
         
         // Draw video frame
         this.ctx.drawImage(
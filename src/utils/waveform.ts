@@ -0,0 +1,101 @@
+/**
+ * Waveform peak utilities for timeline lanes
+ */
+
+import { invoke } from '@tauri-apps/api/core'
+
+/** One resolution of peaks as returned by the get_waveform_peaks command */
+export interface PeakLevel {
+  samples_per_peak: number
+  peaks_per_second: number
+  count: number
+  data: string // Base64 of interleaved min/max signed bytes
+}
+
+export interface WaveformPeaks {
+  duration: number
+  sample_rate: number
+  levels: PeakLevel[]
+}
+
+/** A decoded peak level: `peaks[2i]` is the min and `peaks[2i + 1]` the max of peak i */
+export interface DecodedLevel {
+  peaksPerSecond: number
+  peaks: Int8Array
+}
+
+// Decoded levels, keyed by path and requested resolution
+const levelCache = new Map<string, Promise<DecodedLevel | null>>()
+
+/**
+ * Fetch the peak level best suited to drawing at `pixelsPerSecond`.
+ * Resolves to null for files without audio.
+ */
+export const getWaveformLevel = (
+  path: string,
+  pixelsPerSecond: number
+): Promise<DecodedLevel | null> => {
+  // Round to a power of two so small zoom changes reuse the same level
+  const peaksPerSecond = Math.pow(2, Math.ceil(Math.log2(Math.max(pixelsPerSecond, 1))))
+  const key = `${path}_${peaksPerSecond}`
+
+  let level = levelCache.get(key)
+  if (!level) {
+    level = invoke<WaveformPeaks>('get_waveform_peaks', { path, peaksPerSecond }).then(result =>
+      result.levels.length > 0 ? decodeLevel(result.levels[0]) : null
+    )
+    // Let a failed request be retried
+    level.catch(() => levelCache.delete(key))
+    levelCache.set(key, level)
+  }
+  return level
+}
+
+/**
+ * Decode a level's base64 data into signed peaks
+ */
+export function decodeLevel(level: PeakLevel): DecodedLevel {
+  const binary = atob(level.data)
+  const peaks = new Int8Array(binary.length)
+  for (let i = 0; i < binary.length; i++) {
+    peaks[i] = (binary.charCodeAt(i) << 24) >> 24
+  }
+  return { peaksPerSecond: level.peaks_per_second, peaks }
+}
+
+/**
+ * Reduce the peaks between `start` and `end` seconds to `columns` min/max
+ * pairs, scaled to -1..1
+ * @returns Interleaved min/max values, two per column
+ */
+export function peaksForRange(
+  level: DecodedLevel,
+  start: number,
+  end: number,
+  columns: number
+): Float32Array {
+  const result = new Float32Array(Math.max(columns, 0) * 2)
+  const count = level.peaks.length / 2
+  if (columns <= 0 || end <= start || count === 0) return result
+
+  const secondsPerColumn = (end - start) / columns
+  for (let column = 0; column < columns; column++) {
+    const from = Math.floor((start + column * secondsPerColumn) * level.peaksPerSecond)
+    // Every column covers at least one peak, even when zoomed past the level
+    const to = Math.max(from + 1, Math.floor((start + (column + 1) * secondsPerColumn) * level.peaksPerSecond))
+
+    let min = 0
+    let max = 0
+    for (let i = Math.max(from, 0); i < Math.min(to, count); i++) {
+      min = Math.min(min, level.peaks[2 * i])
+      max = Math.max(max, level.peaks[2 * i + 1])
+    }
+    result[2 * column] = min / 128
+    result[2 * column + 1] = max / 128
+  }
+  return result
+}
+
+export const clearWaveformCache = (): void => {
+  levelCache.clear()
+}